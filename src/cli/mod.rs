@@ -0,0 +1,648 @@
+use clap::{Args, Parser, Subcommand};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{read_dir, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+use thiserror::Error;
+
+use rbiblio::db::{ArticleStore, Config, DbError};
+use rbiblio::dtd::{Dtd, DtdError, DtdValidator, DtdVersion};
+use rbiblio::export::delimited::{Column, DelimitedWriter};
+use rbiblio::export::ndjson::json_schema;
+use rbiblio::export::parquet::ParquetWriter;
+use rbiblio::export::{ExportError, Format};
+use rbiblio::pipeline::{Event, Events, FileProgress, Pipeline};
+use rbiblio::sync::{FileOutcome, SyncError, Synchronizer};
+use rbiblio::{ErrorContext, ErrorHandler, ErrorReport, FileSummary, ParseError};
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Export(#[from] ExportError),
+    #[error(transparent)]
+    Db(#[from] DbError),
+    #[error("invalid DTD: {0}")]
+    Dtd(#[from] DtdError),
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+    #[error("invalid glob pattern: {0}")]
+    Pattern(#[from] glob::PatternError),
+    #[error("cannot read {0}: {1}")]
+    Glob(PathBuf, std::io::Error),
+    #[error("no input files matched {0:?}")]
+    NoInputs(Vec<String>),
+}
+
+#[derive(Parser)]
+#[command(
+    name = "rbiblio",
+    version,
+    about = "Parse, export and load PubMed baseline and update files"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+pub struct InputArgs {
+    /// `.xml.gz` files, directories containing them, or glob patterns
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Number of parser threads, defaults to the number of cores
+    #[arg(short = 'j', long)]
+    threads: Option<usize>,
+    /// Write parse errors as JSON lines to this file instead of stderr
+    #[arg(long)]
+    errors: Option<PathBuf>,
+    /// Do not print progress for each finished file to stderr
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+impl InputArgs {
+    /// Starts parsing the inputs; events come back in input order. In audit
+    /// mode the file summaries count the paths the model does not map.
+    fn events(&self, audit: bool) -> Result<Events, CliError> {
+        let files = expand_inputs(&self.inputs)?;
+        let threads = self
+            .threads
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+        let mut pipeline = Pipeline::new().with_threads(threads).with_audit(audit);
+        // with fewer files than threads, decode each file on several threads
+        if files.len() < threads {
+            pipeline = pipeline
+                .with_threads(files.len())
+                .with_decoders(threads / files.len());
+        }
+        Ok(pipeline.run(files))
+    }
+
+    fn progress(&self, progress: &FileProgress) {
+        if self.quiet {
+            return;
+        }
+        let summary = &progress.summary;
+        eprintln!(
+            "{}: {} records, {} errors, {:.1} MB in {:.2}s ({:.0} records/s, {:.1} MB/s)",
+            summary.file.as_deref().unwrap_or("-"),
+            summary.records(),
+            summary.error_count(),
+            progress.bytes as f64 / 1e6,
+            progress.elapsed.as_secs_f64(),
+            progress.records_per_second(),
+            progress.megabytes_per_second()
+        );
+    }
+}
+
+#[derive(Args)]
+pub struct MongoArgs {
+    /// MongoDB connection string
+    #[arg(long, default_value = "mongodb://localhost:27017")]
+    uri: String,
+    #[arg(long, default_value = "pubmed")]
+    database: String,
+    #[arg(long, default_value = "articles")]
+    collection: String,
+    /// Articles per bulk write
+    #[arg(long, default_value_t = 1000)]
+    batch_size: usize,
+}
+
+impl MongoArgs {
+    fn config(&self) -> Config {
+        Config {
+            uri: self.uri.clone(),
+            database: self.database.clone(),
+            collection: self.collection.clone(),
+            batch_size: self.batch_size,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Parse files and print record and error counts for each file
+    Parse {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Convert records to another format
+    Export {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: Format,
+        /// Output file, `-` for stdout; a `.gz` suffix compresses the output.
+        /// For `parquet`, the directory the tables are written to
+        #[arg(short, long, default_value = "-")]
+        output: String,
+        /// Columns of `csv` and `tsv` output, as field paths such as
+        /// `article.journal.title` or `header=mesh[*].descriptor`
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<Column>,
+        /// Separator between the values of a multi-valued column
+        #[arg(long, default_value = "; ")]
+        join: String,
+    },
+    /// Print the JSON Schema of `ndjson` export records
+    Schema,
+    /// Upsert articles into MongoDB and apply deletions
+    Load {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        mongo: MongoArgs,
+    },
+    /// Print record and error totals over all files
+    Stats {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Also count the element and attribute paths the model does not
+        /// map, with example PMIDs
+        #[arg(long)]
+        audit: bool,
+    },
+    /// Check that every record can be read; exits with 1 if any cannot
+    Validate {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Also check the files against the bundled PubMed DTD of the
+        /// release named in their DOCTYPE and report each violation. The
+        /// check is a second pass that decompresses and reads every file
+        /// again
+        #[arg(long)]
+        dtd: bool,
+        /// Check against this DTD file instead of a bundled one, e.g. a
+        /// release that is not bundled; implies `--dtd`
+        #[arg(long)]
+        dtd_file: Option<PathBuf>,
+        /// Also report the element and attribute paths the model does not
+        /// map, and fail if there are any
+        #[arg(long)]
+        audit: bool,
+    },
+    /// Apply new baseline and update files of a local mirror to MongoDB
+    Sync {
+        /// Directory containing `baseline/` and `updatefiles/`
+        mirror: PathBuf,
+        #[command(flatten)]
+        mongo: MongoArgs,
+        /// Collection that records the applied files
+        #[arg(long, default_value = "sync_state")]
+        state_collection: String,
+        /// Write parse errors as JSON lines to this file instead of stderr
+        #[arg(long)]
+        errors: Option<PathBuf>,
+    },
+}
+
+pub fn run(cli: Cli) -> Result<ExitCode, CliError> {
+    match cli.command {
+        Command::Parse { input } => parse(&input),
+        Command::Export {
+            input,
+            format,
+            output,
+            fields,
+            join,
+        } => export(&input, format, &output, fields, &join),
+        Command::Schema => schema(),
+        Command::Load { input, mongo } => load(&input, &mongo),
+        Command::Stats { input, audit } => stats(&input, audit),
+        Command::Validate {
+            input,
+            dtd,
+            dtd_file,
+            audit,
+        } => validate(&input, dtd, dtd_file.as_deref(), audit),
+        Command::Sync {
+            mirror,
+            mongo,
+            state_collection,
+            errors,
+        } => sync(&mirror, &mongo, &state_collection, errors.as_deref()),
+    }
+}
+
+fn is_input_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with(".xml.gz"))
+}
+
+/// Expands directories and glob patterns into a sorted list of files.
+pub fn expand_inputs(inputs: &[String]) -> Result<Vec<PathBuf>, CliError> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            let mut found = Vec::new();
+            for entry in read_dir(path)? {
+                let path = entry?.path();
+                if is_input_file(&path) {
+                    found.push(path);
+                }
+            }
+            found.sort();
+            files.extend(found);
+        } else if input.contains(['*', '?', '[']) {
+            for entry in glob::glob(input)? {
+                let path = entry.map_err(|e| CliError::Glob(e.path().to_path_buf(), e.into()))?;
+                if is_input_file(&path) {
+                    files.push(path);
+                }
+            }
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+    if files.is_empty() {
+        return Err(CliError::NoInputs(inputs.to_vec()));
+    }
+    Ok(files)
+}
+
+type Report = ErrorReport<Box<dyn Write + Send>>;
+
+fn report_file(path: &Path) -> Result<Report, CliError> {
+    Ok(ErrorReport::new(Box::new(BufWriter::new(File::create(
+        path,
+    )?))))
+}
+
+/// Where parse errors go: a JSONL report or plain lines on stderr.
+enum ErrorSink {
+    Report(Mutex<Report>),
+    Stderr,
+}
+
+impl ErrorSink {
+    fn new(path: Option<&Path>) -> Result<ErrorSink, CliError> {
+        Ok(match path {
+            Some(path) => ErrorSink::Report(Mutex::new(report_file(path)?)),
+            None => ErrorSink::Stderr,
+        })
+    }
+
+    fn write(&self, error: &ParseError) -> Result<(), CliError> {
+        match self {
+            ErrorSink::Report(report) => report.lock().unwrap().write(error)?,
+            ErrorSink::Stderr => eprintln!("{}", error),
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), CliError> {
+        if let ErrorSink::Report(report) = self {
+            report.lock().unwrap().flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorHandler for ErrorSink {
+    fn write(&mut self, error: &ParseError) -> std::io::Result<()> {
+        match self {
+            ErrorSink::Report(report) => report.get_mut().unwrap().write(error),
+            ErrorSink::Stderr => {
+                eprintln!("{}", error);
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ErrorSink::Report(report) => report.get_mut().unwrap().flush(),
+            ErrorSink::Stderr => Ok(()),
+        }
+    }
+}
+
+/// Reads all inputs, passing errors to the sink and progress to `done`.
+fn summarize(
+    input: &InputArgs,
+    audit: bool,
+    errors: &ErrorSink,
+    mut done: impl FnMut(FileProgress) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    for event in input.events(audit)? {
+        match event {
+            Event::Doctype(_) | Event::Record(_) => {}
+            Event::Error(e) => errors.write(&e)?,
+            Event::FileDone(progress) => done(progress)?,
+        }
+    }
+    errors.flush()
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), CliError> {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, value).map_err(std::io::Error::from)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn parse(input: &InputArgs) -> Result<ExitCode, CliError> {
+    let errors = ErrorSink::new(input.errors.as_deref())?;
+    summarize(input, false, &errors, |progress| print_json(&progress))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize, Default)]
+struct Totals {
+    files: usize,
+    #[serde(flatten)]
+    summary: FileSummary,
+    bytes: u64,
+    seconds: f64,
+    records_per_second: f64,
+}
+
+impl Totals {
+    fn add(&mut self, progress: &FileProgress) {
+        self.files += 1;
+        self.summary.merge(&progress.summary);
+        self.bytes += progress.bytes;
+    }
+}
+
+fn stats(input: &InputArgs, audit: bool) -> Result<ExitCode, CliError> {
+    let started = Instant::now();
+    let errors = ErrorSink::new(input.errors.as_deref())?;
+    let mut totals = Totals::default();
+    summarize(input, audit, &errors, |progress| {
+        input.progress(&progress);
+        totals.add(&progress);
+        Ok(())
+    })?;
+    totals.seconds = started.elapsed().as_secs_f64();
+    totals.records_per_second =
+        (totals.summary.records() + totals.summary.error_count()) as f64 / totals.seconds;
+    print_json(&totals)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(
+    input: &InputArgs,
+    dtd: bool,
+    dtd_file: Option<&Path>,
+    audit: bool,
+) -> Result<ExitCode, CliError> {
+    // the report is the output of this command, so it defaults to stdout
+    let errors = match &input.errors {
+        Some(path) => ErrorSink::new(Some(path))?,
+        None => ErrorSink::Report(Mutex::new(ErrorReport::new(Box::new(std::io::stdout())))),
+    };
+    let mut totals = Totals::default();
+    let mut versions = HashMap::new();
+    summarize(input, audit, &errors, |progress| {
+        input.progress(&progress);
+        totals.add(&progress);
+        // the summary names the release like its system identifier does
+        let summary = &progress.summary;
+        if let (Some(file), Some(version)) = (&summary.file, summary.dtd.as_deref()) {
+            versions.extend(DtdVersion::from_doctype(version).map(|v| (file.clone(), v)));
+        }
+        Ok(())
+    })?;
+    let mut failed = totals.summary.error_count();
+    if dtd || dtd_file.is_some() {
+        failed += check_dtd(input, dtd_file, &versions, &errors)?;
+    }
+    for (path, unmapped) in &totals.summary.unmapped {
+        eprintln!(
+            "unmapped {}: {} times, e.g. in {}",
+            path,
+            unmapped.count,
+            unmapped.pmids.join(", ")
+        );
+    }
+    let unmapped = totals.summary.unmapped.len();
+    if audit {
+        eprintln!(
+            "{} files, {} records, {} errors, {} unmapped paths",
+            totals.files,
+            totals.summary.records(),
+            failed,
+            unmapped
+        );
+    } else {
+        eprintln!(
+            "{} files, {} records, {} errors",
+            totals.files,
+            totals.summary.records(),
+            failed
+        );
+    }
+    Ok(if failed == 0 && unmapped == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Checks each input against `dtd_file`, or else the bundled DTD of the
+/// release in `versions`, the releases by file name from the record pass.
+/// Returns the number of violations. Each file is read a second time.
+fn check_dtd(
+    input: &InputArgs,
+    dtd_file: Option<&Path>,
+    versions: &HashMap<String, DtdVersion>,
+    errors: &ErrorSink,
+) -> Result<u64, CliError> {
+    let custom = match dtd_file {
+        Some(path) => Some(Dtd::parse(&std::fs::read_to_string(path)?)?),
+        None => None,
+    };
+    let mut bundled: HashMap<DtdVersion, Dtd> = HashMap::new();
+    let mut total = 0;
+    for file in expand_inputs(&input.inputs)? {
+        let dtd = match &custom {
+            Some(dtd) => dtd,
+            None => {
+                let named = versions.get(&file.display().to_string()).copied();
+                let release = bundled_release(&file, named);
+                bundled
+                    .entry(release)
+                    .or_insert_with(|| Dtd::bundled(release).expect("the release is bundled"))
+            }
+        };
+        let mut validator = DtdValidator::open(dtd, &file)?;
+        let mut found = 0;
+        for error in validator.by_ref() {
+            errors.write(&error)?;
+            found += 1;
+        }
+        if !input.quiet {
+            eprintln!("{}: {} DTD violations", file.display(), found);
+        }
+        total += found;
+    }
+    errors.flush()?;
+    Ok(total)
+}
+
+/// The bundled release to check a file against: the one it names, or else
+/// the newest bundled one that is not newer. Files without a DOCTYPE are
+/// checked against the 2019 DTD.
+fn bundled_release(file: &Path, named: Option<DtdVersion>) -> DtdVersion {
+    let Some(named) = named else {
+        return DtdVersion::PUBMED_190101;
+    };
+    if DtdVersion::BUNDLED.contains(&named) {
+        return named;
+    }
+    let release = DtdVersion::BUNDLED
+        .iter()
+        .rev()
+        .copied()
+        .find(|&b| b <= named)
+        .unwrap_or(DtdVersion::PUBMED_190101);
+    // printed even with --quiet, as the check is not the one the file asks for
+    eprintln!(
+        "{}: written against {}, which is not bundled; checked against {}",
+        file.display(),
+        named,
+        release
+    );
+    release
+}
+
+fn open_output(output: &str) -> Result<Box<dyn Write>, CliError> {
+    if output == "-" {
+        return Ok(Box::new(BufWriter::new(std::io::stdout().lock())));
+    }
+    let file = BufWriter::new(File::create(output)?);
+    if output.ends_with(".gz") {
+        Ok(Box::new(GzEncoder::new(file, Compression::default())))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+fn export(
+    input: &InputArgs,
+    format: Format,
+    output: &str,
+    fields: Vec<Column>,
+    join: &str,
+) -> Result<ExitCode, CliError> {
+    let errors = ErrorSink::new(input.errors.as_deref())?;
+    let mut writer = if format.is_directory() {
+        if output == "-" {
+            return Err(ExportError::NotAStream(format).into());
+        }
+        Box::new(ParquetWriter::create(output)?)
+    } else if let Some(delimiter) = format.delimiter() {
+        let mut writer = DelimitedWriter::new(open_output(output)?, delimiter).with_separator(join);
+        if !fields.is_empty() {
+            writer = writer.with_columns(fields);
+        }
+        Box::new(writer)
+    } else {
+        format.writer(open_output(output)?)?
+    };
+    for event in input.events(false)? {
+        match event {
+            Event::Record(record) => match writer.write_record(&record) {
+                Err(e) if e.is_record_error() => errors.write(&skipped(e))?,
+                result => result?,
+            },
+            Event::Doctype(version) => writer.set_dtd_version(version),
+            Event::Error(e) => errors.write(&e)?,
+            Event::FileDone(progress) => input.progress(&progress),
+        }
+    }
+    writer.finish()?;
+    errors.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// A record the writer left out, as an entry of the error report.
+fn skipped(error: ExportError) -> ParseError {
+    let pmid = match &error {
+        ExportError::InvalidPmid(pmid) => Some(pmid.clone()),
+        _ => None,
+    };
+    ParseError::Skipped {
+        context: Box::new(ErrorContext {
+            pmid,
+            ..Default::default()
+        }),
+        message: error.to_string(),
+    }
+}
+
+fn schema() -> Result<ExitCode, CliError> {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &json_schema()).map_err(std::io::Error::from)?;
+    writeln!(stdout)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn load(input: &InputArgs, mongo: &MongoArgs) -> Result<ExitCode, CliError> {
+    let events = input.events(false)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let store = runtime.block_on(async {
+        let store = ArticleStore::connect(&mongo.config()).await?;
+        store.create_indexes().await?;
+        Ok::<_, DbError>(store)
+    })?;
+    // loading is a sync run without the applied-file bookkeeping
+    let mut synchronizer = Synchronizer::new(store, "sync_state")
+        .with_error_handler(ErrorSink::new(input.errors.as_deref())?);
+    let mut outcome = FileOutcome::default();
+    for event in events {
+        match event {
+            Event::Record(record) => {
+                runtime.block_on(synchronizer.apply_record(Ok(*record), &mut outcome))?
+            }
+            Event::Doctype(_) => {}
+            Event::Error(e) => runtime.block_on(synchronizer.apply_record(Err(e), &mut outcome))?,
+            Event::FileDone(progress) => {
+                runtime.block_on(synchronizer.finish_file(&mut outcome))?;
+                outcome.name = progress.summary.file.clone().unwrap_or_default();
+                print_json(&outcome)?;
+                input.progress(&progress);
+                outcome = FileOutcome::default();
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn sync(
+    mirror: &Path,
+    mongo: &MongoArgs,
+    state_collection: &str,
+    errors: Option<&Path>,
+) -> Result<ExitCode, CliError> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let store = ArticleStore::connect(&mongo.config()).await?;
+        store.create_indexes().await?;
+        let mut synchronizer =
+            Synchronizer::new(store, state_collection).with_error_handler(ErrorSink::new(errors)?);
+        for outcome in synchronizer.run(mirror).await? {
+            print_json(&outcome)?;
+        }
+        Ok(ExitCode::SUCCESS)
+    })
+}
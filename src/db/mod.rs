@@ -0,0 +1,219 @@
+use bson::{doc, Bson, Document};
+use mongodb::options::IndexOptions;
+use mongodb::{Client, Collection, Database, IndexModel};
+use thiserror::Error;
+
+use crate::pubmed::PubmedArticle;
+
+// a single `update` command must fit in a 16MB BSON document
+const MAX_BATCH_BYTES: usize = 12 * 1024 * 1024;
+
+const DUPLICATE_KEY: i32 = 11000;
+
+#[derive(Error, Debug)]
+pub enum DbError {
+    #[error("mongodb error: {0}")]
+    Mongo(#[from] mongodb::error::Error),
+    #[error("failed to convert article to bson: {0}")]
+    Bson(#[from] bson::ser::Error),
+    #[error("article has an invalid PMID: {0}")]
+    Pmid(#[from] std::num::ParseIntError),
+    #[error("bulk write failed: {0}")]
+    BulkWrite(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub uri: String,
+    pub database: String,
+    pub collection: String,
+    /// Number of articles sent per `update` command.
+    pub batch_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            uri: String::from("mongodb://localhost:27017"),
+            database: String::from("pubmed"),
+            collection: String::from("articles"),
+            batch_size: 1000,
+        }
+    }
+}
+
+/// Converts an article into the stored document.
+///
+/// The PMID becomes `_id` and its version is stored as `version`; DOI, MeSH descriptor UIs and the journal NLM ID are
+/// copied to top-level fields so they can be indexed, and the full record is
+/// kept under `article`.
+pub fn to_document(article: &PubmedArticle) -> Result<Document, DbError> {
+    let citation = article.medline_citation();
+    let doi = match article.doi() {
+        Some(doi) => Bson::String(doi.to_string()),
+        None => Bson::Null,
+    };
+    Ok(doc! {
+        "_id": i64::from(article.pubmed_id()?),
+        "version": i64::from(citation.pmid().version_number()),
+        "doi": doi,
+        "mesh_ui": citation.mesh_descriptor_uis(),
+        "nlm_unique_id": citation.nlm_unique_id(),
+        "article": bson::to_document(article)?,
+    })
+}
+
+/// Outcome of writing one batch or deletion.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WriteSummary {
+    /// Documents inserted or replaced.
+    pub written: u64,
+    /// Articles skipped because a newer version is already stored.
+    pub stale: u64,
+    /// Documents removed.
+    pub deleted: u64,
+}
+
+impl std::ops::AddAssign for WriteSummary {
+    fn add_assign(&mut self, other: WriteSummary) {
+        self.written += other.written;
+        self.stale += other.stale;
+        self.deleted += other.deleted;
+    }
+}
+
+/// Batched upserts of `PubmedArticle` documents keyed by PMID.
+///
+/// Only the latest version of a PMID is kept: an article never replaces a
+/// stored document with a higher `version`.
+pub struct ArticleStore {
+    db: Database,
+    collection: String,
+    batch_size: usize,
+    pending: Vec<Document>,
+    pending_bytes: usize,
+}
+
+impl ArticleStore {
+    pub async fn connect(config: &Config) -> Result<ArticleStore, DbError> {
+        let client = Client::with_uri_str(&config.uri).await?;
+        Ok(ArticleStore {
+            db: client.database(&config.database),
+            collection: config.collection.clone(),
+            batch_size: config.batch_size.max(1),
+            pending: Vec::new(),
+            pending_bytes: 0,
+        })
+    }
+
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    pub fn collection(&self) -> Collection<Document> {
+        self.db.collection(&self.collection)
+    }
+
+    /// Creates the DOI, MeSH UI and NLM ID indexes; PMID is the `_id`.
+    pub async fn create_indexes(&self) -> Result<(), DbError> {
+        let indexes = [
+            ("doi", "doi_1"),
+            ("mesh_ui", "mesh_ui_1"),
+            ("nlm_unique_id", "nlm_unique_id_1"),
+        ]
+        .into_iter()
+        .map(|(field, name)| {
+            IndexModel::builder()
+                .keys(doc! { field: 1 })
+                .options(IndexOptions::builder().name(name.to_string()).build())
+                .build()
+        });
+        self.collection().create_indexes(indexes, None).await?;
+        Ok(())
+    }
+
+    /// Queues an article, writing the batch once it is full. The summary is
+    /// empty unless a batch was written.
+    pub async fn upsert(&mut self, article: &PubmedArticle) -> Result<WriteSummary, DbError> {
+        let mut summary = WriteSummary::default();
+        let document = to_document(article)?;
+        let size = bson::to_vec(&document)?.len();
+        if !self.pending.is_empty() && self.pending_bytes + size > MAX_BATCH_BYTES {
+            summary += self.flush().await?;
+        }
+        self.pending.push(document);
+        self.pending_bytes += size;
+        if self.pending.len() >= self.batch_size {
+            summary += self.flush().await?;
+        }
+        Ok(summary)
+    }
+
+    /// Writes all queued articles in one unordered `update` command.
+    pub async fn flush(&mut self) -> Result<WriteSummary, DbError> {
+        if self.pending.is_empty() {
+            return Ok(WriteSummary::default());
+        }
+        // when a newer version is stored the filter does not match and the
+        // upsert fails with a duplicate `_id`, which leaves that document as is
+        let updates: Vec<Document> = self
+            .pending
+            .drain(..)
+            .map(|document| {
+                let id = document.get("_id").cloned().unwrap_or(Bson::Null);
+                let version = document.get("version").cloned().unwrap_or(Bson::Null);
+                doc! {
+                    "q": { "_id": id, "version": { "$lte": version } },
+                    "u": document,
+                    "upsert": true,
+                }
+            })
+            .collect();
+        self.pending_bytes = 0;
+        let reply = self
+            .db
+            .run_command(
+                doc! { "update": &self.collection, "updates": updates, "ordered": false },
+                None,
+            )
+            .await?;
+        let mut summary = WriteSummary {
+            written: reply.get_i32("n").unwrap_or(0) as u64,
+            stale: 0,
+            deleted: 0,
+        };
+        if let Ok(errors) = reply.get_array("writeErrors") {
+            let (stale, failed): (Vec<&Bson>, Vec<&Bson>) = errors.iter().partition(|e| {
+                e.as_document()
+                    .and_then(|e| e.get_i32("code").ok())
+                    .is_some_and(|code| code == DUPLICATE_KEY)
+            });
+            if let Some(first) = failed.first() {
+                return Err(DbError::BulkWrite(format!(
+                    "{} of the batch failed, first error: {}",
+                    failed.len(),
+                    first
+                )));
+            }
+            summary.stale = stale.len() as u64;
+        }
+        Ok(summary)
+    }
+
+    /// Removes the given PMIDs, writing any queued articles first so that a
+    /// deletion always wins over an earlier upsert of the same record. The
+    /// summary includes the articles written by that flush.
+    pub async fn delete(&mut self, pmids: &[u32]) -> Result<WriteSummary, DbError> {
+        let mut summary = self.flush().await?;
+        if pmids.is_empty() {
+            return Ok(summary);
+        }
+        let ids: Vec<i64> = pmids.iter().map(|&pmid| i64::from(pmid)).collect();
+        let result = self
+            .collection()
+            .delete_many(doc! { "_id": { "$in": ids } }, None)
+            .await?;
+        summary.deleted = result.deleted_count;
+        Ok(summary)
+    }
+}
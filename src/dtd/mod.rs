@@ -0,0 +1,648 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use thiserror::Error;
+
+mod validator;
+
+pub use validator::DtdValidator;
+
+/// The DTD of the 2019 baseline, shipped with the crate.
+pub const PUBMED_190101: &str = include_str!("../../pubmed_190101.dtd");
+
+/// The DTD of the 2025 baseline, shipped with the crate.
+pub const PUBMED_250101: &str = include_str!("../../pubmed_250101.dtd");
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Release of the PubMed DTD a file is written against, named after its
+/// date: `pubmed_250101` is the DTD of 1 January 2025.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DtdVersion {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl DtdVersion {
+    pub const PUBMED_190101: DtdVersion = DtdVersion::new(2019, 1, 1);
+    pub const PUBMED_250101: DtdVersion = DtdVersion::new(2025, 1, 1);
+
+    /// Releases whose DTD is shipped with the crate, oldest first.
+    pub const BUNDLED: &'static [DtdVersion] =
+        &[DtdVersion::PUBMED_190101, DtdVersion::PUBMED_250101];
+
+    pub const fn new(year: u16, month: u8, day: u8) -> Self {
+        DtdVersion { year, month, day }
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Reads the version from the content of a `DOCTYPE` declaration: the
+    /// system identifier, e.g. `.../pubmed_250101.dtd`, or else the public
+    /// one, e.g. `-//NLM//DTD PubMedArticle, 1st January 2025//EN`.
+    pub fn from_doctype(doctype: &str) -> Option<DtdVersion> {
+        DtdVersion::from_system_id(doctype).or_else(|| DtdVersion::from_public_id(doctype))
+    }
+
+    fn from_system_id(doctype: &str) -> Option<DtdVersion> {
+        let start = doctype.find("pubmed_")? + "pubmed_".len();
+        let digits = doctype.get(start..start + 6)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let month = digits[2..4].parse().ok()?;
+        let day = digits[4..].parse().ok()?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        Some(DtdVersion::new(
+            2000 + digits[..2].parse::<u16>().ok()?,
+            month,
+            day,
+        ))
+    }
+
+    fn from_public_id(doctype: &str) -> Option<DtdVersion> {
+        let start = doctype.find("DTD PubMedArticle,")? + "DTD PubMedArticle,".len();
+        let date = &doctype[start..];
+        let date = &date[..date.find("//")?];
+        let mut words = date.split_whitespace();
+        let day = words
+            .next()?
+            .trim_end_matches(char::is_alphabetic)
+            .parse()
+            .ok()?;
+        let month = words.next()?;
+        let month = MONTHS.iter().position(|&m| m == month)? as u8 + 1;
+        let year = words.next()?.parse().ok()?;
+        Some(DtdVersion::new(year, month, day))
+    }
+
+    /// Public identifier of the release, as in its `DOCTYPE`:
+    /// `-//NLM//DTD PubMedArticle, 1st January 2025//EN`.
+    pub fn public_id(&self) -> String {
+        let suffix = match (self.day % 10, self.day) {
+            (_, 11..=13) => "th",
+            (1, _) => "st",
+            (2, _) => "nd",
+            (3, _) => "rd",
+            _ => "th",
+        };
+        format!(
+            "-//NLM//DTD PubMedArticle, {}{} {} {}//EN",
+            self.day,
+            suffix,
+            MONTHS[self.month as usize - 1],
+            self.year
+        )
+    }
+
+    /// System identifier of the release, where NLM publishes it.
+    pub fn system_id(&self) -> String {
+        format!("https://dtd.nlm.nih.gov/ncbi/pubmed/out/{}.dtd", self)
+    }
+}
+
+impl fmt::Display for DtdVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pubmed_{:02}{:02}{:02}",
+            self.year % 100,
+            self.month,
+            self.day
+        )
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum DtdError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+}
+
+/// How often a particle of a content model may occur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    One,
+    /// `?`
+    Optional,
+    /// `*`
+    ZeroOrMore,
+    /// `+`
+    OneOrMore,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    Element(String),
+    /// `(a, b)`
+    Seq(Vec<Particle>),
+    /// `(a | b)`
+    Choice(Vec<Particle>),
+}
+
+/// A term of an element content model and its repetition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Particle {
+    pub term: Term,
+    pub repeat: Repeat,
+}
+
+impl Particle {
+    /// True when the child element names match this particle exactly.
+    pub fn matches(&self, children: &[&str]) -> bool {
+        self.ends(children, 0).contains(&children.len())
+    }
+
+    /// Positions in `children` where a match starting at `start` can end.
+    fn ends(&self, children: &[&str], start: usize) -> BTreeSet<usize> {
+        let mut ends = BTreeSet::new();
+        match self.repeat {
+            Repeat::One => return self.term_ends(children, start),
+            Repeat::Optional => {
+                ends.insert(start);
+                ends.extend(self.term_ends(children, start));
+            }
+            Repeat::ZeroOrMore | Repeat::OneOrMore => {
+                if self.repeat == Repeat::ZeroOrMore {
+                    ends.insert(start);
+                }
+                let mut frontier = vec![start];
+                let mut seen = BTreeSet::from([start]);
+                while let Some(position) = frontier.pop() {
+                    for end in self.term_ends(children, position) {
+                        ends.insert(end);
+                        if seen.insert(end) {
+                            frontier.push(end);
+                        }
+                    }
+                }
+            }
+        }
+        ends
+    }
+
+    fn term_ends(&self, children: &[&str], start: usize) -> BTreeSet<usize> {
+        match &self.term {
+            Term::Element(name) => match children.get(start) {
+                Some(child) if child == name => BTreeSet::from([start + 1]),
+                _ => BTreeSet::new(),
+            },
+            Term::Seq(particles) => {
+                let mut positions = BTreeSet::from([start]);
+                for particle in particles {
+                    positions = positions
+                        .iter()
+                        .flat_map(|&position| particle.ends(children, position))
+                        .collect();
+                }
+                positions
+            }
+            Term::Choice(particles) => particles
+                .iter()
+                .flat_map(|particle| particle.ends(children, start))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for Particle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.term {
+            Term::Element(name) => f.write_str(name)?,
+            Term::Seq(particles) | Term::Choice(particles) => {
+                let separator = match self.term {
+                    Term::Seq(_) => ", ",
+                    _ => " | ",
+                };
+                f.write_str("(")?;
+                for (i, particle) in particles.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(separator)?;
+                    }
+                    write!(f, "{}", particle)?;
+                }
+                f.write_str(")")?;
+            }
+        }
+        match self.repeat {
+            Repeat::One => Ok(()),
+            Repeat::Optional => f.write_str("?"),
+            Repeat::ZeroOrMore => f.write_str("*"),
+            Repeat::OneOrMore => f.write_str("+"),
+        }
+    }
+}
+
+/// What an element may contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentModel {
+    Empty,
+    Any,
+    /// Text mixed with the listed elements: `(#PCDATA | b | i)*`.
+    Mixed(Vec<String>),
+    /// Elements only, in the order of the particle.
+    Children(Particle),
+}
+
+impl fmt::Display for ContentModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentModel::Empty => f.write_str("EMPTY"),
+            ContentModel::Any => f.write_str("ANY"),
+            ContentModel::Mixed(names) if names.is_empty() => f.write_str("(#PCDATA)"),
+            ContentModel::Mixed(names) => write!(f, "(#PCDATA | {})*", names.join(" | ")),
+            ContentModel::Children(particle) => write!(f, "{}", particle),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeDefault {
+    Required,
+    Implied,
+    Fixed(String),
+    Value(String),
+}
+
+/// An attribute declared in an `ATTLIST`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: String,
+    /// Allowed values of an enumerated attribute; `None` for `CDATA` and
+    /// the other string types.
+    pub values: Option<Vec<String>>,
+    pub default: AttributeDefault,
+}
+
+/// Element and attribute declarations of a DTD.
+///
+/// Parameter entities declared in the DTD itself are expanded; external
+/// ones, such as the MathML module the PubMed DTDs pull in, are not read,
+/// so the elements they declare are unknown here.
+#[derive(Debug, Clone, Default)]
+pub struct Dtd {
+    elements: HashMap<String, ContentModel>,
+    attributes: HashMap<String, Vec<Attribute>>,
+}
+
+impl Dtd {
+    pub fn pubmed_190101() -> Dtd {
+        Dtd::parse(PUBMED_190101).expect("the bundled DTD parses")
+    }
+
+    pub fn pubmed_250101() -> Dtd {
+        Dtd::parse(PUBMED_250101).expect("the bundled DTD parses")
+    }
+
+    /// The bundled DTD of a release, if it is shipped with the crate.
+    pub fn bundled(version: DtdVersion) -> Option<Dtd> {
+        match version {
+            DtdVersion::PUBMED_190101 => Some(Dtd::pubmed_190101()),
+            DtdVersion::PUBMED_250101 => Some(Dtd::pubmed_250101()),
+            _ => None,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Dtd, DtdError> {
+        let mut dtd = Dtd::default();
+        let mut entities: HashMap<String, Option<String>> = HashMap::new();
+        for (line, declaration) in declarations(text)? {
+            let error = |message: String| DtdError::Syntax { line, message };
+            let declaration = expand(declaration, &entities);
+            let mut tokens = Tokens::new(&declaration);
+            match tokens.name().as_deref() {
+                Some("ENTITY") => {
+                    if tokens.peek() != Some('%') {
+                        // general entities do not occur in content models
+                        continue;
+                    }
+                    tokens.next_char();
+                    let name = tokens
+                        .name()
+                        .ok_or_else(|| error("expected an entity name".into()))?;
+                    let value = tokens.literal();
+                    entities.entry(name).or_insert(value);
+                }
+                Some("ELEMENT") => {
+                    let name = tokens
+                        .name()
+                        .ok_or_else(|| error("expected an element name".into()))?;
+                    let model = tokens.content_model().map_err(error)?;
+                    dtd.elements.insert(name, model);
+                }
+                Some("ATTLIST") => {
+                    let name = tokens
+                        .name()
+                        .ok_or_else(|| error("expected an element name".into()))?;
+                    let attributes = dtd.attributes.entry(name).or_default();
+                    while let Some(attribute) = tokens.attribute().map_err(error)? {
+                        attributes.push(attribute);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(dtd)
+    }
+
+    pub fn element(&self, name: &str) -> Option<&ContentModel> {
+        self.elements.get(name)
+    }
+
+    pub fn attributes(&self, element: &str) -> &[Attribute] {
+        self.attributes.get(element).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn attribute(&self, element: &str, name: &str) -> Option<&Attribute> {
+        self.attributes(element).iter().find(|a| a.name == name)
+    }
+}
+
+/// Markup declarations, `<!` to `>`, with the line each starts on and
+/// without the delimiters. Comments and processing instructions are
+/// dropped.
+fn declarations(text: &str) -> Result<Vec<(usize, &str)>, DtdError> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let line = text[..text.len() - rest.len() + start]
+            .matches('\n')
+            .count()
+            + 1;
+        rest = &rest[start..];
+        let unclosed = |what: &str| DtdError::Syntax {
+            line,
+            message: format!("unclosed {}", what),
+        };
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").ok_or_else(|| unclosed("comment"))?;
+            rest = &comment[end + 3..];
+        } else if let Some(pi) = rest.strip_prefix("<?") {
+            let end = pi
+                .find("?>")
+                .ok_or_else(|| unclosed("processing instruction"))?;
+            rest = &pi[end + 2..];
+        } else if let Some(declaration) = rest.strip_prefix("<!") {
+            let mut quote = None;
+            let end = declaration
+                .char_indices()
+                .find(|&(_, c)| match quote {
+                    Some(q) if c == q => {
+                        quote = None;
+                        false
+                    }
+                    Some(_) => false,
+                    None if c == '"' || c == '\'' => {
+                        quote = Some(c);
+                        false
+                    }
+                    None => c == '>',
+                })
+                .map(|(i, _)| i)
+                .ok_or_else(|| unclosed("declaration"))?;
+            found.push((line, &declaration[..end]));
+            rest = &declaration[end + 1..];
+        } else {
+            rest = &rest[1..];
+        }
+    }
+    Ok(found)
+}
+
+/// Replaces parameter entity references; external and undeclared entities
+/// expand to nothing.
+fn expand(declaration: &str, entities: &HashMap<String, Option<String>>) -> String {
+    let mut out = declaration.to_string();
+    // entities may refer to others; the depth guards against cycles
+    for _ in 0..8 {
+        let mut expanded = String::with_capacity(out.len());
+        let mut rest = out.as_str();
+        let mut changed = false;
+        while let Some(start) = rest.find('%') {
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            match after.find(';') {
+                Some(end) if is_name(&after[..end]) => {
+                    let value = entities.get(&after[..end]).cloned().flatten();
+                    expanded.push(' ');
+                    expanded.push_str(&value.unwrap_or_default());
+                    expanded.push(' ');
+                    rest = &after[end + 1..];
+                    changed = true;
+                }
+                _ => {
+                    expanded.push('%');
+                    rest = after;
+                }
+            }
+        }
+        expanded.push_str(rest);
+        out = expanded;
+        if !changed {
+            break;
+        }
+    }
+    out
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '#')
+}
+
+fn is_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(is_name_char)
+}
+
+/// Tokens of a declaration after the `<!`.
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Tokens { rest: text }
+    }
+
+    fn skip_space(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.rest.chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.rest = &self.rest[c.len_utf8()..];
+        Some(c)
+    }
+
+    fn name(&mut self) -> Option<String> {
+        self.skip_space();
+        let end = self
+            .rest
+            .find(|c: char| !is_name_char(c))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return None;
+        }
+        let name = self.rest[..end].to_string();
+        self.rest = &self.rest[end..];
+        Some(name)
+    }
+
+    /// A quoted literal; `None` for anything else, e.g. `SYSTEM "..."`.
+    fn literal(&mut self) -> Option<String> {
+        let quote = self.peek().filter(|&c| c == '"' || c == '\'')?;
+        self.next_char();
+        let end = self.rest.find(quote)?;
+        let value = self.rest[..end].to_string();
+        self.rest = &self.rest[end + 1..];
+        Some(value)
+    }
+
+    fn repeat(&mut self) -> Repeat {
+        let repeat = match self.rest.chars().next() {
+            Some('?') => Repeat::Optional,
+            Some('*') => Repeat::ZeroOrMore,
+            Some('+') => Repeat::OneOrMore,
+            _ => return Repeat::One,
+        };
+        self.rest = &self.rest[1..];
+        repeat
+    }
+
+    fn content_model(&mut self) -> Result<ContentModel, String> {
+        if self.peek() != Some('(') {
+            return match self.name().as_deref() {
+                Some("EMPTY") => Ok(ContentModel::Empty),
+                Some("ANY") => Ok(ContentModel::Any),
+                _ => Err("expected a content model".into()),
+            };
+        }
+        let saved = self.rest;
+        self.next_char();
+        if self.name().as_deref() == Some("#PCDATA") {
+            let mut names = Vec::new();
+            loop {
+                match self.next_char() {
+                    Some('|') => names.push(self.name().ok_or("expected an element name")?),
+                    Some(')') => break,
+                    _ => return Err("unclosed mixed content model".into()),
+                }
+            }
+            self.repeat();
+            return Ok(ContentModel::Mixed(names));
+        }
+        self.rest = saved;
+        Ok(ContentModel::Children(self.particle()?))
+    }
+
+    fn particle(&mut self) -> Result<Particle, String> {
+        if self.peek() != Some('(') {
+            let name = self.name().ok_or("expected an element name")?;
+            return Ok(Particle {
+                term: Term::Element(name),
+                repeat: self.repeat(),
+            });
+        }
+        self.next_char();
+        let mut particles = vec![self.particle()?];
+        let mut separator = None;
+        loop {
+            match self.next_char() {
+                Some(')') => break,
+                Some(c @ (',' | '|')) if separator.is_none() || separator == Some(c) => {
+                    separator = Some(c);
+                    particles.push(self.particle()?);
+                }
+                Some(c) => return Err(format!("unexpected {:?} in content model", c)),
+                None => return Err("unclosed content model".into()),
+            }
+        }
+        let term = match separator {
+            Some('|') => Term::Choice(particles),
+            _ => Term::Seq(particles),
+        };
+        Ok(Particle {
+            term,
+            repeat: self.repeat(),
+        })
+    }
+
+    fn attribute(&mut self) -> Result<Option<Attribute>, String> {
+        let Some(name) = self.name() else {
+            return Ok(None);
+        };
+        let values = if self.peek() == Some('(') {
+            Some(self.enumeration()?)
+        } else {
+            match self.name().as_deref() {
+                Some("NOTATION") => Some(self.enumeration()?),
+                Some(_) => None,
+                None => return Err(format!("attribute {} has no type", name)),
+            }
+        };
+        let default = match self.peek() {
+            Some('#') => match self.name().as_deref() {
+                Some("#REQUIRED") => AttributeDefault::Required,
+                Some("#IMPLIED") => AttributeDefault::Implied,
+                Some("#FIXED") => AttributeDefault::Fixed(
+                    self.literal()
+                        .ok_or_else(|| format!("attribute {} has no fixed value", name))?,
+                ),
+                _ => return Err(format!("attribute {} has an unknown default", name)),
+            },
+            _ => AttributeDefault::Value(
+                self.literal()
+                    .ok_or_else(|| format!("attribute {} has no default", name))?,
+            ),
+        };
+        Ok(Some(Attribute {
+            name,
+            values,
+            default,
+        }))
+    }
+
+    fn enumeration(&mut self) -> Result<Vec<String>, String> {
+        if self.next_char() != Some('(') {
+            return Err("expected an enumeration".into());
+        }
+        let mut values = Vec::new();
+        loop {
+            values.push(self.name().ok_or("expected an enumerated value")?);
+            match self.next_char() {
+                Some('|') => (),
+                Some(')') => return Ok(values),
+                _ => return Err("unclosed enumeration".into()),
+            }
+        }
+    }
+}
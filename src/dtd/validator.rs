@@ -0,0 +1,329 @@
+use flate2::read::GzDecoder;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::{AttributeDefault, ContentModel, Dtd, DtdVersion};
+use crate::error::{ErrorContext, ParseError};
+use crate::reader::{is_mathml, GzFileReader};
+
+/// An open element and what it contained so far.
+struct Frame {
+    name: String,
+    offset: usize,
+    children: Vec<String>,
+    text: bool,
+}
+
+/// A violation found in the record being read, reported once its PMID is
+/// known.
+struct Pending {
+    offset: usize,
+    path: String,
+    message: String,
+}
+
+/// Checks a document against the content models and attribute lists of a
+/// DTD, streaming it like `RecordReader`, and yields a `ParseError::Invalid`
+/// for each violation.
+///
+/// Violations carry the PMID of the record they were found in and the path
+/// of the element from the record down, e.g.
+/// `PubmedArticle/MedlineCitation/Article`. MathML is not checked, as its
+/// DTD module is not bundled. Malformed XML is reported as
+/// `ParseError::Xml` and ends the check.
+pub struct DtdValidator<'d, R: BufRead> {
+    dtd: &'d Dtd,
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    file: Option<String>,
+    version: Option<DtdVersion>,
+    stack: Vec<Frame>,
+    pmid: Option<String>,
+    in_pmid: bool,
+    pending: Vec<Pending>,
+    found: VecDeque<ParseError>,
+    done: bool,
+}
+
+impl<'d> DtdValidator<'d, GzFileReader> {
+    /// Opens a `.xml.gz` file such as `pubmed24n0001.xml.gz`.
+    pub fn open<P: AsRef<Path>>(dtd: &'d Dtd, path: P) -> Result<Self, ParseError> {
+        let path = path.as_ref();
+        let name = path.display().to_string();
+        let file = File::open(path).map_err(|source| ParseError::Io {
+            context: Box::new(ErrorContext {
+                file: Some(name.clone()),
+                ..Default::default()
+            }),
+            source,
+        })?;
+        let decoder = GzDecoder::new(BufReader::new(file));
+        Ok(DtdValidator::from_reader(dtd, BufReader::new(decoder)).with_file_name(name))
+    }
+}
+
+impl<'d, R: BufRead> DtdValidator<'d, R> {
+    /// Reads uncompressed XML from any buffered source.
+    pub fn from_reader(dtd: &'d Dtd, reader: R) -> Self {
+        DtdValidator {
+            dtd,
+            reader: Reader::from_reader(reader),
+            buf: Vec::new(),
+            file: None,
+            version: None,
+            stack: Vec::new(),
+            pmid: None,
+            in_pmid: false,
+            pending: Vec::new(),
+            found: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Sets the file name used in error contexts.
+    pub fn with_file_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.file = Some(name.into());
+        self
+    }
+
+    /// The DTD release named by the document's `DOCTYPE`, once read. The
+    /// document is checked against the DTD it was given regardless.
+    pub fn version(&self) -> Option<DtdVersion> {
+        self.version
+    }
+
+    /// Element path from the record down; the document element alone
+    /// outside of records.
+    fn path(&self) -> String {
+        let names: Vec<&str> = self.stack.iter().map(|f| f.name.as_str()).collect();
+        match names.len() {
+            0 | 1 => names.join("/"),
+            _ => names[1..].join("/"),
+        }
+    }
+
+    fn violation(&mut self, offset: usize, message: String) {
+        let pending = Pending {
+            offset,
+            path: self.path(),
+            message,
+        };
+        if self.stack.len() > 1 {
+            self.pending.push(pending);
+        } else {
+            self.report(pending);
+        }
+    }
+
+    fn report(&mut self, pending: Pending) {
+        self.found.push_back(ParseError::Invalid {
+            context: Box::new(ErrorContext {
+                file: self.file.clone(),
+                offset: pending.offset as u64,
+                pmid: self.pmid.clone(),
+                path: pending.path,
+            }),
+            message: pending.message,
+        });
+    }
+
+    fn start(&mut self, e: &BytesStart, offset: usize) -> Result<(), quick_xml::Error> {
+        let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children.push(name.clone());
+        }
+        self.in_pmid = name == "PMID" && self.pmid.is_none() && !self.stack.is_empty();
+        self.stack.push(Frame {
+            name: name.clone(),
+            offset,
+            children: Vec::new(),
+            text: false,
+        });
+        if self.dtd.element(&name).is_none() {
+            self.violation(offset, format!("element {} is not declared", name));
+        }
+        let mut present = Vec::new();
+        for attribute in e.attributes() {
+            let attribute = attribute?;
+            let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+            if key == "xmlns" || key.starts_with("xmlns:") || key.starts_with("xml:") {
+                continue;
+            }
+            let value = attribute.unescape_value()?.into_owned();
+            let message = match self.dtd.attribute(&name, &key) {
+                None => Some(format!("attribute {} is not declared", key)),
+                Some(declared) => match (&declared.values, &declared.default) {
+                    (Some(values), _) if !values.contains(&value) => Some(format!(
+                        "attribute {} is {:?}, expected one of: {}",
+                        key,
+                        value,
+                        values.join(", ")
+                    )),
+                    (_, AttributeDefault::Fixed(fixed)) if *fixed != value => {
+                        Some(format!("attribute {} must be {:?}", key, fixed))
+                    }
+                    _ => None,
+                },
+            };
+            if let Some(message) = message {
+                self.violation(offset, message);
+            }
+            present.push(key);
+        }
+        let missing: Vec<String> = self
+            .dtd
+            .attributes(&name)
+            .iter()
+            .filter(|a| a.default == AttributeDefault::Required && !present.contains(&a.name))
+            .map(|a| a.name.clone())
+            .collect();
+        for attribute in missing {
+            self.violation(offset, format!("missing required attribute {}", attribute));
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) {
+        let Some(frame) = self.stack.last() else {
+            return;
+        };
+        let message = match self.dtd.element(&frame.name) {
+            None | Some(ContentModel::Any) => None,
+            Some(ContentModel::Empty) if frame.text || !frame.children.is_empty() => {
+                Some(format!("{} must be empty", frame.name))
+            }
+            Some(ContentModel::Empty) => None,
+            Some(ContentModel::Mixed(names)) => frame
+                .children
+                .iter()
+                .find(|child| !names.contains(child))
+                .map(|child| {
+                    format!(
+                        "element {} is not allowed in {}, expected {}",
+                        child,
+                        frame.name,
+                        self.dtd.element(&frame.name).unwrap()
+                    )
+                }),
+            Some(ContentModel::Children(_)) if frame.text => {
+                Some(format!("text is not allowed in {}", frame.name))
+            }
+            Some(ContentModel::Children(particle)) => {
+                let children: Vec<&str> = frame.children.iter().map(String::as_str).collect();
+                (!particle.matches(&children)).then(|| {
+                    format!(
+                        "content ({}) does not match {}",
+                        children.join(", "),
+                        particle
+                    )
+                })
+            }
+        };
+        if let Some(message) = message {
+            self.violation(frame.offset, message);
+        }
+        self.stack.pop();
+        self.in_pmid = false;
+        if self.stack.len() == 1 {
+            // end of a record: its violations now get its PMID
+            for pending in std::mem::take(&mut self.pending) {
+                self.report(pending);
+            }
+            self.pmid = None;
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_pmid {
+            self.pmid = Some(text.trim().to_string());
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.text |= !text.trim().is_empty();
+        }
+    }
+
+    fn xml_error(&mut self, source: quick_xml::Error) -> ParseError {
+        self.done = true;
+        ParseError::Xml {
+            context: Box::new(ErrorContext {
+                file: self.file.clone(),
+                offset: self.reader.buffer_position() as u64,
+                pmid: self.pmid.clone(),
+                path: self.path(),
+            }),
+            source,
+        }
+    }
+
+    /// Reads until a violation is found or the document ends.
+    fn step(&mut self) -> Result<(), quick_xml::Error> {
+        while self.found.is_empty() {
+            self.buf.clear();
+            let offset = self.reader.buffer_position();
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::DocType(doctype) => {
+                    self.version = DtdVersion::from_doctype(&String::from_utf8_lossy(&doctype));
+                }
+                Event::Start(e) if is_mathml(&e) => {
+                    if let Some(parent) = self.stack.last_mut() {
+                        parent
+                            .children
+                            .push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+                    }
+                    let end = e.to_end().into_owned();
+                    let mut skipped = Vec::new();
+                    self.reader.read_to_end_into(end.name(), &mut skipped)?;
+                }
+                Event::Empty(e) if is_mathml(&e) => {
+                    if let Some(parent) = self.stack.last_mut() {
+                        parent
+                            .children
+                            .push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+                    }
+                }
+                Event::Start(e) => {
+                    let e = e.into_owned();
+                    self.start(&e, offset)?;
+                }
+                Event::Empty(e) => {
+                    let e = e.into_owned();
+                    self.start(&e, offset)?;
+                    self.end();
+                }
+                Event::End(_) => self.end(),
+                Event::Text(t) => {
+                    let text = t.unescape()?.into_owned();
+                    self.text(&text);
+                }
+                Event::CData(t) => {
+                    let text = String::from_utf8_lossy(&t).into_owned();
+                    self.text(&text);
+                }
+                Event::Eof => {
+                    self.done = true;
+                    return Ok(());
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for DtdValidator<'_, R> {
+    type Item = ParseError;
+
+    fn next(&mut self) -> Option<ParseError> {
+        if self.found.is_empty() && !self.done {
+            if let Err(e) = self.step() {
+                let error = self.xml_error(e);
+                self.found.push_back(error);
+            }
+        }
+        self.found.pop_front()
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Serialize;
+use std::fmt;
+use thiserror::Error;
+
+/// Where in the input a parse error happened.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ErrorContext {
+    /// Name of the source file, when reading from a file.
+    pub file: Option<String>,
+    /// Byte offset in the decompressed XML: the start of the record for
+    /// deserialization errors, the failing token for XML errors.
+    pub offset: u64,
+    /// PMID of the record, when it was read before the error.
+    pub pmid: Option<String>,
+    /// Element path such as `PubmedArticle/MedlineCitation/Article/Journal`.
+    pub path: String,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(f, "{}", self.offset)?;
+        if let Some(pmid) = &self.pmid {
+            write!(f, " PMID {}", pmid)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("{context}: io error: {source}")]
+    Io {
+        context: Box<ErrorContext>,
+        source: std::io::Error,
+    },
+    #[error("{context}: malformed xml: {source}")]
+    Xml {
+        context: Box<ErrorContext>,
+        source: quick_xml::Error,
+    },
+    #[error("{context}: record is not valid utf-8: {source}")]
+    Utf8 {
+        context: Box<ErrorContext>,
+        source: std::str::Utf8Error,
+    },
+    #[error("{context}: {message}")]
+    Deserialize {
+        context: Box<ErrorContext>,
+        message: String,
+    },
+    /// The record does not conform to the DTD.
+    #[error("{context}: {message}")]
+    Invalid {
+        context: Box<ErrorContext>,
+        message: String,
+    },
+    /// The record was read but left out of the output, see
+    /// `ExportError::is_record_error`.
+    #[error("{context}: {message}")]
+    Skipped {
+        context: Box<ErrorContext>,
+        message: String,
+    },
+}
+
+impl ParseError {
+    pub fn context(&self) -> &ErrorContext {
+        match self {
+            ParseError::Io { context, .. } => context,
+            ParseError::Xml { context, .. } => context,
+            ParseError::Utf8 { context, .. } => context,
+            ParseError::Deserialize { context, .. } => context,
+            ParseError::Invalid { context, .. } => context,
+            ParseError::Skipped { context, .. } => context,
+        }
+    }
+
+    /// Short name of the variant, used as the `kind` of report entries.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParseError::Io { .. } => "io",
+            ParseError::Xml { .. } => "xml",
+            ParseError::Utf8 { .. } => "utf8",
+            ParseError::Deserialize { .. } => "deserialize",
+            ParseError::Invalid { .. } => "dtd",
+            ParseError::Skipped { .. } => "skipped",
+        }
+    }
+
+    /// True when only a single record was lost and reading can go on.
+    pub fn is_record_error(&self) -> bool {
+        matches!(
+            self,
+            ParseError::Utf8 { .. }
+                | ParseError::Deserialize { .. }
+                | ParseError::Invalid { .. }
+                | ParseError::Skipped { .. }
+        )
+    }
+
+    /// Error message without the context prefix.
+    pub fn message(&self) -> String {
+        match self {
+            ParseError::Io { source, .. } => source.to_string(),
+            ParseError::Xml { source, .. } => source.to_string(),
+            ParseError::Utf8 { source, .. } => source.to_string(),
+            ParseError::Deserialize { message, .. } => message.clone(),
+            ParseError::Invalid { message, .. } => message.clone(),
+            ParseError::Skipped { message, .. } => message.clone(),
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use super::{page_range, ExportError, RecordWriter};
+use crate::pubmed::{
+    Author, Inline, PartialDate, PubDate, PubmedArticle, PubmedBookArticle, Record, RichText, Style,
+};
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Words skipped when picking the title word of a citation key.
+const STOP_WORDS: [&str; 16] = [
+    "a", "an", "the", "of", "on", "in", "for", "and", "to", "with", "by", "at", "from", "is",
+    "are", "its",
+];
+
+/// Writes articles as `@article` entries and book articles as `@book`, or
+/// `@incollection` for a chapter. Deletions are skipped.
+///
+/// Citation keys are the first author's last name, the year and the first
+/// significant title word, e.g. `muller2016effect`. Keys that were already
+/// used get a letter appended (`muller2016effecta`), so the keys of an
+/// export depend only on its records and their order.
+///
+/// With BibLaTeX fields the entries use `journaltitle`, `date` and
+/// `location`, and carry `doi`, `pmid`, `pmcid` and the PMID as a PubMed
+/// `eprint`. The authors of the book a chapter is in go to `bookauthor`,
+/// which plain BibTeX does not have.
+pub struct BibtexWriter<W: Write> {
+    out: W,
+    biblatex: bool,
+    keys: HashSet<String>,
+    duplicates: HashMap<String, u32>,
+}
+
+impl<W: Write> BibtexWriter<W> {
+    pub fn new(out: W) -> Self {
+        BibtexWriter {
+            out,
+            biblatex: false,
+            keys: HashSet::new(),
+            duplicates: HashMap::new(),
+        }
+    }
+
+    pub fn with_biblatex(mut self, biblatex: bool) -> Self {
+        self.biblatex = biblatex;
+        self
+    }
+
+    fn unique_key(&mut self, base: String) -> String {
+        if self.keys.insert(base.clone()) {
+            return base;
+        }
+        loop {
+            let count = self.duplicates.entry(base.clone()).or_insert(0);
+            *count += 1;
+            let key = format!("{}{}", base, letters(*count));
+            if self.keys.insert(key.clone()) {
+                return key;
+            }
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for BibtexWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        let entry = match record {
+            Record::Article(article) => article_entry(article, self.biblatex),
+            Record::BookArticle(book) => book_entry(book, self.biblatex),
+            Record::DeleteCitation(_) | Record::DeleteDocument(_) => return Ok(()),
+        };
+        let key = self.unique_key(entry.key());
+        writeln!(self.out, "@{}{{{},", entry.entry_type, key)?;
+        for (name, value) in &entry.fields {
+            writeln!(self.out, "  {} = {},", name, value)?;
+        }
+        self.out.write_all(b"}\n\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// `a` to `z`, then `aa`, `ab` and so on.
+fn letters(mut n: u32) -> String {
+    let mut suffix = Vec::new();
+    while n > 0 {
+        n -= 1;
+        suffix.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+    suffix.reverse();
+    String::from_utf8(suffix).unwrap()
+}
+
+struct Entry {
+    entry_type: &'static str,
+    fields: Vec<(&'static str, String)>,
+    key_name: Option<String>,
+    key_year: Option<i32>,
+    key_title: String,
+}
+
+impl Entry {
+    fn new(entry_type: &'static str, title: &RichText) -> Entry {
+        let mut entry = Entry {
+            entry_type,
+            fields: Vec::new(),
+            key_name: None,
+            key_year: None,
+            key_title: title.to_plain(),
+        };
+        entry.push_braced("title", latex(title));
+        entry
+    }
+
+    fn key(&self) -> String {
+        let name = self.key_name.as_deref().map(key_part).unwrap_or_default();
+        let year = match self.key_year {
+            Some(year) => year.to_string(),
+            None => "nd".to_string(),
+        };
+        let word = self
+            .key_title
+            .split(|c: char| !c.is_alphanumeric())
+            .map(key_part)
+            .find(|w| !w.is_empty() && !STOP_WORDS.contains(&w.as_str()))
+            .unwrap_or_default();
+        let name = if name.is_empty() { "anon".into() } else { name };
+        format!("{}{}{}", name, year, word)
+    }
+
+    /// Adds a field whose value is LaTeX already.
+    fn push_braced(&mut self, name: &'static str, value: String) {
+        if !value.trim().is_empty() {
+            self.fields.push((name, format!("{{{}}}", value.trim())));
+        }
+    }
+
+    fn push<S: AsRef<str>>(&mut self, name: &'static str, value: Option<S>) {
+        if let Some(value) = value {
+            self.push_braced(name, escape(value.as_ref()));
+        }
+    }
+
+    fn push_names(&mut self, name: &'static str, authors: &[&Author]) {
+        let names: Vec<String> = authors.iter().filter_map(|a| person(a)).collect();
+        if self.key_name.is_none() {
+            self.key_name = authors
+                .iter()
+                .find_map(|a| a.last_name().or(a.collective_name()))
+                .map(str::to_string);
+        }
+        if !names.is_empty() {
+            self.push_braced(name, names.join(" and "));
+        }
+    }
+
+    fn push_date(&mut self, date: &PubDate, biblatex: bool) {
+        let Some(date) = date.date() else {
+            return;
+        };
+        self.key_year = Some(date.year());
+        if biblatex {
+            let iso = |d: &PartialDate| match (d.month(), d.day()) {
+                (Some(m), Some(day)) => format!("{:04}-{:02}-{:02}", d.year(), m, day),
+                (Some(m), None) => format!("{:04}-{:02}", d.year(), m),
+                _ => format!("{:04}", d.year()),
+            };
+            let value = match date.end() {
+                Some(end) => format!("{}/{}", iso(date.start()), iso(end)),
+                None => iso(date.start()),
+            };
+            self.push_braced("date", value);
+        } else {
+            self.push_braced("year", date.year().to_string());
+            if let Some(month) = date.month() {
+                // month macros are written without braces
+                self.fields
+                    .push(("month", MONTHS[month as usize - 1].to_string()));
+            }
+        }
+    }
+
+    fn push_pages(&mut self, pages: Option<&str>) {
+        if let Some(pages) = pages {
+            let value = match page_range(pages) {
+                (start, Some(end)) => format!("{}--{}", escape(&start), escape(&end)),
+                (start, None) => escape(&start),
+            };
+            self.push_braced("pages", value);
+        }
+    }
+
+    fn push_ids(&mut self, pmid: Option<u32>, doi: Option<&str>, pmc: Option<&str>) {
+        self.push("doi", doi);
+        if let Some(pmid) = pmid {
+            self.push("pmid", Some(pmid.to_string()));
+            self.push("eprint", Some(pmid.to_string()));
+            self.push("eprinttype", Some("pubmed"));
+        }
+        self.push("pmcid", pmc);
+    }
+}
+
+/// A name in BibTeX form; group names are braced so that they are not
+/// split into first and last names.
+fn person(author: &Author) -> Option<String> {
+    if !author.is_valid() {
+        return None;
+    }
+    match (author.last_name(), author.collective_name()) {
+        (Some(last), _) => {
+            let first = author.fore_name().or(author.initials());
+            let name = escape(last);
+            Some(match first {
+                Some(first) => format!("{}, {}", name, escape(first)),
+                None => name,
+            })
+        }
+        (None, Some(collective)) => Some(format!("{{{}}}", escape(collective))),
+        (None, None) => None,
+    }
+}
+
+fn article_entry(article: &PubmedArticle, biblatex: bool) -> Entry {
+    let a = article.article();
+    let journal = a.journal();
+    let issue = journal.journal_issue();
+    let title = a.title().cloned().unwrap_or_default();
+
+    let mut entry = Entry::new("article", &title);
+    let authors: Vec<&Author> = a.authors().iter().collect();
+    entry.push_names("author", &authors);
+    let journal_title = journal.title().or(article
+        .medline_citation()
+        .medline_journal_info()
+        .medline_ta());
+    if biblatex {
+        entry.push("journaltitle", journal_title);
+        entry.push("shortjournal", journal.iso_abbreviation());
+    } else {
+        entry.push("journal", journal_title);
+    }
+    entry.push_date(issue.pub_date(), biblatex);
+    entry.push("volume", issue.volume());
+    entry.push("number", issue.issue());
+    entry.push_pages(a.pages().as_deref());
+    entry.push("issn", journal.issn().map(|i| i.value()));
+    if biblatex {
+        entry.push_ids(article.pubmed_id().ok(), article.doi(), article.pmc());
+    }
+    entry
+}
+
+fn book_entry(book_article: &PubmedBookArticle, biblatex: bool) -> Entry {
+    let document = book_article.book_document();
+    let book = document.book();
+
+    let mut entry = match document.title() {
+        Some(title) => {
+            let mut entry = Entry::new("incollection", title);
+            entry.push_braced("booktitle", latex(book.title()));
+            entry
+        }
+        None => Entry::new("book", book.title()),
+    };
+    entry.push_names("author", &document.primary_authors());
+    entry.push_names("editor", &document.all_editors());
+    if biblatex && document.title().is_some() {
+        entry.push_names("bookauthor", &book.authors());
+    }
+    entry.push("series", book.collection_title());
+    entry.push("edition", book.edition());
+    entry.push("volume", book.volume());
+    entry.push("publisher", Some(book.publisher().name()));
+    let location = if biblatex { "location" } else { "address" };
+    entry.push(location, book.publisher().location());
+    entry.push_date(book.pub_date(), biblatex);
+    entry.push_pages(document.pages().as_deref());
+    let isbns = book.isbns();
+    entry.push("isbn", isbns.first());
+    if biblatex {
+        let doi = document.article_id_list().find("doi");
+        entry.push_ids(book_article.pubmed_id().ok(), doi, None);
+    }
+    entry
+}
+
+/// Lower case ASCII letters and digits of a name or word, with accents
+/// dropped: `Müller` becomes `muller`.
+fn key_part(text: &str) -> String {
+    let mut key = String::new();
+    for c in text.chars() {
+        match fold(c) {
+            Some(folded) => key.push_str(folded),
+            None if c.is_ascii_alphanumeric() => key.push(c.to_ascii_lowercase()),
+            None => (),
+        }
+    }
+    key
+}
+
+fn fold(c: char) -> Option<&'static str> {
+    let folded = match c.to_lowercase().next().unwrap_or(c) {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => "i",
+        'ł' | 'ľ' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        'þ' => "th",
+        _ => return None,
+    };
+    Some(folded)
+}
+
+/// Escapes the characters LaTeX treats specially.
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '%' | '#' | '_' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str("\\textasciicircum{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '<' => out.push_str("\\textless{}"),
+            '>' => out.push_str("\\textgreater{}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Braces words with capitals, such as `CYP3A5`, `Germany` or the `H` of
+/// `H<sub>2</sub>O`, so that bibliography styles do not lower case them.
+/// The first word of a title keeps its capital without braces.
+fn protect_case(text: &str, start: &mut bool) -> String {
+    let mut out = String::new();
+    for (i, word) in text.split(' ').enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        let capitals = if *start {
+            word.chars().skip(1).any(char::is_uppercase)
+        } else {
+            word.chars().any(char::is_uppercase)
+        };
+        if capitals {
+            out.push_str(&format!("{{{}}}", escape(word)));
+        } else {
+            out.push_str(&escape(word));
+        }
+        if !word.is_empty() {
+            *start = false;
+        }
+    }
+    out
+}
+
+/// LaTeX for a title: styles become `\textit` and friends, formulas math
+/// mode and capitals are protected.
+fn latex(title: &RichText) -> String {
+    latex_nodes(title.nodes(), &mut true)
+}
+
+fn latex_nodes(nodes: &[Inline], start: &mut bool) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            Inline::Text(text) => out.push_str(&protect_case(text, start)),
+            Inline::Styled(style, children) => {
+                let command = match style {
+                    Style::Italic => "textit",
+                    Style::Bold => "textbf",
+                    Style::Superscript => "textsuperscript",
+                    Style::Subscript => "textsubscript",
+                    Style::Underline => "underline",
+                };
+                let inner = latex_nodes(children, start);
+                out.push_str(&format!("\\{}{{{}}}", command, inner));
+            }
+            Inline::Formula(children) => out.push_str(&latex_nodes(children, start)),
+            Inline::Math(math) => {
+                *start = false;
+                match math.to_latex() {
+                    Some(formula) => out.push_str(&format!("${}$", formula)),
+                    None => out.push_str(&escape(&math.to_plain())),
+                }
+            }
+        }
+    }
+    out
+}
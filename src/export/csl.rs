@@ -0,0 +1,351 @@
+use serde::Serialize;
+use std::io::Write;
+
+use super::{page_range, ExportError, RecordWriter};
+use crate::pubmed::{
+    Author, Inline, PartialDate, PubDate, PubmedArticle, PubmedBookArticle, Record, RichText, Style,
+};
+
+/// Writes the records as one CSL-JSON array, the bibliography format read
+/// by Pandoc, Zotero and other citeproc implementations. Deletions are
+/// skipped.
+pub struct CslWriter<W: Write> {
+    out: W,
+    items: usize,
+}
+
+impl<W: Write> CslWriter<W> {
+    pub fn new(out: W) -> Self {
+        CslWriter { out, items: 0 }
+    }
+}
+
+impl<W: Write> RecordWriter for CslWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        let item = match record {
+            Record::Article(article) => article_item(article),
+            Record::BookArticle(book) => book_item(book),
+            Record::DeleteCitation(_) | Record::DeleteDocument(_) => return Ok(()),
+        };
+        self.out
+            .write_all(if self.items == 0 { b"[\n" } else { b",\n" })?;
+        serde_json::to_writer(&mut self.out, &item)?;
+        self.items += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.out
+            .write_all(if self.items == 0 { b"[]\n" } else { b"\n]\n" })?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// A CSL-JSON item. Only the variables PubMed records can fill are
+/// present.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct CslItem {
+    id: String,
+    #[serde(rename = "type")]
+    item_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    container_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    container_title_short: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    collection_title: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    author: Vec<CslName>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    container_author: Vec<CslName>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    editor: Vec<CslName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issued: Option<CslDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    volume: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issue: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    edition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    publisher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    publisher_place: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(rename = "abstract", skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keyword: Option<String>,
+    #[serde(rename = "ISSN", skip_serializing_if = "Option::is_none")]
+    issn: Option<String>,
+    #[serde(rename = "ISBN", skip_serializing_if = "Option::is_none")]
+    isbn: Option<String>,
+    #[serde(rename = "DOI", skip_serializing_if = "Option::is_none")]
+    doi: Option<String>,
+    #[serde(rename = "PMID", skip_serializing_if = "Option::is_none")]
+    pmid: Option<String>,
+    #[serde(rename = "PMCID", skip_serializing_if = "Option::is_none")]
+    pmcid: Option<String>,
+    #[serde(rename = "URL", skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+/// A personal name split into family and given names, or the literal name
+/// of a group.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CslName {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    given: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    literal: Option<String>,
+}
+
+/// A date as `date-parts`: one `[year, month, day]` array, or two for a
+/// range. Dates PubMed gives as free text are kept as a literal.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CslDate {
+    #[serde(rename = "date-parts", skip_serializing_if = "Vec::is_empty")]
+    date_parts: Vec<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    season: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    literal: Option<String>,
+}
+
+impl CslItem {
+    fn new(id: String, item_type: &'static str) -> CslItem {
+        CslItem {
+            id,
+            item_type,
+            title: None,
+            container_title: None,
+            container_title_short: None,
+            collection_title: None,
+            author: Vec::new(),
+            container_author: Vec::new(),
+            editor: Vec::new(),
+            issued: None,
+            volume: None,
+            issue: None,
+            page: None,
+            edition: None,
+            publisher: None,
+            publisher_place: None,
+            language: None,
+            summary: None,
+            keyword: None,
+            issn: None,
+            isbn: None,
+            doi: None,
+            pmid: None,
+            pmcid: None,
+            url: None,
+        }
+    }
+
+    fn set_pmid(&mut self, pmid: Result<u32, std::num::ParseIntError>) {
+        if let Ok(pmid) = pmid {
+            self.pmid = Some(pmid.to_string());
+            self.url = Some(format!("https://pubmed.ncbi.nlm.nih.gov/{}/", pmid));
+        }
+    }
+}
+
+fn owned(value: Option<&str>) -> Option<String> {
+    value.map(str::to_string)
+}
+
+fn names<'a, I: IntoIterator<Item = &'a Author>>(authors: I) -> Vec<CslName> {
+    authors
+        .into_iter()
+        .filter(|a| a.is_valid())
+        .filter_map(|a| match (a.last_name(), a.collective_name()) {
+            (Some(last), _) => Some(CslName {
+                family: Some(last.to_string()),
+                given: owned(a.fore_name().or(a.initials())),
+                literal: None,
+            }),
+            (None, Some(collective)) => Some(CslName {
+                family: None,
+                given: None,
+                literal: Some(collective.to_string()),
+            }),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+fn date_parts(date: &PartialDate) -> Vec<i32> {
+    let mut parts = vec![date.year()];
+    if let Some(month) = date.month() {
+        parts.push(month as i32);
+        if let Some(day) = date.day() {
+            parts.push(day as i32);
+        }
+    }
+    parts
+}
+
+fn season_number(season: &str) -> Option<u32> {
+    match season.to_ascii_lowercase().as_str() {
+        "spring" => Some(1),
+        "summer" => Some(2),
+        "autumn" | "fall" => Some(3),
+        "winter" => Some(4),
+        _ => None,
+    }
+}
+
+/// The normalized publication date; a `MedlineDate` that cannot be read
+/// becomes a literal.
+fn issued(pub_date: &PubDate) -> Option<CslDate> {
+    match pub_date.date() {
+        Some(date) => {
+            let mut parts = vec![date_parts(date.start())];
+            parts.extend(date.end().map(date_parts));
+            Some(CslDate {
+                date_parts: parts,
+                season: date.season().and_then(season_number),
+                literal: None,
+            })
+        }
+        None => pub_date.medline_date().map(|literal| CslDate {
+            date_parts: Vec::new(),
+            season: None,
+            literal: Some(literal.to_string()),
+        }),
+    }
+}
+
+fn pages(pages: Option<&str>) -> Option<String> {
+    pages.map(|pages| match page_range(pages) {
+        (start, Some(end)) => format!("{}-{}", start, end),
+        (start, None) => start,
+    })
+}
+
+fn escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Rich text with the HTML-like tags CSL processors understand: `<i>`,
+/// `<b>`, `<sup>` and `<sub>`. Formulas become linear text.
+fn title(text: &RichText) -> String {
+    fn write(nodes: &[Inline], out: &mut String) {
+        for node in nodes {
+            match node {
+                Inline::Text(text) => escape(text, out),
+                Inline::Styled(Style::Underline, children) | Inline::Formula(children) => {
+                    write(children, out)
+                }
+                Inline::Styled(style, children) => {
+                    out.push_str(&format!("<{}>", style.tag()));
+                    write(children, out);
+                    out.push_str(&format!("</{}>", style.tag()));
+                }
+                Inline::Math(math) => escape(&math.to_plain(), out),
+            }
+        }
+    }
+    let mut out = String::new();
+    write(text.nodes(), &mut out);
+    out
+}
+
+fn keywords(keywords: Vec<&str>) -> Option<String> {
+    Some(keywords.join(", ")).filter(|k| !k.is_empty())
+}
+
+/// A journal article as an `article-journal` item.
+pub fn article_item(article: &PubmedArticle) -> CslItem {
+    let citation = article.medline_citation();
+    let a = article.article();
+    let journal = a.journal();
+    let issue = journal.journal_issue();
+
+    let mut item = CslItem::new(citation.pmid().value.clone(), "article-journal");
+    item.title = a.title().map(title);
+    item.container_title = owned(journal.title().or(journal.iso_abbreviation()));
+    item.container_title_short = owned(
+        journal
+            .iso_abbreviation()
+            .or(citation.medline_journal_info().medline_ta()),
+    );
+    item.author = names(a.authors());
+    item.issued = issued(issue.pub_date());
+    item.volume = owned(issue.volume());
+    item.issue = owned(issue.issue());
+    item.page = pages(a.pages().as_deref());
+    item.language = owned(a.languages().first().copied());
+    item.summary = a.summary().map(|s| s.text());
+    item.keyword = keywords(citation.keywords());
+    item.issn = owned(journal.issn().map(|i| i.value()));
+    item.doi = owned(article.doi());
+    item.pmcid = owned(article.pmc());
+    item.set_pmid(article.pubmed_id());
+    item
+}
+
+/// A book article as a `chapter` item, or a `book` when the document is
+/// the whole book. The authors of the book a chapter is in are its
+/// `container-author`.
+pub fn book_item(book_article: &PubmedBookArticle) -> CslItem {
+    let document = book_article.book_document();
+    let book = document.book();
+
+    let id = document.pmid().value.clone();
+    let mut item = match document.title() {
+        Some(chapter) => {
+            let mut item = CslItem::new(id, "chapter");
+            item.title = Some(title(chapter));
+            item.container_title = Some(title(book.title()));
+            item.container_author = names(book.authors());
+            item
+        }
+        None => {
+            let mut item = CslItem::new(id, "book");
+            item.title = Some(title(book.title()));
+            item
+        }
+    };
+    item.author = names(document.primary_authors());
+    item.editor = names(document.all_editors());
+    item.collection_title = owned(book.collection_title());
+    item.issued = issued(book.pub_date());
+    item.volume = owned(book.volume());
+    item.edition = owned(book.edition());
+    item.page = pages(document.pages().as_deref());
+    item.publisher = Some(book.publisher().name().to_string());
+    item.publisher_place = owned(book.publisher().location());
+    item.language = owned(document.languages().first().copied());
+    item.summary = document.summary().map(|s| s.text());
+    item.keyword = keywords(
+        document
+            .keyword_lists()
+            .iter()
+            .flat_map(|l| l.keywords())
+            .filter_map(|k| k.value())
+            .collect(),
+    );
+    item.isbn = owned(book.isbns().first().copied());
+    item.doi = owned(document.article_id_list().find("doi"));
+    item.set_pmid(book_article.pubmed_id());
+    item
+}
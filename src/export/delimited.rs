@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+use std::io::Write;
+use std::str::FromStr;
+
+use super::fields::FieldPath;
+use super::{ExportError, RecordWriter};
+use crate::pubmed::Record;
+
+/// Columns written when none are chosen.
+pub const DEFAULT_COLUMNS: &[&str] = &[
+    "pmid",
+    "title",
+    "journal=article.journal.title",
+    "year",
+    "doi",
+    "first_author=authors[0].name",
+];
+
+/// A column of delimited output: a field path, and the header to write for
+/// it. Written as `path` or `header=path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    header: String,
+    path: FieldPath,
+}
+
+impl Column {
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    pub fn path(&self) -> &FieldPath {
+        &self.path
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (header, path) = match s.split_once('=') {
+            Some((header, path)) => (header.trim(), path.parse()?),
+            None => (s.trim(), s.parse()?),
+        };
+        Ok(Column {
+            header: header.to_string(),
+            path,
+        })
+    }
+}
+
+/// Writes one line per record with the values of the chosen columns,
+/// separated by a comma (CSV) or a tab (TSV), after a header line. Fields
+/// with several values are joined with a separator, `; ` by default.
+/// Deletions are skipped.
+///
+/// CSV values are quoted as in RFC 4180 when needed. TSV has no quoting, so
+/// tabs and line breaks in values become spaces.
+pub struct DelimitedWriter<W: Write> {
+    out: W,
+    delimiter: char,
+    columns: Vec<Column>,
+    separator: String,
+    header_written: bool,
+}
+
+impl<W: Write> DelimitedWriter<W> {
+    pub fn new(out: W, delimiter: char) -> Self {
+        DelimitedWriter {
+            out,
+            delimiter,
+            columns: DEFAULT_COLUMNS.iter().map(|c| c.parse().unwrap()).collect(),
+            separator: "; ".to_string(),
+            header_written: false,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Separator placed between the values of a multi-valued field.
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    fn cell<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        if self.delimiter == '\t' {
+            if value.contains(['\t', '\r', '\n']) {
+                return Cow::Owned(value.replace(['\t', '\r', '\n'], " "));
+            }
+        } else if value.contains([self.delimiter, '"', '\r', '\n']) {
+            return Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")));
+        }
+        Cow::Borrowed(value)
+    }
+
+    fn write_line<'a, I: IntoIterator<Item = &'a str>>(
+        &mut self,
+        cells: I,
+    ) -> Result<(), ExportError> {
+        let line: Vec<String> = cells
+            .into_iter()
+            .map(|c| self.cell(c).into_owned())
+            .collect();
+        writeln!(self.out, "{}", line.join(&self.delimiter.to_string()))?;
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> Result<(), ExportError> {
+        if !self.header_written {
+            self.header_written = true;
+            let headers: Vec<String> = self.columns.iter().map(|c| c.header.clone()).collect();
+            self.write_line(headers.iter().map(String::as_str))?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordWriter for DelimitedWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        if matches!(
+            record,
+            Record::DeleteCitation(_) | Record::DeleteDocument(_)
+        ) {
+            return Ok(());
+        }
+        let mut cells = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            cells.push(column.path.values(record)?.join(&self.separator));
+        }
+        self.write_header()?;
+        self.write_line(cells.iter().map(String::as_str))
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.write_header()?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,488 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{author_name, ExportError};
+use crate::pubmed::{
+    Article, ArticleId, Author, Book, BookDocument, Chemical, ELocationID, Grant, Journal,
+    MedlineCitation, MedlineJournalInfo, MeshHeading, PubDate, PubMedPubDate, PubmedArticle,
+    PubmedBookArticle, PubmedData, QualifierName, Record, Reference,
+};
+
+/// The value of a field: text, a nested object, a list, or nothing.
+pub enum Node<'a> {
+    Null,
+    Text(Cow<'a, str>),
+    Object(&'a dyn Fields),
+    List(Vec<Node<'a>>),
+}
+
+/// A part of the model that field paths can walk into.
+pub trait Fields {
+    /// The field called `name`, or `None` when there is no such field.
+    fn field(&self, name: &str) -> Option<Node<'_>>;
+}
+
+fn text(value: Option<&str>) -> Node<'_> {
+    match value {
+        Some(value) => Node::Text(Cow::Borrowed(value)),
+        None => Node::Null,
+    }
+}
+
+fn owned<'a, T: ToString>(value: Option<T>) -> Node<'a> {
+    match value {
+        Some(value) => Node::Text(Cow::Owned(value.to_string())),
+        None => Node::Null,
+    }
+}
+
+fn texts<'a, I: IntoIterator<Item = &'a str>>(values: I) -> Node<'a> {
+    Node::List(values.into_iter().map(|v| text(Some(v))).collect())
+}
+
+fn list<T: Fields>(values: &[T]) -> Node<'_> {
+    Node::List(
+        values
+            .iter()
+            .map(|v| Node::Object(v as &dyn Fields))
+            .collect(),
+    )
+}
+
+fn object<T: Fields>(value: Option<&T>) -> Node<'_> {
+    match value {
+        Some(value) => Node::Object(value),
+        None => Node::Null,
+    }
+}
+
+fn flag<'a>(value: bool) -> Node<'a> {
+    owned(Some(value))
+}
+
+/// `1998 Mar-Apr` style display form of a publication date.
+fn pub_date(date: &PubDate) -> Node<'_> {
+    match date.date() {
+        Some(date) => owned(Some(date)),
+        None => text(date.medline_date()),
+    }
+}
+
+/// One step of a field path: a field name, optionally followed by `[*]`
+/// for every element of a list or `[n]` for the element at index `n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    name: String,
+    index: Option<usize>,
+}
+
+/// A dotted path through the model, such as `article.journal.title`,
+/// `authors[0].last_name` or `mesh[*].descriptor`.
+///
+/// Lists met on the way are expanded, so a path can yield several values;
+/// `[n]` keeps only the element at index `n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPath {
+    path: String,
+    steps: Vec<Step>,
+}
+
+impl FromStr for FieldPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid field path `{}`", s);
+        let mut steps = Vec::new();
+        for part in s.trim().split('.') {
+            let (name, index) = match part.split_once('[') {
+                Some((name, rest)) => {
+                    let index = rest.strip_suffix(']').ok_or_else(invalid)?;
+                    match index {
+                        "*" => (name, None),
+                        n => (name, Some(n.parse().map_err(|_| invalid())?)),
+                    }
+                }
+                None => (part, None),
+            };
+            let valid = |c: char| c.is_ascii_alphanumeric() || c == '_';
+            if name.is_empty() || !name.chars().all(valid) {
+                return Err(invalid());
+            }
+            steps.push(Step {
+                name: name.to_string(),
+                index,
+            });
+        }
+        Ok(FieldPath {
+            path: s.trim().to_string(),
+            steps,
+        })
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+impl FieldPath {
+    /// The values the path leads to in `root`, in document order. An
+    /// unknown field, a path that continues past a value or one that ends at
+    /// an object is an error.
+    pub fn values<'a>(&self, root: &'a dyn Fields) -> Result<Vec<Cow<'a, str>>, ExportError> {
+        let error = |message: String| ExportError::Field(format!("`{}`: {}", self.path, message));
+        let mut nodes = vec![Node::Object(root)];
+        for step in &self.steps {
+            let mut next = Vec::new();
+            for node in nodes {
+                let Node::Object(object) = node else {
+                    return Err(error(format!("`{}` is not an object", step.name)));
+                };
+                let value = object
+                    .field(&step.name)
+                    .ok_or_else(|| error(format!("unknown field `{}`", step.name)))?;
+                let mut values = Vec::new();
+                flatten(value, &mut values);
+                match step.index {
+                    Some(index) => next.extend(values.into_iter().nth(index)),
+                    None => next.extend(values),
+                }
+            }
+            nodes = next;
+        }
+        nodes
+            .into_iter()
+            .map(|node| match node {
+                Node::Text(value) => Ok(value),
+                _ => Err(error("the path ends at an object".to_string())),
+            })
+            .collect()
+    }
+}
+
+/// Expands nested lists and drops empty values.
+fn flatten<'a>(node: Node<'a>, out: &mut Vec<Node<'a>>) {
+    match node {
+        Node::Null => {}
+        Node::List(nodes) => {
+            for node in nodes {
+                flatten(node, out);
+            }
+        }
+        node => out.push(node),
+    }
+}
+
+/// The top level of every record, with shortcuts for the fields most
+/// exports need: `pmid`, `type`, `title`, `year`, `doi`, `pmc`, `authors`
+/// and `mesh`. The full model is under `article`, `citation` and `data` for
+/// articles, and `document`, `book` and `data` for book articles.
+impl Fields for Record {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        match self {
+            Record::Article(article) => article.field(name),
+            Record::BookArticle(book) => book.field(name),
+            Record::DeleteCitation(_) | Record::DeleteDocument(_) => match name {
+                "type" => Some(text(Some("delete"))),
+                _ => Some(Node::Null),
+            },
+        }
+    }
+}
+
+impl Fields for PubmedArticle {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        let article = self.article();
+        let citation = self.medline_citation();
+        Some(match name {
+            "type" => text(Some("article")),
+            "pmid" => text(Some(&citation.pmid().value)),
+            "title" => owned(article.title().map(|t| t.to_plain())),
+            "year" => owned(article.year()),
+            "doi" => text(self.doi()),
+            "pmc" => text(self.pmc()),
+            "authors" => list(article.authors()),
+            "mesh" => list(citation.mesh_headings()),
+            "article" => Node::Object(article),
+            "citation" => Node::Object(citation),
+            "data" => object(self.pubmed_data()),
+            "book" | "document" => Node::Null,
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for PubmedBookArticle {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        let document = self.book_document();
+        let book = document.book();
+        Some(match name {
+            "type" => text(Some("book")),
+            "pmid" => text(Some(&document.pmid().value)),
+            "title" => owned(Some(document.title().unwrap_or(book.title()).to_plain())),
+            "year" => owned(book.date().map(|d| d.year())),
+            "doi" => text(document.article_id_list().find("doi")),
+            "authors" => people(document.primary_authors()),
+            "document" => Node::Object(document),
+            "book" => Node::Object(book),
+            "data" => object(self.pubmed_book_data()),
+            "pmc" | "mesh" | "article" | "citation" => Node::Null,
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for Article {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "title" => owned(self.title().map(|t| t.to_plain())),
+            "vernacular_title" => owned(self.vernacular_title().map(|t| t.to_plain())),
+            "journal" => Node::Object(self.journal()),
+            "pages" => self.pages().map_or(Node::Null, Node::Text),
+            "elocation_ids" => list(self.elocation_ids()),
+            "abstract" => owned(self.summary().map(|s| s.text())),
+            "authors" => list(self.authors()),
+            "languages" => texts(self.languages()),
+            "grants" => list(self.grants()),
+            "publication_types" => texts(self.publication_types().iter().map(|t| t.value())),
+            "dates" => Node::List(
+                self.article_dates()
+                    .iter()
+                    .map(|d| owned(d.date()))
+                    .collect(),
+            ),
+            "pub_model" => text(Some(self.pub_model())),
+            "year" => owned(self.year()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for Journal {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        let issue = self.journal_issue();
+        Some(match name {
+            "title" => text(self.title()),
+            "iso_abbreviation" => text(self.iso_abbreviation()),
+            "issn" => text(self.issn().map(|i| i.value())),
+            "issn_type" => text(self.issn().map(|i| i.issn_type())),
+            "volume" => text(issue.volume()),
+            "issue" => text(issue.issue()),
+            "date" => pub_date(issue.pub_date()),
+            "year" => owned(self.year()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for MedlineCitation {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "pmid" => text(Some(&self.pmid().value)),
+            "status" => text(Some(self.status())),
+            "owner" => text(self.owner()),
+            "date_completed" => owned(self.date_completed().and_then(|d| d.date())),
+            "date_revised" => owned(self.date_revised().and_then(|d| d.date())),
+            "journal_info" => Node::Object(self.medline_journal_info()),
+            "chemicals" => list(self.chemicals()),
+            "mesh" => list(self.mesh_headings()),
+            "keywords" => texts(self.keywords()),
+            "subsets" => texts(self.citation_subsets()),
+            "coi_statement" => owned(self.coi_statement().map(|c| c.to_plain())),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for MedlineJournalInfo {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "country" => text(self.country()),
+            "medline_ta" => text(self.medline_ta()),
+            "nlm_unique_id" => text(Some(self.nlm_unique_id())),
+            "issn_linking" => text(self.issn_linking()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for Author {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "name" => owned(author_name(self)),
+            "last_name" => text(self.last_name()),
+            "fore_name" => text(self.fore_name()),
+            "initials" => text(self.initials()),
+            "collective_name" => text(self.collective_name()),
+            "affiliations" => texts(self.affiliations()),
+            "valid" => flag(self.is_valid()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for ELocationID {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "type" => text(Some(self.eid_type())),
+            "value" => text(Some(self.value())),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for Grant {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "id" => text(self.grant_id()),
+            "acronym" => text(self.acronym()),
+            "agency" => text(self.agency()),
+            "country" => text(self.country()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for MeshHeading {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        let descriptor = self.descriptor();
+        Some(match name {
+            "descriptor" => text(Some(descriptor.value())),
+            "descriptor_ui" => text(Some(descriptor.ui())),
+            "major" => flag(self.is_major_topic()),
+            "qualifiers" => list(self.qualifiers()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for QualifierName {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "name" => text(Some(self.value())),
+            "ui" => text(Some(self.ui())),
+            "major" => flag(self.is_major_topic()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for Chemical {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "registry_number" => text(Some(self.registry_number())),
+            "substance" => text(Some(self.substance().value())),
+            "substance_ui" => text(Some(self.substance().ui())),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for PubmedData {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "publication_status" => text(Some(self.publication_status())),
+            "history" => list(self.history()),
+            "article_ids" => list(self.article_id_list().ids()),
+            "references" => Node::List(
+                self.reference_lists()
+                    .iter()
+                    .flat_map(|l| l.references())
+                    .map(|r| Node::Object(r as &dyn Fields))
+                    .collect(),
+            ),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for crate::pubmed::PubmedBookData {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "publication_status" => text(Some(self.publication_status())),
+            "history" => list(self.history()),
+            "article_ids" => list(self.article_id_list().ids()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for PubMedPubDate {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "status" => text(Some(self.pub_status())),
+            "date" => owned(self.date()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for ArticleId {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "type" => text(Some(self.id_type())),
+            "value" => text(self.value()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for Reference {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "citation" => text(self.citation()),
+            "pmid" => owned(self.pubmed_id()),
+            "doi" => text(self.article_id_list().and_then(|ids| ids.find("doi"))),
+            _ => return None,
+        })
+    }
+}
+
+fn people(authors: Vec<&Author>) -> Node<'_> {
+    Node::List(
+        authors
+            .into_iter()
+            .map(|a| Node::Object(a as &dyn Fields))
+            .collect(),
+    )
+}
+
+impl Fields for BookDocument {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "pmid" => text(Some(&self.pmid().value)),
+            "title" => owned(self.title().map(|t| t.to_plain())),
+            "vernacular_title" => owned(self.vernacular_title().map(|t| t.to_plain())),
+            "book" => Node::Object(self.book()),
+            "pages" => self.pages().map_or(Node::Null, Node::Text),
+            "abstract" => owned(self.summary().map(|s| s.text())),
+            "authors" => people(self.authors()),
+            "editors" => people(self.editors()),
+            "languages" => texts(self.languages()),
+            "publication_types" => texts(self.publication_types().iter().map(|t| t.value())),
+            "article_ids" => list(self.article_id_list().ids()),
+            _ => return None,
+        })
+    }
+}
+
+impl Fields for Book {
+    fn field(&self, name: &str) -> Option<Node<'_>> {
+        Some(match name {
+            "title" => owned(Some(self.title().to_plain())),
+            "publisher" => text(Some(self.publisher().name())),
+            "location" => text(self.publisher().location()),
+            "date" => pub_date(self.pub_date()),
+            "year" => owned(self.date().map(|d| d.year())),
+            "volume" => text(self.volume()),
+            "edition" => text(self.edition()),
+            "collection_title" => text(self.collection_title()),
+            "isbns" => texts(self.isbns()),
+            "authors" => people(self.authors()),
+            "editors" => people(self.editors()),
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::Write;
+
+use super::{ExportError, RecordWriter};
+use crate::pubmed::Record;
+
+/// Writes each record as a single line of JSON.
+pub struct JsonWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        JsonWriter { out }
+    }
+}
+
+impl<W: Write> RecordWriter for JsonWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        serde_json::to_writer(&mut self.out, record)?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
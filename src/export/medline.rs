@@ -0,0 +1,424 @@
+use std::io::Write;
+
+use super::{ExportError, RecordWriter};
+use crate::pubmed::{
+    Abstract, Author, ELocationID, Investigator, KeywordList, PartialDate, PubMedPubDate,
+    PubmedArticle, PubmedBookArticle, Record,
+};
+
+/// Longest line written, continuation indent included.
+const LINE_WIDTH: usize = 82;
+const INDENT: &str = "      ";
+
+/// Writes records in the MEDLINE display format of PubMed (`.nbib` files):
+/// one `TAG - value` line per field, long values wrapped onto lines
+/// indented by six spaces, and a blank line after each record. Deletions are
+/// skipped. The format has no tag for the authors of the book a chapter is
+/// in, so chapter records list the chapter authors only.
+pub struct MedlineWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> MedlineWriter<W> {
+    pub fn new(out: W) -> Self {
+        MedlineWriter { out }
+    }
+}
+
+impl<W: Write> RecordWriter for MedlineWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        let fields = match record {
+            Record::Article(article) => article_fields(article),
+            Record::BookArticle(book) => book_fields(book),
+            Record::DeleteCitation(_) | Record::DeleteDocument(_) => return Ok(()),
+        };
+        for (tag, value) in &fields.0 {
+            write_field(&mut self.out, tag, value)?;
+        }
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `TAG - value`, wrapping the value at word boundaries. A word
+/// longer than a line is not broken.
+fn write_field<W: Write>(out: &mut W, tag: &str, value: &str) -> Result<(), ExportError> {
+    let mut line = format!("{:<4}- ", tag);
+    let mut empty = true;
+    for word in value.split_whitespace() {
+        let width = line.chars().count();
+        if !empty && width + 1 + word.chars().count() > LINE_WIDTH {
+            writeln!(out, "{}", line)?;
+            line = INDENT.to_string();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        empty = false;
+    }
+    writeln!(out, "{}", line.trim_end())?;
+    Ok(())
+}
+
+struct Fields(Vec<(&'static str, String)>);
+
+impl Fields {
+    fn push<S: AsRef<str>>(&mut self, tag: &'static str, value: S) {
+        let value = value.as_ref().trim();
+        if !value.is_empty() {
+            self.0.push((tag, value.to_string()));
+        }
+    }
+
+    fn push_opt<S: AsRef<str>>(&mut self, tag: &'static str, value: Option<S>) {
+        if let Some(value) = value {
+            self.push(tag, value);
+        }
+    }
+
+    fn push_abstract(&mut self, summary: Option<&Abstract>) {
+        if let Some(summary) = summary {
+            let sections: Vec<String> = summary
+                .texts()
+                .iter()
+                .map(|t| match t.label() {
+                    Some(label) => format!("{}: {}", label, t.text()),
+                    None => t.text().to_plain(),
+                })
+                .collect();
+            self.push("AB", sections.join(" "));
+            self.push_opt("CI", summary.copyright_information());
+        }
+    }
+
+    /// `FAU`, `AU` and `AD` for each person (`FED`, `ED` for editors), and
+    /// `CN` for group authors.
+    fn push_authors<'a>(&mut self, authors: impl IntoIterator<Item = &'a Author>, editors: bool) {
+        let (full, short) = if editors {
+            ("FED", "ED")
+        } else {
+            ("FAU", "AU")
+        };
+        for author in authors.into_iter().filter(|a| a.is_valid()) {
+            match (author.last_name(), author.collective_name()) {
+                (Some(last), _) => {
+                    match author.fore_name() {
+                        Some(fore) => self.push(full, format!("{}, {}", last, fore)),
+                        None => self.push(full, last),
+                    }
+                    match author.initials() {
+                        Some(initials) => self.push(short, format!("{} {}", last, initials)),
+                        None => self.push(short, last),
+                    }
+                }
+                (None, Some(collective)) => self.push("CN", collective),
+                (None, None) => continue,
+            }
+            for affiliation in author.affiliations() {
+                self.push("AD", affiliation);
+            }
+        }
+    }
+
+    fn push_investigators(&mut self, investigators: &[Investigator]) {
+        for investigator in investigators.iter().filter(|i| i.is_valid()) {
+            let last = investigator.last_name();
+            match investigator.fore_name() {
+                Some(fore) => self.push("FIR", format!("{}, {}", last, fore)),
+                None => self.push("FIR", last),
+            }
+            match investigator.initials() {
+                Some(initials) => self.push("IR", format!("{} {}", last, initials)),
+                None => self.push("IR", last),
+            }
+            for affiliation in investigator.affiliations() {
+                self.push("IRAD", affiliation);
+            }
+        }
+    }
+
+    fn push_keywords(&mut self, lists: &[KeywordList]) {
+        for list in lists {
+            self.push_opt("OTO", list.owner());
+            for keyword in list.keywords() {
+                self.push_opt("OT", keyword.value());
+            }
+        }
+    }
+
+    /// `EDAT`, `MHDA` and `CRDT` from the `pubmed`, `medline` and `entrez`
+    /// history dates, then every history date as `PHST`.
+    fn push_history(&mut self, history: &[PubMedPubDate]) {
+        for (status, tag) in [("pubmed", "EDAT"), ("medline", "MHDA"), ("entrez", "CRDT")] {
+            if let Some(date) = history.iter().find(|d| d.pub_status() == status) {
+                self.push_opt(tag, history_date(date));
+            }
+        }
+        for date in history {
+            if let Some(value) = history_date(date) {
+                self.push("PHST", format!("{} [{}]", value, date.pub_status()));
+            }
+        }
+    }
+}
+
+/// `2016/05/06 06:00`
+fn history_date(date: &PubMedPubDate) -> Option<String> {
+    let day = date.date()?.first_day()?;
+    let number = |n: Option<&str>| n.and_then(|n| n.trim().parse::<u32>().ok()).unwrap_or(0);
+    Some(format!(
+        "{} {:02}:{:02}",
+        day.format("%Y/%m/%d"),
+        number(date.hour()),
+        number(date.minute())
+    ))
+}
+
+/// `20170101`
+fn compact_date(date: Option<PartialDate>) -> Option<String> {
+    date?.first_day().map(|d| d.format("%Y%m%d").to_string())
+}
+
+fn location_id(eid: &ELocationID) -> String {
+    format!("{} [{}]", eid.value(), eid.eid_type())
+}
+
+/// Tag for each `CommentsCorrections` type.
+fn comment_tag(ref_type: &str) -> Option<&'static str> {
+    let tag = match ref_type {
+        "AssociatedDataset" => "ADS",
+        "AssociatedPublication" => "APB",
+        "CommentOn" => "CON",
+        "CommentIn" => "CIN",
+        "ErratumIn" => "EIN",
+        "ErratumFor" => "EFR",
+        "ExpressionOfConcernIn" => "ECI",
+        "ExpressionOfConcernFor" => "ECF",
+        "CorrectedandRepublishedIn" => "CRI",
+        "CorrectedandRepublishedFrom" => "CRF",
+        "RetractionIn" => "RIN",
+        "RetractionOf" => "ROF",
+        "RepublishedIn" => "RPI",
+        "RepublishedFrom" => "RPF",
+        "UpdateIn" => "UIN",
+        "UpdateOf" => "UOF",
+        "SummaryForPatientsIn" => "SPIN",
+        "OriginalReportIn" => "ORI",
+        _ => return None,
+    };
+    Some(tag)
+}
+
+fn article_fields(article: &PubmedArticle) -> Fields {
+    let citation = article.medline_citation();
+    let a = article.article();
+    let journal = a.journal();
+    let issue = journal.journal_issue();
+    let info = citation.medline_journal_info();
+    let mut fields = Fields(Vec::new());
+
+    fields.push("PMID", &citation.pmid().value);
+    fields.push_opt("OWN", citation.owner());
+    fields.push("STAT", citation.status());
+    fields.push_opt(
+        "DCOM",
+        compact_date(citation.date_completed().and_then(|d| d.date())),
+    );
+    fields.push_opt(
+        "LR",
+        compact_date(citation.date_revised().and_then(|d| d.date())),
+    );
+    if let Some(issn) = journal.issn() {
+        fields.push("IS", format!("{} ({})", issn.value(), issn.issn_type()));
+    }
+    if let Some(linking) = info.issn_linking() {
+        fields.push("IS", format!("{} (Linking)", linking));
+    }
+    fields.push_opt("VI", issue.volume());
+    fields.push_opt("IP", issue.issue());
+    let date = issue
+        .pub_date()
+        .medline_date()
+        .map(str::to_string)
+        .or_else(|| journal.date().map(|d| d.to_string()));
+    fields.push_opt("DP", date.as_deref());
+    fields.push_opt("TI", a.title().map(|t| t.to_plain()));
+    fields.push_opt("PG", a.pages());
+    for eid in a.elocation_ids() {
+        fields.push("LID", location_id(eid));
+    }
+    fields.push_abstract(a.summary());
+    fields.push_authors(a.authors(), false);
+    for language in a.languages() {
+        fields.push("LA", language);
+    }
+    for bank in a.data_bank_list().map(|l| l.data_banks()).unwrap_or(&[]) {
+        for accession in bank.accession_numbers() {
+            fields.push(
+                "SI",
+                format!("{}/{}", bank.name().unwrap_or_default(), accession),
+            );
+        }
+    }
+    for grant in a.grants() {
+        let parts = [
+            grant.grant_id(),
+            grant.acronym(),
+            grant.agency(),
+            grant.country(),
+        ];
+        let parts: Vec<&str> = parts.into_iter().flatten().collect();
+        fields.push("GR", parts.join("/"));
+    }
+    for publication_type in a.publication_types() {
+        fields.push("PT", publication_type.value());
+    }
+    for date in a.article_dates() {
+        fields.push_opt("DEP", compact_date(date.date()));
+    }
+    fields.push_opt("PL", info.country());
+    fields.push_opt("TA", info.medline_ta());
+    fields.push_opt("JT", journal.title());
+    fields.push("JID", info.nlm_unique_id());
+    for chemical in citation.chemicals() {
+        fields.push(
+            "RN",
+            format!(
+                "{} ({})",
+                chemical.registry_number(),
+                chemical.substance().value()
+            ),
+        );
+    }
+    for subset in citation.citation_subsets() {
+        fields.push("SB", subset);
+    }
+    for comment in citation.comments_corrections() {
+        if let Some(tag) = comment_tag(comment.ref_type()) {
+            let mut value = comment.ref_source().unwrap_or_default().to_string();
+            if let Some(pmid) = comment.pmid() {
+                value = format!("{} PMID: {}", value, pmid.value);
+            }
+            fields.push(tag, value);
+        }
+    }
+    for heading in citation.mesh_headings() {
+        let mut value = heading.descriptor().value().to_string();
+        if heading.descriptor().is_major_topic() {
+            value.insert(0, '*');
+        }
+        for qualifier in heading.qualifiers() {
+            let star = if qualifier.is_major_topic() { "*" } else { "" };
+            value = format!("{}/{}{}", value, star, qualifier.value());
+        }
+        fields.push("MH", value);
+    }
+    fields.push_opt("PMC", article.pmc());
+    for other in citation.other_ids() {
+        fields.push("OID", format!("{}: {}", other.source(), other.value()));
+    }
+    fields.push_keywords(citation.keyword_lists());
+    fields.push_opt("COIS", citation.coi_statement().map(|c| c.to_plain()));
+    fields.push_investigators(citation.investigators());
+    for mission in citation.space_flight_missions() {
+        fields.push("SFM", mission);
+    }
+    for note in citation.general_notes() {
+        fields.push("GN", note.value());
+    }
+    if let Some(data) = article.pubmed_data() {
+        fields.push_history(data.history());
+        for id in data.article_id_list().ids() {
+            if !id.is_pubmed_id() && id.id_type() != "pmc" {
+                fields.push_opt(
+                    "AID",
+                    id.value().map(|v| format!("{} [{}]", v, id.id_type())),
+                );
+            }
+        }
+        fields.push("PST", data.publication_status());
+    }
+    fields.push("SO", source(article, date.as_deref()));
+    fields
+}
+
+/// `Nephrol Dial Transplant. 2016 Jul 4;31(7):1170-7. doi: 10.1093/ndt/gfw079.`
+fn source(article: &PubmedArticle, date: Option<&str>) -> String {
+    let a = article.article();
+    let issue = a.journal().journal_issue();
+    let journal = article
+        .medline_citation()
+        .medline_journal_info()
+        .medline_ta()
+        .or(a.journal().iso_abbreviation())
+        .or(a.journal().title())
+        .unwrap_or_default();
+    let mut so = format!("{}. {}", journal, date.unwrap_or_default());
+    if let Some(volume) = issue.volume() {
+        so = format!("{};{}", so, volume);
+    }
+    if let Some(number) = issue.issue() {
+        so = format!("{}({})", so, number);
+    }
+    if let Some(pages) = a.pages() {
+        so = format!("{}:{}", so, pages);
+    }
+    so.push('.');
+    if let Some(doi) = article.doi() {
+        so = format!("{} doi: {}.", so, doi);
+    }
+    so
+}
+
+fn book_fields(book_article: &PubmedBookArticle) -> Fields {
+    let document = book_article.book_document();
+    let book = document.book();
+    let mut fields = Fields(Vec::new());
+
+    fields.push("PMID", &document.pmid().value);
+    fields.push("STAT", "Publisher");
+    fields.push_opt("DP", book.date().map(|d| d.to_string()));
+    fields.push_opt("TI", document.title().map(|t| t.to_plain()));
+    fields.push("BTI", book.title().to_plain());
+    fields.push_opt("VTI", book.volume_title());
+    fields.push_opt("CTI", book.collection_title());
+    fields.push_opt("PG", document.pages());
+    for id in document.article_id_list().ids() {
+        if !id.is_pubmed_id() {
+            fields.push_opt(
+                "LID",
+                id.value().map(|v| format!("{} [{}]", v, id.id_type())),
+            );
+        }
+    }
+    fields.push_abstract(document.summary());
+    fields.push_authors(document.primary_authors(), false);
+    fields.push_authors(document.all_editors(), true);
+    for language in document.languages() {
+        fields.push("LA", language);
+    }
+    for publication_type in document.publication_types() {
+        fields.push("PT", publication_type.value());
+    }
+    fields.push_opt("PL", book.publisher().location());
+    fields.push("PB", book.publisher().name());
+    fields.push_opt("EN", book.edition());
+    fields.push_opt("VI", book.volume());
+    for isbn in book.isbns() {
+        fields.push("ISBN", isbn);
+    }
+    fields.push_keywords(document.keyword_lists());
+    fields.push_investigators(document.investigators());
+    if let Some(data) = book_article.pubmed_book_data() {
+        fields.push_history(data.history());
+        fields.push("PST", data.publication_status());
+    }
+    fields
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::dtd::DtdVersion;
+use crate::pubmed::{Author, Record};
+
+pub mod bibtex;
+pub mod csl;
+pub mod delimited;
+pub mod fields;
+pub mod json;
+pub mod medline;
+pub mod ndjson;
+pub mod parquet;
+pub mod ris;
+pub mod xml;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("parquet error: {0}")]
+    Parquet(#[from] ::parquet::errors::ParquetError),
+    #[error("arrow error: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+    #[error("xml error: {0}")]
+    Xml(String),
+    #[error("field path {0}")]
+    Field(String),
+    #[error("invalid PMID `{0}`")]
+    InvalidPmid(String),
+    #[error("{element} of PMIDs {pmids} has no place in {format} output")]
+    NotAllowed {
+        element: &'static str,
+        format: Format,
+        pmids: String,
+    },
+    #[error("{0} output is a directory, not a stream")]
+    NotAStream(Format),
+}
+
+impl ExportError {
+    /// True when only one record was left out and writing can go on.
+    pub fn is_record_error(&self) -> bool {
+        matches!(
+            self,
+            ExportError::InvalidPmid(_) | ExportError::NotAllowed { .. }
+        )
+    }
+}
+
+/// A sink that writes records in one output format.
+pub trait RecordWriter {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError>;
+
+    /// The DTD release of the records that follow, for formats that name
+    /// one; the others ignore it.
+    fn set_dtd_version(&mut self, _version: DtdVersion) {}
+
+    /// Writes any trailer and flushes the output.
+    fn finish(&mut self) -> Result<(), ExportError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON object per line, as serialized by the model's serde derives.
+    Json,
+    /// One JSON object per line in the versioned record schema, see
+    /// [`ndjson::JsonRecord`].
+    Ndjson,
+    /// RIS tagged records for reference managers.
+    Ris,
+    /// BibTeX entries.
+    Bibtex,
+    /// BibTeX entries with BibLaTeX fields, such as `doi` and `eprint`.
+    Biblatex,
+    /// A CSL-JSON array, as read by Pandoc and Zotero.
+    Csl,
+    /// The MEDLINE display format of PubMed, also known as nbib.
+    Medline,
+    /// A directory of Parquet tables, see [`parquet::ParquetWriter`].
+    Parquet,
+    /// A `PubmedArticleSet` document, as in the PubMed baseline.
+    Xml,
+    /// Comma-separated values, one line per record.
+    Csv,
+    /// Tab-separated values, one line per record.
+    Tsv,
+}
+
+impl Format {
+    pub const NAMES: &'static [&'static str] = &[
+        "json", "ndjson", "ris", "bibtex", "biblatex", "csl-json", "medline", "parquet", "xml",
+        "csv", "tsv",
+    ];
+
+    /// Whether the format writes a directory of files instead of one stream.
+    pub fn is_directory(&self) -> bool {
+        matches!(self, Format::Parquet)
+    }
+
+    /// The field delimiter of the delimited-text formats.
+    pub fn delimiter(&self) -> Option<char> {
+        match self {
+            Format::Csv => Some(','),
+            Format::Tsv => Some('\t'),
+            _ => None,
+        }
+    }
+
+    /// A writer to `out`, for the formats written to one stream. Delimited
+    /// formats get the default columns.
+    pub fn writer<'a, W: Write + 'a>(
+        &self,
+        out: W,
+    ) -> Result<Box<dyn RecordWriter + 'a>, ExportError> {
+        Ok(match self {
+            Format::Json => Box::new(json::JsonWriter::new(out)),
+            Format::Ndjson => Box::new(ndjson::NdjsonWriter::new(out)),
+            Format::Ris => Box::new(ris::RisWriter::new(out)),
+            Format::Bibtex => Box::new(bibtex::BibtexWriter::new(out)),
+            Format::Biblatex => Box::new(bibtex::BibtexWriter::new(out).with_biblatex(true)),
+            Format::Csl => Box::new(csl::CslWriter::new(out)),
+            Format::Medline => Box::new(medline::MedlineWriter::new(out)),
+            Format::Xml => Box::new(xml::XmlWriter::new(out)),
+            Format::Csv | Format::Tsv => Box::new(delimited::DelimitedWriter::new(
+                out,
+                self.delimiter().unwrap_or(','),
+            )),
+            Format::Parquet => return Err(ExportError::NotAStream(*self)),
+        })
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::Json => "json",
+            Format::Ndjson => "ndjson",
+            Format::Ris => "ris",
+            Format::Bibtex => "bibtex",
+            Format::Biblatex => "biblatex",
+            Format::Csl => "csl-json",
+            Format::Medline => "medline",
+            Format::Parquet => "parquet",
+            Format::Xml => "xml",
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "ris" => Ok(Format::Ris),
+            "bibtex" => Ok(Format::Bibtex),
+            "biblatex" => Ok(Format::Biblatex),
+            "csl-json" => Ok(Format::Csl),
+            "medline" | "nbib" => Ok(Format::Medline),
+            "parquet" => Ok(Format::Parquet),
+            "xml" => Ok(Format::Xml),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            _ => Err(format!(
+                "unknown format `{}`, expected one of: {}",
+                s,
+                Format::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Splits a MEDLINE page range such as `1170-7` into its first and last
+/// page, expanding the abbreviated last page to `1177`. Anything after the
+/// first range (`1170-7; discussion 1178`) is ignored.
+pub(crate) fn page_range(pages: &str) -> (String, Option<String>) {
+    let first = pages.split([',', ';']).next().unwrap_or(pages).trim();
+    let Some((start, end)) = first.split_once('-') else {
+        return (first.to_string(), None);
+    };
+    let (start, end) = (start.trim(), end.trim());
+    let numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let end = if numeric(start) && numeric(end) && end.len() < start.len() {
+        format!("{}{}", &start[..start.len() - end.len()], end)
+    } else {
+        end.to_string()
+    };
+    (start.to_string(), Some(end).filter(|e| !e.is_empty()))
+}
+
+/// `Last, Fore` (or `Last, Initials` without a fore name) for a person,
+/// or the collective name of a group.
+pub(crate) fn author_name(author: &Author) -> Option<String> {
+    match (author.last_name(), author.collective_name()) {
+        (Some(last), _) => match author.fore_name().or(author.initials()) {
+            Some(first) => Some(format!("{}, {}", last, first)),
+            None => Some(last.to_string()),
+        },
+        (None, collective) => collective.map(str::to_string),
+    }
+}
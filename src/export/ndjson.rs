@@ -0,0 +1,597 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+use super::{ExportError, RecordWriter};
+use crate::pubmed::{
+    Abstract, ArticleIdList, Author, KeywordList, PartialDate, PubDate, PubMedPubDate,
+    PubmedArticle, PubmedBookArticle, Record, ReferenceList, RichText, PMID,
+};
+
+/// Version of the NDJSON record schema, written in every record as
+/// `schema_version`. Fields may be added within a version; renaming,
+/// removing or retyping a field bumps it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `$id` of the JSON Schema for [`SCHEMA_VERSION`].
+pub const SCHEMA_ID: &str = "https://github.com/sdor/rbiblio/schema/record-v1.schema.json";
+
+/// Writes each record as one line of JSON in the versioned record schema,
+/// see [`JsonRecord`].
+pub struct NdjsonWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        NdjsonWriter { out }
+    }
+}
+
+impl<W: Write> RecordWriter for NdjsonWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        serde_json::to_writer(&mut self.out, &JsonRecord::from(record))?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// The JSON Schema of the records written by [`NdjsonWriter`].
+pub fn json_schema() -> serde_json::Value {
+    let mut schema =
+        serde_json::to_value(schemars::schema_for!(JsonRecord)).expect("a JSON Schema serializes");
+    require_all(&mut schema);
+    if let Some(object) = schema.as_object_mut() {
+        object.insert("$id".to_string(), SCHEMA_ID.into());
+    }
+    schema
+}
+
+/// Marks every property as required: the writer emits `null` for absent
+/// values instead of leaving the key out.
+fn require_all(schema: &mut serde_json::Value) {
+    match schema {
+        serde_json::Value::Object(object) => {
+            if let Some(serde_json::Value::Object(properties)) = object.get("properties") {
+                let names = properties.keys().cloned().map(serde_json::Value::from);
+                object.insert("required".to_string(), names.collect());
+            }
+            object.values_mut().for_each(require_all);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(require_all),
+        _ => {}
+    }
+}
+
+/// One record. Every record has `type` and `schema_version`; text fields
+/// are plain strings and absent values are `null`, lists are always present
+/// and may be empty. Dates are ISO 8601 to the known precision: `2016`,
+/// `2016-07` or `2016-07-04`.
+// as with `Record`, boxing the article variants would only add an allocation
+// per record
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonRecord {
+    Article(JsonArticle),
+    BookArticle(JsonBookArticle),
+    DeleteCitation(JsonDeletion),
+    DeleteDocument(JsonDeletion),
+}
+
+/// A `PubmedArticle`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonArticle {
+    pub schema_version: u32,
+    pub pmid: String,
+    pub pmid_version: String,
+    /// Citation status, e.g. `MEDLINE` or `PubMed-not-MEDLINE`.
+    pub status: String,
+    pub owner: Option<String>,
+    pub date_completed: Option<String>,
+    pub date_revised: Option<String>,
+    pub title: Option<String>,
+    /// The title with its inline markup, e.g. `<i>CYP3A5</i>`.
+    pub title_markup: Option<String>,
+    pub vernacular_title: Option<String>,
+    pub journal: JsonJournal,
+    pub pub_date: JsonPubDate,
+    pub pub_model: String,
+    pub pages: Option<String>,
+    pub elocation_ids: Vec<JsonId>,
+    #[serde(rename = "abstract")]
+    pub summary: Vec<JsonAbstractSection>,
+    pub copyright: Option<String>,
+    pub authors: Vec<JsonAuthor>,
+    pub languages: Vec<String>,
+    pub grants: Vec<JsonGrant>,
+    pub publication_types: Vec<String>,
+    pub article_dates: Vec<String>,
+    pub chemicals: Vec<JsonChemical>,
+    pub mesh: Vec<JsonMeshHeading>,
+    pub keywords: Vec<JsonKeyword>,
+    pub coi_statement: Option<String>,
+    pub doi: Option<String>,
+    pub pmc: Option<String>,
+    pub article_ids: Vec<JsonId>,
+    pub history: Vec<JsonHistoryDate>,
+    pub publication_status: Option<String>,
+    pub references: Vec<JsonReference>,
+}
+
+/// A `PubmedBookArticle`: a book, or a chapter of one when `title` is set.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonBookArticle {
+    pub schema_version: u32,
+    pub pmid: String,
+    pub pmid_version: String,
+    /// Title of the chapter; `null` when the document is the whole book.
+    pub title: Option<String>,
+    pub title_markup: Option<String>,
+    pub vernacular_title: Option<String>,
+    pub book: JsonBook,
+    pub pages: Option<String>,
+    #[serde(rename = "abstract")]
+    pub summary: Vec<JsonAbstractSection>,
+    pub copyright: Option<String>,
+    /// Authors of the chapter, or of the book when the document is the whole
+    /// book. The authors of the book a chapter is in are under `book`.
+    pub authors: Vec<JsonAuthor>,
+    /// Editors of the document and of its book.
+    pub editors: Vec<JsonAuthor>,
+    pub languages: Vec<String>,
+    pub publication_types: Vec<String>,
+    pub keywords: Vec<JsonKeyword>,
+    pub date_revised: Option<String>,
+    pub doi: Option<String>,
+    pub article_ids: Vec<JsonId>,
+    pub history: Vec<JsonHistoryDate>,
+    pub publication_status: Option<String>,
+    pub references: Vec<JsonReference>,
+}
+
+/// PMIDs removed by a `DeleteCitation` or `DeleteDocument`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonDeletion {
+    pub schema_version: u32,
+    pub pmids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonJournal {
+    pub title: Option<String>,
+    pub iso_abbreviation: Option<String>,
+    pub medline_ta: Option<String>,
+    pub nlm_unique_id: String,
+    pub country: Option<String>,
+    pub issn: Option<String>,
+    /// `Print` or `Electronic`.
+    pub issn_type: Option<String>,
+    pub issn_linking: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonBook {
+    pub title: String,
+    pub title_markup: String,
+    pub publisher: String,
+    pub location: Option<String>,
+    pub pub_date: JsonPubDate,
+    pub volume: Option<String>,
+    pub edition: Option<String>,
+    pub collection_title: Option<String>,
+    pub isbns: Vec<String>,
+    pub authors: Vec<JsonAuthor>,
+    pub editors: Vec<JsonAuthor>,
+}
+
+/// A publication date: `start` and, for ranges such as `1998 Mar-Apr`,
+/// `end`. `text` is the date as displayed by PubMed.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonPubDate {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub season: Option<String>,
+    pub text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonAbstractSection {
+    pub label: Option<String>,
+    pub category: Option<String>,
+    pub text: String,
+}
+
+/// A person, or a group when `collective_name` is set.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonAuthor {
+    pub last_name: Option<String>,
+    pub fore_name: Option<String>,
+    pub initials: Option<String>,
+    pub collective_name: Option<String>,
+    pub affiliations: Vec<String>,
+    pub valid: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonId {
+    #[serde(rename = "type")]
+    pub id_type: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonGrant {
+    pub id: Option<String>,
+    pub acronym: Option<String>,
+    pub agency: Option<String>,
+    pub country: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonChemical {
+    pub registry_number: String,
+    pub substance: String,
+    pub substance_ui: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonMeshHeading {
+    pub descriptor: String,
+    pub descriptor_ui: String,
+    pub major: bool,
+    pub qualifiers: Vec<JsonMeshQualifier>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonMeshQualifier {
+    pub name: String,
+    pub ui: String,
+    pub major: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonKeyword {
+    pub keyword: String,
+    /// Owner of the keyword list, e.g. `NOTNLM` for author keywords.
+    pub owner: Option<String>,
+    pub major: bool,
+}
+
+/// A date of the publication history, with `time` as `HH:MM` when given.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonHistoryDate {
+    pub status: String,
+    pub date: Option<String>,
+    pub time: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct JsonReference {
+    pub citation: Option<String>,
+    pub pmid: Option<String>,
+    pub doi: Option<String>,
+}
+
+fn owned(value: Option<&str>) -> Option<String> {
+    value.map(str::to_string)
+}
+
+fn strings<'a, I: IntoIterator<Item = &'a str>>(values: I) -> Vec<String> {
+    values.into_iter().map(str::to_string).collect()
+}
+
+/// `2016`, `2016-07` or `2016-07-04`.
+fn iso(date: &PartialDate) -> String {
+    match (date.month(), date.day()) {
+        (Some(month), Some(day)) => format!("{:04}-{:02}-{:02}", date.year(), month, day),
+        (Some(month), None) => format!("{:04}-{:02}", date.year(), month),
+        _ => format!("{:04}", date.year()),
+    }
+}
+
+fn pub_date(pub_date: &PubDate) -> JsonPubDate {
+    match pub_date.date() {
+        Some(date) => JsonPubDate {
+            start: Some(iso(date.start())),
+            end: date.end().map(iso),
+            season: owned(date.season()),
+            text: Some(
+                pub_date
+                    .medline_date()
+                    .map_or_else(|| date.to_string(), str::to_string),
+            ),
+        },
+        None => JsonPubDate {
+            start: None,
+            end: None,
+            season: None,
+            text: owned(pub_date.medline_date()),
+        },
+    }
+}
+
+fn markup(text: Option<&RichText>) -> (Option<String>, Option<String>) {
+    (text.map(|t| t.to_plain()), text.map(|t| t.to_markup()))
+}
+
+fn summary(summary: Option<&Abstract>) -> (Vec<JsonAbstractSection>, Option<String>) {
+    match summary {
+        Some(summary) => (
+            summary
+                .texts()
+                .iter()
+                .map(|t| JsonAbstractSection {
+                    label: owned(t.label()),
+                    category: owned(t.nlm_category()),
+                    text: t.text().to_plain(),
+                })
+                .collect(),
+            owned(summary.copyright_information()),
+        ),
+        None => (Vec::new(), None),
+    }
+}
+
+fn authors<'a, I: IntoIterator<Item = &'a Author>>(authors: I) -> Vec<JsonAuthor> {
+    authors
+        .into_iter()
+        .map(|a| JsonAuthor {
+            last_name: owned(a.last_name()),
+            fore_name: owned(a.fore_name()),
+            initials: owned(a.initials()),
+            collective_name: owned(a.collective_name()),
+            affiliations: strings(a.affiliations()),
+            valid: a.is_valid(),
+        })
+        .collect()
+}
+
+fn keywords(lists: &[KeywordList]) -> Vec<JsonKeyword> {
+    lists
+        .iter()
+        .flat_map(|list| {
+            list.keywords().iter().filter_map(move |k| {
+                Some(JsonKeyword {
+                    keyword: k.value()?.to_string(),
+                    owner: owned(list.owner()),
+                    major: k.is_major_topic(),
+                })
+            })
+        })
+        .collect()
+}
+
+fn article_ids(ids: &ArticleIdList) -> Vec<JsonId> {
+    ids.ids()
+        .iter()
+        .filter_map(|id| {
+            Some(JsonId {
+                id_type: id.id_type().to_string(),
+                value: id.value()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn history(dates: &[PubMedPubDate]) -> Vec<JsonHistoryDate> {
+    let number = |n: Option<&str>| n.and_then(|n| n.trim().parse::<u32>().ok());
+    dates
+        .iter()
+        .map(|d| JsonHistoryDate {
+            status: d.pub_status().to_string(),
+            date: d.date().as_ref().map(iso),
+            time: number(d.hour())
+                .map(|hour| format!("{:02}:{:02}", hour, number(d.minute()).unwrap_or(0))),
+        })
+        .collect()
+}
+
+/// References of all lists, nested lists included, in document order.
+fn references(lists: &[ReferenceList]) -> Vec<JsonReference> {
+    let mut out = Vec::new();
+    let mut pending: Vec<&ReferenceList> = lists.iter().rev().collect();
+    while let Some(list) = pending.pop() {
+        out.extend(list.references().iter().map(|r| JsonReference {
+            citation: owned(r.citation()),
+            pmid: r.pubmed_id().map(|p| p.to_string()),
+            doi: owned(r.article_id_list().and_then(|ids| ids.find("doi"))),
+        }));
+        pending.extend(list.reference_lists().iter().rev());
+    }
+    out
+}
+
+impl From<&PubmedArticle> for JsonArticle {
+    fn from(article: &PubmedArticle) -> Self {
+        let citation = article.medline_citation();
+        let a = article.article();
+        let journal = a.journal();
+        let issue = journal.journal_issue();
+        let info = citation.medline_journal_info();
+        let data = article.pubmed_data();
+        let (title, title_markup) = markup(a.title());
+        let (summary, copyright) = summary(a.summary());
+
+        JsonArticle {
+            schema_version: SCHEMA_VERSION,
+            pmid: citation.pmid().value.clone(),
+            pmid_version: citation.pmid().version.clone(),
+            status: citation.status().to_string(),
+            owner: owned(citation.owner()),
+            date_completed: citation
+                .date_completed()
+                .and_then(|d| d.date())
+                .as_ref()
+                .map(iso),
+            date_revised: citation
+                .date_revised()
+                .and_then(|d| d.date())
+                .as_ref()
+                .map(iso),
+            title,
+            title_markup,
+            vernacular_title: a.vernacular_title().map(|t| t.to_plain()),
+            journal: JsonJournal {
+                title: owned(journal.title()),
+                iso_abbreviation: owned(journal.iso_abbreviation()),
+                medline_ta: owned(info.medline_ta()),
+                nlm_unique_id: info.nlm_unique_id().to_string(),
+                country: owned(info.country()),
+                issn: owned(journal.issn().map(|i| i.value())),
+                issn_type: owned(journal.issn().map(|i| i.issn_type())),
+                issn_linking: owned(info.issn_linking()),
+                volume: owned(issue.volume()),
+                issue: owned(issue.issue()),
+            },
+            pub_date: pub_date(issue.pub_date()),
+            pub_model: a.pub_model().to_string(),
+            pages: owned(a.pages().as_deref()),
+            elocation_ids: a
+                .elocation_ids()
+                .iter()
+                .map(|e| JsonId {
+                    id_type: e.eid_type().to_string(),
+                    value: e.value().to_string(),
+                })
+                .collect(),
+            summary,
+            copyright,
+            authors: authors(a.authors()),
+            languages: strings(a.languages()),
+            grants: a
+                .grants()
+                .iter()
+                .map(|g| JsonGrant {
+                    id: owned(g.grant_id()),
+                    acronym: owned(g.acronym()),
+                    agency: owned(g.agency()),
+                    country: owned(g.country()),
+                })
+                .collect(),
+            publication_types: strings(a.publication_types().iter().map(|t| t.value())),
+            article_dates: a
+                .article_dates()
+                .iter()
+                .filter_map(|d| d.date())
+                .map(|d| iso(&d))
+                .collect(),
+            chemicals: citation
+                .chemicals()
+                .iter()
+                .map(|c| JsonChemical {
+                    registry_number: c.registry_number().to_string(),
+                    substance: c.substance().value().to_string(),
+                    substance_ui: c.substance().ui().to_string(),
+                })
+                .collect(),
+            mesh: citation
+                .mesh_headings()
+                .iter()
+                .map(|h| JsonMeshHeading {
+                    descriptor: h.descriptor().value().to_string(),
+                    descriptor_ui: h.descriptor().ui().to_string(),
+                    major: h.descriptor().is_major_topic(),
+                    qualifiers: h
+                        .qualifiers()
+                        .iter()
+                        .map(|q| JsonMeshQualifier {
+                            name: q.value().to_string(),
+                            ui: q.ui().to_string(),
+                            major: q.is_major_topic(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            keywords: keywords(citation.keyword_lists()),
+            coi_statement: citation.coi_statement().map(|c| c.to_plain()),
+            doi: owned(article.doi()),
+            pmc: owned(article.pmc()),
+            article_ids: data
+                .map(|d| article_ids(d.article_id_list()))
+                .unwrap_or_default(),
+            history: data.map(|d| history(d.history())).unwrap_or_default(),
+            publication_status: data.map(|d| d.publication_status().to_string()),
+            references: data
+                .map(|d| references(d.reference_lists()))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&PubmedBookArticle> for JsonBookArticle {
+    fn from(book_article: &PubmedBookArticle) -> Self {
+        let document = book_article.book_document();
+        let book = document.book();
+        let data = book_article.pubmed_book_data();
+        let (title, title_markup) = markup(document.title());
+        let (summary, copyright) = summary(document.summary());
+
+        JsonBookArticle {
+            schema_version: SCHEMA_VERSION,
+            pmid: document.pmid().value.clone(),
+            pmid_version: document.pmid().version.clone(),
+            title,
+            title_markup,
+            vernacular_title: document.vernacular_title().map(|t| t.to_plain()),
+            book: JsonBook {
+                title: book.title().to_plain(),
+                title_markup: book.title().to_markup(),
+                publisher: book.publisher().name().to_string(),
+                location: owned(book.publisher().location()),
+                pub_date: pub_date(book.pub_date()),
+                volume: owned(book.volume()),
+                edition: owned(book.edition()),
+                collection_title: owned(book.collection_title()),
+                isbns: strings(book.isbns()),
+                authors: authors(book.authors()),
+                editors: authors(book.editors()),
+            },
+            pages: owned(document.pages().as_deref()),
+            summary,
+            copyright,
+            authors: authors(document.primary_authors()),
+            editors: authors(document.all_editors()),
+            languages: strings(document.languages()),
+            publication_types: strings(document.publication_types().iter().map(|t| t.value())),
+            keywords: keywords(document.keyword_lists()),
+            date_revised: document
+                .date_revised()
+                .and_then(|d| d.date())
+                .as_ref()
+                .map(iso),
+            doi: owned(document.article_id_list().find("doi")),
+            article_ids: article_ids(document.article_id_list()),
+            history: data.map(|d| history(d.history())).unwrap_or_default(),
+            publication_status: data.map(|d| d.publication_status().to_string()),
+            references: references(document.reference_lists()),
+        }
+    }
+}
+
+fn deletion(pmids: &[PMID]) -> JsonDeletion {
+    JsonDeletion {
+        schema_version: SCHEMA_VERSION,
+        pmids: pmids.iter().map(|p| p.value.clone()).collect(),
+    }
+}
+
+impl From<&Record> for JsonRecord {
+    fn from(record: &Record) -> Self {
+        match record {
+            Record::Article(article) => JsonRecord::Article(article.into()),
+            Record::BookArticle(book) => JsonRecord::BookArticle(book.into()),
+            Record::DeleteCitation(delete) => JsonRecord::DeleteCitation(deletion(&delete.pmid)),
+            Record::DeleteDocument(delete) => {
+                JsonRecord::DeleteDocument(deletion(delete.pmid.as_deref().unwrap_or_default()))
+            }
+        }
+    }
+}
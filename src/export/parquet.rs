@@ -0,0 +1,641 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    ArrayBuilder, BooleanBuilder, Date32Builder, Int32Builder, StringBuilder, UInt32Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use super::{ExportError, RecordWriter};
+use crate::pubmed::{
+    Author, Chemical, Grant, MeshHeading, PubDate, PubmedArticle, PubmedBookArticle, Record,
+    ReferenceList,
+};
+
+/// Rows buffered per table before they are handed to the Parquet writer.
+const BATCH_ROWS: usize = 8192;
+
+/// Rows per row group of the files written by [`ParquetWriter::create`].
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 128 * 1024;
+
+/// Names of the tables, each written to `<name>.parquet`.
+pub const TABLES: &[&str] = &[
+    "articles",
+    "authors",
+    "affiliations",
+    "mesh",
+    "chemicals",
+    "grants",
+    "references",
+];
+
+use DataType::{Boolean, Date32, Int32, UInt32, Utf8};
+
+const ARTICLES: &[(&str, DataType, bool)] = &[
+    ("pmid", UInt32, false),
+    ("record_type", Utf8, false),
+    ("title", Utf8, true),
+    ("vernacular_title", Utf8, true),
+    ("journal_title", Utf8, true),
+    ("iso_abbreviation", Utf8, true),
+    ("medline_ta", Utf8, true),
+    ("nlm_unique_id", Utf8, true),
+    ("issn", Utf8, true),
+    ("issn_linking", Utf8, true),
+    ("book_title", Utf8, true),
+    ("publisher", Utf8, true),
+    ("isbn", Utf8, true),
+    ("volume", Utf8, true),
+    ("issue", Utf8, true),
+    ("pages", Utf8, true),
+    ("pub_year", Int32, true),
+    ("pub_date", Date32, true),
+    ("pub_date_text", Utf8, true),
+    ("languages", Utf8, true),
+    ("publication_types", Utf8, true),
+    ("keywords", Utf8, true),
+    ("abstract", Utf8, true),
+    ("doi", Utf8, true),
+    ("pmc", Utf8, true),
+    ("status", Utf8, true),
+    ("date_completed", Date32, true),
+    ("date_revised", Date32, true),
+    ("publication_status", Utf8, true),
+];
+
+const AUTHORS: &[(&str, DataType, bool)] = &[
+    ("pmid", UInt32, false),
+    ("position", UInt32, false),
+    ("role", Utf8, false),
+    ("level", Utf8, false),
+    ("last_name", Utf8, true),
+    ("fore_name", Utf8, true),
+    ("initials", Utf8, true),
+    ("collective_name", Utf8, true),
+    ("valid", Boolean, false),
+];
+
+const AFFILIATIONS: &[(&str, DataType, bool)] = &[
+    ("pmid", UInt32, false),
+    ("author_position", UInt32, false),
+    ("role", Utf8, false),
+    ("level", Utf8, false),
+    ("position", UInt32, false),
+    ("affiliation", Utf8, false),
+];
+
+const MESH: &[(&str, DataType, bool)] = &[
+    ("pmid", UInt32, false),
+    ("position", UInt32, false),
+    ("descriptor_ui", Utf8, false),
+    ("descriptor", Utf8, false),
+    ("descriptor_major", Boolean, false),
+    ("qualifier_ui", Utf8, true),
+    ("qualifier", Utf8, true),
+    ("qualifier_major", Boolean, true),
+];
+
+const CHEMICALS: &[(&str, DataType, bool)] = &[
+    ("pmid", UInt32, false),
+    ("position", UInt32, false),
+    ("registry_number", Utf8, false),
+    ("substance_ui", Utf8, false),
+    ("substance", Utf8, false),
+];
+
+const GRANTS: &[(&str, DataType, bool)] = &[
+    ("pmid", UInt32, false),
+    ("position", UInt32, false),
+    ("grant_id", Utf8, true),
+    ("acronym", Utf8, true),
+    ("agency", Utf8, true),
+    ("country", Utf8, true),
+];
+
+const REFERENCES: &[(&str, DataType, bool)] = &[
+    ("pmid", UInt32, false),
+    ("position", UInt32, false),
+    ("list_title", Utf8, true),
+    ("citation", Utf8, true),
+    ("cited_pmid", UInt32, true),
+    ("cited_doi", Utf8, true),
+];
+
+/// A cell of a row; the variant matches the column type.
+enum Value<'a> {
+    Str(Option<&'a str>),
+    U32(Option<u32>),
+    I32(Option<i32>),
+    Bool(Option<bool>),
+    Date(Option<NaiveDate>),
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(value: &'a str) -> Self {
+        Value::Str(Some(value))
+    }
+}
+
+impl<'a> From<Option<&'a str>> for Value<'a> {
+    fn from(value: Option<&'a str>) -> Self {
+        Value::Str(value)
+    }
+}
+
+impl From<u32> for Value<'_> {
+    fn from(value: u32) -> Self {
+        Value::U32(Some(value))
+    }
+}
+
+impl From<Option<u32>> for Value<'_> {
+    fn from(value: Option<u32>) -> Self {
+        Value::U32(value)
+    }
+}
+
+impl From<Option<i32>> for Value<'_> {
+    fn from(value: Option<i32>) -> Self {
+        Value::I32(value)
+    }
+}
+
+impl From<bool> for Value<'_> {
+    fn from(value: bool) -> Self {
+        Value::Bool(Some(value))
+    }
+}
+
+impl From<Option<bool>> for Value<'_> {
+    fn from(value: Option<bool>) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<Option<NaiveDate>> for Value<'_> {
+    fn from(value: Option<NaiveDate>) -> Self {
+        Value::Date(value)
+    }
+}
+
+enum Column {
+    Str(StringBuilder),
+    U32(UInt32Builder),
+    I32(Int32Builder),
+    Bool(BooleanBuilder),
+    Date(Date32Builder),
+}
+
+impl Column {
+    fn new(data_type: &DataType) -> Column {
+        match data_type {
+            Utf8 => Column::Str(StringBuilder::new()),
+            UInt32 => Column::U32(UInt32Builder::new()),
+            Int32 => Column::I32(Int32Builder::new()),
+            Boolean => Column::Bool(BooleanBuilder::new()),
+            Date32 => Column::Date(Date32Builder::new()),
+            other => unreachable!("no column builder for {}", other),
+        }
+    }
+
+    fn append(&mut self, value: Value) {
+        match (self, value) {
+            (Column::Str(b), Value::Str(v)) => b.append_option(v),
+            (Column::U32(b), Value::U32(v)) => b.append_option(v),
+            (Column::I32(b), Value::I32(v)) => b.append_option(v),
+            (Column::Bool(b), Value::Bool(v)) => b.append_option(v),
+            (Column::Date(b), Value::Date(v)) => b.append_option(v.map(days_since_epoch)),
+            _ => unreachable!("value does not match the column type"),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Column::Str(b) => Arc::new(b.finish()),
+            Column::U32(b) => Arc::new(b.finish()),
+            Column::I32(b) => Arc::new(b.finish()),
+            Column::Bool(b) => Arc::new(b.finish()),
+            Column::Date(b) => Arc::new(b.finish()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Column::Str(b) => b.len(),
+            Column::U32(b) => b.len(),
+            Column::I32(b) => b.len(),
+            Column::Bool(b) => b.len(),
+            Column::Date(b) => b.len(),
+        }
+    }
+}
+
+fn days_since_epoch(date: NaiveDate) -> i32 {
+    (date - NaiveDate::default()).num_days() as i32
+}
+
+/// One Parquet file: rows are buffered in column builders and written as a
+/// record batch every [`BATCH_ROWS`] rows.
+struct Table<W: Write + Send> {
+    schema: SchemaRef,
+    columns: Vec<Column>,
+    writer: ArrowWriter<W>,
+}
+
+impl<W: Write + Send> Table<W> {
+    fn new(
+        out: W,
+        fields: &[(&str, DataType, bool)],
+        properties: WriterProperties,
+    ) -> Result<Table<W>, ExportError> {
+        let schema: SchemaRef = Arc::new(Schema::new(
+            fields
+                .iter()
+                .map(|(name, data_type, nullable)| Field::new(*name, data_type.clone(), *nullable))
+                .collect::<Vec<_>>(),
+        ));
+        let columns = fields.iter().map(|(_, t, _)| Column::new(t)).collect();
+        let writer = ArrowWriter::try_new(out, schema.clone(), Some(properties))?;
+        Ok(Table {
+            schema,
+            columns,
+            writer,
+        })
+    }
+
+    fn push(&mut self, row: Vec<Value>) -> Result<(), ExportError> {
+        debug_assert_eq!(row.len(), self.columns.len());
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            column.append(value);
+        }
+        if self.columns[0].len() >= BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ExportError> {
+        if self.columns[0].len() == 0 {
+            return Ok(());
+        }
+        let arrays = self.columns.iter_mut().map(Column::finish).collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)?;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), ExportError> {
+        self.flush()?;
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Writes records as a set of Parquet tables keyed by PMID: one row per
+/// article or book article in `articles`, and child tables for authors,
+/// affiliations, MeSH headings (one row per qualifier), chemicals, grants
+/// and references. Authors and editors have a `level`: `document` for those
+/// of the article or book document, `book` for those of the book a document
+/// is in.
+///
+/// Rows are written in row groups as records arrive, so memory use does not
+/// grow with the number of records. Deletions are skipped, and so is a
+/// record whose PMID is not a number: it fails with
+/// `ExportError::InvalidPmid`, a record error, before any of its rows are
+/// written, and the next record can be written as usual.
+pub struct ParquetWriter<W: Write + Send> {
+    articles: Table<W>,
+    authors: Table<W>,
+    affiliations: Table<W>,
+    mesh: Table<W>,
+    chemicals: Table<W>,
+    grants: Table<W>,
+    references: Table<W>,
+}
+
+impl ParquetWriter<File> {
+    /// Creates `dir` if needed and one `<table>.parquet` file in it for each
+    /// of [`TABLES`].
+    pub fn create<P: AsRef<Path>>(dir: P) -> Result<Self, ExportError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        ParquetWriter::new(
+            |table| File::create(dir.join(format!("{}.parquet", table))),
+            DEFAULT_ROW_GROUP_SIZE,
+        )
+    }
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Writes each table to the output `open` returns for its name, in row
+    /// groups of at most `row_group_size` rows.
+    pub fn new<F>(mut open: F, row_group_size: usize) -> Result<Self, ExportError>
+    where
+        F: FnMut(&str) -> std::io::Result<W>,
+    {
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(row_group_size.max(1))
+            .build();
+        let mut table = |name: &str, fields| Table::new(open(name)?, fields, properties.clone());
+        Ok(ParquetWriter {
+            articles: table("articles", ARTICLES)?,
+            authors: table("authors", AUTHORS)?,
+            affiliations: table("affiliations", AFFILIATIONS)?,
+            mesh: table("mesh", MESH)?,
+            chemicals: table("chemicals", CHEMICALS)?,
+            grants: table("grants", GRANTS)?,
+            references: table("references", REFERENCES)?,
+        })
+    }
+
+    fn tables(&mut self) -> [&mut Table<W>; 7] {
+        [
+            &mut self.articles,
+            &mut self.authors,
+            &mut self.affiliations,
+            &mut self.mesh,
+            &mut self.chemicals,
+            &mut self.grants,
+            &mut self.references,
+        ]
+    }
+
+    fn write_article(&mut self, article: &PubmedArticle) -> Result<(), ExportError> {
+        let citation = article.medline_citation();
+        let a = article.article();
+        let journal = a.journal();
+        let issue = journal.journal_issue();
+        let info = citation.medline_journal_info();
+        let data = article.pubmed_data();
+        let pmid = pmid(article.pubmed_id(), &citation.pmid().value)?;
+        let title = a.title().map(|t| t.to_plain());
+        let vernacular_title = a.vernacular_title().map(|t| t.to_plain());
+        let (pub_year, pub_date, pub_date_text) = pub_date(issue.pub_date());
+        let summary = a.summary().map(|s| s.text());
+
+        self.articles.push(vec![
+            pmid.into(),
+            "article".into(),
+            title.as_deref().into(),
+            vernacular_title.as_deref().into(),
+            journal.title().into(),
+            journal.iso_abbreviation().into(),
+            info.medline_ta().into(),
+            Some(info.nlm_unique_id()).into(),
+            journal.issn().map(|i| i.value()).into(),
+            info.issn_linking().into(),
+            Value::Str(None),
+            Value::Str(None),
+            Value::Str(None),
+            issue.volume().into(),
+            issue.issue().into(),
+            a.pages().as_deref().into(),
+            pub_year.into(),
+            pub_date.into(),
+            pub_date_text.as_deref().into(),
+            joined(a.languages()).as_deref().into(),
+            joined(a.publication_types().iter().map(|t| t.value()))
+                .as_deref()
+                .into(),
+            joined(citation.keywords()).as_deref().into(),
+            summary.as_deref().into(),
+            article.doi().into(),
+            article.pmc().into(),
+            Some(citation.status()).into(),
+            first_day(citation.date_completed().and_then(|d| d.date())).into(),
+            first_day(citation.date_revised().and_then(|d| d.date())).into(),
+            data.map(|d| d.publication_status()).into(),
+        ])?;
+        self.write_authors(pmid, "author", "document", a.authors())?;
+        self.write_mesh(pmid, citation.mesh_headings())?;
+        self.write_chemicals(pmid, citation.chemicals())?;
+        self.write_grants(pmid, a.grants())?;
+        if let Some(data) = data {
+            self.write_references(pmid, data.reference_lists())?;
+        }
+        Ok(())
+    }
+
+    fn write_book(&mut self, book_article: &PubmedBookArticle) -> Result<(), ExportError> {
+        let document = book_article.book_document();
+        let book = document.book();
+        let pmid = pmid(book_article.pubmed_id(), &document.pmid().value)?;
+        let book_title = book.title().to_plain();
+        let title = document.title().map(|t| t.to_plain());
+        let vernacular_title = document.vernacular_title().map(|t| t.to_plain());
+        let (pub_year, pub_date, pub_date_text) = pub_date(book.pub_date());
+        let summary = document.summary().map(|s| s.text());
+        let keywords = document
+            .keyword_lists()
+            .iter()
+            .flat_map(|l| l.keywords())
+            .filter_map(|k| k.value());
+
+        self.articles.push(vec![
+            pmid.into(),
+            "book".into(),
+            title.as_deref().or(Some(&book_title)).into(),
+            vernacular_title.as_deref().into(),
+            Value::Str(None),
+            Value::Str(None),
+            Value::Str(None),
+            Value::Str(None),
+            Value::Str(None),
+            Value::Str(None),
+            Some(book_title.as_str()).into(),
+            Some(book.publisher().name()).into(),
+            book.isbns().first().copied().into(),
+            book.volume().into(),
+            Value::Str(None),
+            document.pages().as_deref().into(),
+            pub_year.into(),
+            pub_date.into(),
+            pub_date_text.as_deref().into(),
+            joined(document.languages()).as_deref().into(),
+            joined(document.publication_types().iter().map(|t| t.value()))
+                .as_deref()
+                .into(),
+            joined(keywords).as_deref().into(),
+            summary.as_deref().into(),
+            document.article_id_list().find("doi").into(),
+            Value::Str(None),
+            Value::Str(None),
+            Value::Date(None),
+            first_day(document.date_revised().and_then(|d| d.date())).into(),
+            book_article
+                .pubmed_book_data()
+                .map(|d| d.publication_status())
+                .into(),
+        ])?;
+        self.write_authors(pmid, "author", "document", document.authors())?;
+        self.write_authors(pmid, "editor", "document", document.editors())?;
+        self.write_authors(pmid, "author", "book", book.authors())?;
+        self.write_authors(pmid, "editor", "book", book.editors())?;
+        if let Some(grants) = document.grant_list() {
+            self.write_grants(pmid, grants.grants())?;
+        }
+        self.write_references(pmid, document.reference_lists())?;
+        Ok(())
+    }
+
+    fn write_authors<'a>(
+        &mut self,
+        pmid: u32,
+        role: &str,
+        level: &str,
+        authors: impl IntoIterator<Item = &'a Author>,
+    ) -> Result<(), ExportError> {
+        for (position, author) in (1u32..).zip(authors) {
+            self.authors.push(vec![
+                pmid.into(),
+                position.into(),
+                role.into(),
+                level.into(),
+                author.last_name().into(),
+                author.fore_name().into(),
+                author.initials().into(),
+                author.collective_name().into(),
+                author.is_valid().into(),
+            ])?;
+            for (n, affiliation) in (1u32..).zip(author.affiliations()) {
+                self.affiliations.push(vec![
+                    pmid.into(),
+                    position.into(),
+                    role.into(),
+                    level.into(),
+                    n.into(),
+                    affiliation.into(),
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_mesh(&mut self, pmid: u32, headings: &[MeshHeading]) -> Result<(), ExportError> {
+        for (position, heading) in (1u32..).zip(headings) {
+            let descriptor = heading.descriptor();
+            let mut row = |qualifier: Option<&crate::pubmed::QualifierName>| {
+                self.mesh.push(vec![
+                    pmid.into(),
+                    position.into(),
+                    descriptor.ui().into(),
+                    descriptor.value().into(),
+                    descriptor.is_major_topic().into(),
+                    qualifier.map(|q| q.ui()).into(),
+                    qualifier.map(|q| q.value()).into(),
+                    qualifier.map(|q| q.is_major_topic()).into(),
+                ])
+            };
+            match heading.qualifiers() {
+                [] => row(None)?,
+                qualifiers => {
+                    for qualifier in qualifiers {
+                        row(Some(qualifier))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_chemicals(&mut self, pmid: u32, chemicals: &[Chemical]) -> Result<(), ExportError> {
+        for (position, chemical) in (1u32..).zip(chemicals) {
+            self.chemicals.push(vec![
+                pmid.into(),
+                position.into(),
+                chemical.registry_number().into(),
+                chemical.substance().ui().into(),
+                chemical.substance().value().into(),
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn write_grants(&mut self, pmid: u32, grants: &[Grant]) -> Result<(), ExportError> {
+        for (position, grant) in (1u32..).zip(grants) {
+            self.grants.push(vec![
+                pmid.into(),
+                position.into(),
+                grant.grant_id().into(),
+                grant.acronym().into(),
+                grant.agency().into(),
+                grant.country().into(),
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// References of all lists, nested lists included, numbered in
+    /// document order.
+    fn write_references(&mut self, pmid: u32, lists: &[ReferenceList]) -> Result<(), ExportError> {
+        let mut pending: Vec<&ReferenceList> = lists.iter().rev().collect();
+        let mut position = 0u32;
+        while let Some(list) = pending.pop() {
+            for reference in list.references() {
+                position += 1;
+                let ids = reference.article_id_list();
+                self.references.push(vec![
+                    pmid.into(),
+                    position.into(),
+                    list.title().into(),
+                    reference.citation().into(),
+                    reference.pubmed_id().into(),
+                    ids.and_then(|ids| ids.find("doi")).into(),
+                ])?;
+            }
+            pending.extend(list.reference_lists().iter().rev());
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> RecordWriter for ParquetWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        match record {
+            Record::Article(article) => self.write_article(article),
+            Record::BookArticle(book) => self.write_book(book),
+            Record::DeleteCitation(_) | Record::DeleteDocument(_) => Ok(()),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        for table in self.tables() {
+            table.close()?;
+        }
+        Ok(())
+    }
+}
+
+fn pmid(parsed: Result<u32, std::num::ParseIntError>, value: &str) -> Result<u32, ExportError> {
+    parsed.map_err(|_| ExportError::InvalidPmid(value.to_string()))
+}
+
+fn first_day(date: Option<crate::pubmed::PartialDate>) -> Option<NaiveDate> {
+    date.and_then(|d| d.first_day())
+}
+
+/// Year, first day and display form of a publication date; the display
+/// form of an unreadable `MedlineDate` is the date as given.
+fn pub_date(pub_date: &PubDate) -> (Option<i32>, Option<NaiveDate>, Option<String>) {
+    match pub_date.date() {
+        Some(date) => (
+            Some(date.year()),
+            date.to_naive_date(),
+            Some(date.to_string()),
+        ),
+        None => (None, None, pub_date.medline_date().map(str::to_string)),
+    }
+}
+
+fn joined<'a, I: IntoIterator<Item = &'a str>>(values: I) -> Option<String> {
+    let values: Vec<&str> = values.into_iter().collect();
+    Some(values.join("; ")).filter(|v| !v.is_empty())
+}
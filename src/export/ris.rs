@@ -0,0 +1,222 @@
+use std::io::Write;
+
+use super::{author_name, page_range, ExportError, RecordWriter};
+use crate::pubmed::{
+    Abstract, Author, KeywordList, PubDate, PubmedArticle, PubmedBookArticle, Record,
+};
+
+/// Writes articles as `JOUR` records and book articles as `BOOK`, or `CHAP`
+/// when the document is a chapter of the book. Deletions have no RIS form
+/// and are skipped. RIS has no tag for the authors of the book a chapter is
+/// in, so only the chapter authors are written for `CHAP` records.
+///
+/// Lines end with CR LF as in the RIS specification, and values are kept on
+/// one line.
+pub struct RisWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> RisWriter<W> {
+    pub fn new(out: W) -> Self {
+        RisWriter { out }
+    }
+}
+
+impl<W: Write> RecordWriter for RisWriter<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        let entry = match record {
+            Record::Article(article) => article_entry(article),
+            Record::BookArticle(book) => book_entry(book),
+            Record::DeleteCitation(_) | Record::DeleteDocument(_) => return Ok(()),
+        };
+        entry.write_to(&mut self.out)
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Tag and value pairs of one RIS record, in output order.
+struct Entry {
+    tags: Vec<(&'static str, String)>,
+}
+
+impl Entry {
+    fn new(record_type: &str) -> Entry {
+        Entry {
+            tags: vec![("TY", record_type.to_string())],
+        }
+    }
+
+    fn push<S: AsRef<str>>(&mut self, tag: &'static str, value: S) {
+        let value = value
+            .as_ref()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !value.is_empty() {
+            self.tags.push((tag, value));
+        }
+    }
+
+    fn push_opt<S: AsRef<str>>(&mut self, tag: &'static str, value: Option<S>) {
+        if let Some(value) = value {
+            self.push(tag, value);
+        }
+    }
+
+    fn push_date(&mut self, date: &PubDate) {
+        let Some(date) = date.date() else {
+            return;
+        };
+        self.push("PY", date.year().to_string());
+        let part = |n: Option<u32>| n.map(|n| format!("{:02}", n)).unwrap_or_default();
+        self.push(
+            "DA",
+            format!(
+                "{:04}/{}/{}/{}",
+                date.year(),
+                part(date.month()),
+                part(date.day()),
+                date.season().unwrap_or_default()
+            ),
+        );
+    }
+
+    fn push_pages(&mut self, pages: Option<&str>) {
+        if let Some(pages) = pages {
+            let (start, end) = page_range(pages);
+            self.push("SP", start);
+            self.push_opt("EP", end);
+        }
+    }
+
+    fn push_authors<'a>(
+        &mut self,
+        tag: &'static str,
+        authors: impl IntoIterator<Item = &'a Author>,
+    ) {
+        for author in authors.into_iter().filter(|a| a.is_valid()) {
+            self.push_opt(tag, author_name(author));
+        }
+    }
+
+    fn push_abstract(&mut self, summary: Option<&Abstract>) {
+        if let Some(summary) = summary {
+            self.push("AB", summary.text());
+        }
+    }
+
+    fn push_keywords(&mut self, lists: &[KeywordList]) {
+        for keyword in lists.iter().flat_map(|l| l.keywords()) {
+            self.push_opt("KW", keyword.value());
+        }
+    }
+
+    fn push_pubmed_id(&mut self, pmid: Result<u32, std::num::ParseIntError>) {
+        if let Ok(pmid) = pmid {
+            self.push("AN", pmid.to_string());
+            self.push("DB", "PubMed");
+            self.push("UR", format!("https://pubmed.ncbi.nlm.nih.gov/{}/", pmid));
+        }
+    }
+
+    fn write_to<W: Write>(&self, out: &mut W) -> Result<(), ExportError> {
+        for (tag, value) in &self.tags {
+            write!(out, "{}  - {}\r\n", tag, value)?;
+        }
+        out.write_all(b"ER  - \r\n\r\n")?;
+        Ok(())
+    }
+}
+
+fn article_entry(article: &PubmedArticle) -> Entry {
+    let citation = article.medline_citation();
+    let a = article.article();
+    let journal = a.journal();
+    let issue = journal.journal_issue();
+
+    let mut entry = Entry::new("JOUR");
+    entry.push_opt("TI", a.title().map(|t| t.to_plain()));
+    entry.push_authors("AU", a.authors());
+    entry.push_date(issue.pub_date());
+    entry.push_opt("T2", journal.title());
+    entry.push_opt(
+        "J2",
+        journal
+            .iso_abbreviation()
+            .or(citation.medline_journal_info().medline_ta()),
+    );
+    entry.push_opt("VL", issue.volume());
+    entry.push_opt("IS", issue.issue());
+    entry.push_pages(a.pages().as_deref());
+    entry.push_opt("SN", journal.issn().map(|i| i.value()));
+    for language in a.languages() {
+        entry.push("LA", language);
+    }
+    entry.push_abstract(a.summary());
+    entry.push_keywords(citation.keyword_lists());
+    for heading in citation.mesh_headings() {
+        let descriptor = heading.descriptor().value();
+        match heading.qualifiers() {
+            [] => entry.push("KW", descriptor),
+            qualifiers => {
+                for qualifier in qualifiers {
+                    entry.push("KW", format!("{}/{}", descriptor, qualifier.value()));
+                }
+            }
+        }
+    }
+    entry.push_opt("DO", article.doi());
+    entry.push_pubmed_id(article.pubmed_id());
+    entry.push_opt("C2", article.pmc());
+    entry
+}
+
+fn book_entry(book_article: &PubmedBookArticle) -> Entry {
+    let document = book_article.book_document();
+    let book = document.book();
+    let book_title = book.title().to_plain();
+
+    let mut entry = match document.title() {
+        Some(title) => {
+            let mut entry = Entry::new("CHAP");
+            entry.push("TI", title.to_plain());
+            entry.push("T2", &book_title);
+            entry
+        }
+        None => {
+            let mut entry = Entry::new("BOOK");
+            entry.push("TI", &book_title);
+            entry
+        }
+    };
+    entry.push_authors("AU", document.primary_authors());
+    entry.push_authors("A2", document.all_editors());
+    entry.push_opt("T3", book.collection_title());
+    entry.push_date(book.pub_date());
+    entry.push_opt("VL", book.volume());
+    entry.push_opt("ET", book.edition());
+    entry.push("PB", book.publisher().name());
+    entry.push_opt("CY", book.publisher().location());
+    for isbn in book.isbns() {
+        entry.push("SN", isbn);
+    }
+    entry.push_pages(document.pages().as_deref());
+    for language in document.languages() {
+        entry.push("LA", language);
+    }
+    entry.push_abstract(document.summary());
+    entry.push_keywords(document.keyword_lists());
+    let doi = document.article_id_list().find("doi").or_else(|| {
+        book.elocation_ids()
+            .iter()
+            .find(|e| e.eid_type() == "doi")
+            .map(|e| e.value())
+    });
+    entry.push_opt("DO", doi);
+    entry.push_pubmed_id(book_article.pubmed_id());
+    entry
+}
@@ -0,0 +1,518 @@
+use serde::ser::{self, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::Write;
+
+use super::{ExportError, Format, RecordWriter};
+use crate::dtd::DtdVersion;
+use crate::pubmed::{Record, RichText, ELEMENT_TEXT_NEWTYPE, PMID, RICH_TEXT_NEWTYPE};
+
+/// Writes records back as a `PubmedArticleSet` document in the format of
+/// the PubMed baseline: the `DOCTYPE` of the DTD release the records were
+/// read from, see `RecordWriter::set_dtd_version`, elements and attributes
+/// in the order of the model, which follows the DTD, and inline markup and
+/// MathML as elements.
+///
+/// Articles are written as they arrive. Deleted PMIDs are collected and
+/// written at the end in one `DeleteCitation`, where the DTD expects them.
+/// A `DeleteDocument` belongs to a `BookDocumentSet`, so it is left out with
+/// an `ExportError::NotAllowed`.
+pub struct XmlWriter<W: Write> {
+    out: W,
+    dtd_version: DtdVersion,
+    started: bool,
+    deleted_citations: Vec<PMID>,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub fn new(out: W) -> Self {
+        XmlWriter {
+            out,
+            dtd_version: DtdVersion::PUBMED_190101,
+            started: false,
+            deleted_citations: Vec::new(),
+        }
+    }
+
+    /// Sets the DTD release named in the `DOCTYPE`.
+    pub fn with_dtd_version(mut self, version: DtdVersion) -> Self {
+        self.dtd_version = version;
+        self
+    }
+
+    fn start(&mut self) -> Result<(), ExportError> {
+        if !self.started {
+            self.started = true;
+            writeln!(self.out, "<?xml version=\"1.0\" ?>")?;
+            writeln!(
+                self.out,
+                "<!DOCTYPE PubmedArticleSet PUBLIC \"{}\" \"{}\">",
+                self.dtd_version.public_id(),
+                self.dtd_version.system_id()
+            )?;
+            writeln!(self.out, "<PubmedArticleSet>")?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordWriter for XmlWriter<W> {
+    /// Names the release in the `DOCTYPE` unless the first record has been
+    /// written; the 2019 one is named when no release was set by then.
+    fn set_dtd_version(&mut self, version: DtdVersion) {
+        if !self.started {
+            self.dtd_version = version;
+        }
+    }
+
+    fn write_record(&mut self, record: &Record) -> Result<(), ExportError> {
+        self.start()?;
+        let xml = match record {
+            Record::Article(article) => to_xml("PubmedArticle", article, 1)?,
+            Record::BookArticle(book) => to_xml("PubmedBookArticle", book, 1)?,
+            Record::DeleteCitation(delete) => {
+                self.deleted_citations.extend(delete.pmid.iter().cloned());
+                return Ok(());
+            }
+            Record::DeleteDocument(delete) => {
+                let pmids: Vec<&str> = delete
+                    .pmid
+                    .iter()
+                    .flatten()
+                    .map(|p| p.value.as_str())
+                    .collect();
+                if pmids.is_empty() {
+                    return Ok(());
+                }
+                return Err(ExportError::NotAllowed {
+                    element: "DeleteDocument",
+                    format: Format::Xml,
+                    pmids: pmids.join(", "),
+                });
+            }
+        };
+        self.out.write_all(xml.as_bytes())?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ExportError> {
+        self.start()?;
+        if !self.deleted_citations.is_empty() {
+            let xml = to_xml("DeleteCitation", &Deletion(&self.deleted_citations), 1)?;
+            self.out.write_all(xml.as_bytes())?;
+        }
+        writeln!(self.out, "</PubmedArticleSet>")?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// The `PMID` list of a `DeleteCitation`.
+struct Deletion<'a>(&'a [PMID]);
+
+impl Serialize for Deletion<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Deletion", 1)?;
+        state.serialize_field("PMID", self.0)?;
+        state.end()
+    }
+}
+
+/// Serializes `value` as the element `name`, indented two spaces per
+/// `depth`. Fields renamed `@Name` become attributes and `$value` the text
+/// of the element; other fields are child elements, repeated for lists and
+/// left out when `None`.
+pub fn to_xml<T: Serialize + ?Sized>(
+    name: &str,
+    value: &T,
+    depth: usize,
+) -> Result<String, ExportError> {
+    let value = value
+        .serialize(ValueSerializer)
+        .map_err(|e| ExportError::Xml(e.0))?;
+    let mut out = String::new();
+    write_element(&mut out, name, &value, depth);
+    Ok(out)
+}
+
+/// The model as a tree: fields keep their serde names and order.
+enum Value {
+    Null,
+    Text(String),
+    /// XML content written as is, from rich text.
+    Xml(String),
+    Seq(Vec<Value>),
+    /// Fields of a struct, or entries of a map such as a struct with a
+    /// flattened field.
+    Struct(Vec<(Cow<'static, str>, Value)>),
+}
+
+fn escape(text: &str, out: &mut String, attribute: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+fn write_element(out: &mut String, name: &str, value: &Value, depth: usize) {
+    let fields: &[(Cow<str>, Value)] = match value {
+        Value::Null => return,
+        Value::Seq(items) => {
+            for item in items {
+                write_element(out, name, item, depth);
+            }
+            return;
+        }
+        Value::Text(_) | Value::Xml(_) => &[],
+        Value::Struct(fields) => fields,
+    };
+    indent(out, depth);
+    out.push('<');
+    out.push_str(name);
+    for (key, value) in fields {
+        if let (Some(attribute), Value::Text(text)) = (key.strip_prefix('@'), value) {
+            out.push_str(&format!(" {}=\"", attribute));
+            escape(text, out, true);
+            out.push('"');
+        }
+    }
+    let text = match value {
+        Value::Struct(fields) => fields
+            .iter()
+            .find(|(key, _)| *key == "$value")
+            .map(|(_, value)| value),
+        value => Some(value),
+    };
+    let children: Vec<&(Cow<str>, Value)> = fields
+        .iter()
+        .filter(|(key, value)| {
+            !key.starts_with('@') && *key != "$value" && !matches!(value, Value::Null)
+        })
+        .collect();
+    let text = match text {
+        Some(Value::Text(text)) => {
+            let mut escaped = String::new();
+            escape(text, &mut escaped, false);
+            Some(escaped)
+        }
+        Some(Value::Xml(xml)) => Some(xml.clone()),
+        _ => None,
+    };
+    match (text, children.is_empty()) {
+        (None, true) => out.push_str("/>\n"),
+        (Some(text), true) => out.push_str(&format!(">{}</{}>\n", text, name)),
+        (text, false) => {
+            out.push('>');
+            out.push_str(&text.unwrap_or_default());
+            out.push('\n');
+            for (key, value) in children {
+                write_element(out, key, value, depth + 1);
+            }
+            indent(out, depth);
+            out.push_str(&format!("</{}>\n", name));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+fn unsupported(what: &str) -> Error {
+    Error(format!("{} cannot be written as XML", what))
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = ser::Impossible<Value, Error>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = ser::Impossible<Value, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Value, Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        match value.serialize(self)? {
+            Value::Text(markup) if name == RICH_TEXT_NEWTYPE => {
+                Ok(Value::Xml(RichText::parse(&markup).to_xml()))
+            }
+            Value::Struct(mut fields) if name == ELEMENT_TEXT_NEWTYPE && fields.len() == 1 => {
+                fields[0].0 = Cow::Borrowed("$value");
+                Ok(Value::Struct(fields))
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        Ok(Value::Struct(vec![(
+            variant.into(),
+            value.serialize(self)?,
+        )]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, len: usize) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("a tuple variant"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer {
+            fields: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<StructSerializer, Error> {
+        Ok(StructSerializer {
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("a struct variant"))
+    }
+}
+
+struct SeqSerializer(Vec<Value>);
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.0.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Seq(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct StructSerializer {
+    fields: Vec<(Cow<'static, str>, Value)>,
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.fields
+            .push((key.into(), value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Struct(self.fields))
+    }
+}
+
+/// Collects map entries like struct fields; the keys must be strings.
+struct MapSerializer {
+    fields: Vec<(Cow<'static, str>, Value)>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        match key.serialize(ValueSerializer)? {
+            Value::Text(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(unsupported("a map key that is not a string")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| unsupported("a map value without a key"))?;
+        self.fields
+            .push((key.into(), value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Struct(self.fields))
+    }
+}
@@ -0,0 +1,16 @@
+extern crate directories;
+
+pub mod db;
+pub mod dtd;
+pub mod error;
+pub mod export;
+pub mod pipeline;
+pub mod pubmed;
+pub mod reader;
+pub mod report;
+pub mod sync;
+
+pub use error::{ErrorContext, ParseError};
+pub use pubmed::Record;
+pub use reader::{ParallelReader, PubmedReader, RawRecord, RecordReader};
+pub use report::{ErrorHandler, ErrorReport, FileSummary, UnmappedPath};
@@ -0,0 +1,300 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::dtd::DtdVersion;
+use crate::error::ParseError;
+use crate::pubmed::Record;
+use crate::reader::{ParallelReader, RecordReader};
+use crate::report::FileSummary;
+
+/// What the pipeline hands to its consumer.
+#[derive(Debug)]
+pub enum Event {
+    /// Sent before the first record of a file whose `DOCTYPE` names a DTD
+    /// release.
+    Doctype(DtdVersion),
+    Record(Box<Record>),
+    Error(ParseError),
+    /// Sent after the last record of a file.
+    FileDone(FileProgress),
+}
+
+/// Counts and timing of one finished file.
+#[derive(Serialize, Debug, Clone)]
+pub struct FileProgress {
+    #[serde(flatten)]
+    pub summary: FileSummary,
+    /// Bytes of decompressed XML read.
+    pub bytes: u64,
+    #[serde(serialize_with = "seconds")]
+    pub elapsed: Duration,
+}
+
+fn seconds<S: serde::Serializer>(elapsed: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64(elapsed.as_secs_f64())
+}
+
+impl FileProgress {
+    pub fn records_per_second(&self) -> f64 {
+        (self.summary.records() + self.summary.error_count()) as f64
+            / self.elapsed.as_secs_f64().max(1e-9)
+    }
+
+    pub fn megabytes_per_second(&self) -> f64 {
+        self.bytes as f64 / 1e6 / self.elapsed.as_secs_f64().max(1e-9)
+    }
+}
+
+/// Parses many files on a pool of worker threads.
+///
+/// Every file gets its own bounded channel and the consumer drains them in
+/// input order, so records come out exactly as a sequential read would
+/// produce them while later files are already being parsed. At most
+/// `threads * capacity` events are buffered at any time; a worker that is
+/// ahead of the consumer blocks until its file is reached.
+///
+/// ```no_run
+/// use rbiblio::pipeline::{Event, Pipeline};
+///
+/// let files = vec!["pubmed24n0001.xml.gz".into(), "pubmed24n0002.xml.gz".into()];
+/// for event in Pipeline::new().with_threads(4).run(files) {
+///     if let Event::FileDone(progress) = event {
+///         println!("{:?}: {:.0} records/s", progress.summary.file, progress.records_per_second());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    threads: usize,
+    decoders: usize,
+    capacity: usize,
+    audit: bool,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            decoders: 0,
+            capacity: 1024,
+            audit: false,
+        }
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Number of files parsed at the same time, at least one.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Decoder threads per file, see `ParallelReader`. With the default of
+    /// zero each file is scanned and decoded on its worker thread, which is
+    /// best when there are at least as many files as threads.
+    pub fn with_decoders(mut self, decoders: usize) -> Self {
+        self.decoders = decoders;
+        self
+    }
+
+    /// Events buffered per file before its worker waits for the consumer.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Reads in audit mode, see `RecordReader::with_audit`; the unmapped
+    /// paths of each file are in its summary.
+    pub fn with_audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Starts the workers. They stop and are joined when the returned
+    /// iterator is exhausted or dropped.
+    pub fn run(&self, files: Vec<PathBuf>) -> Events {
+        let (files_tx, files_rx) = channel();
+        let queue = Arc::new(Mutex::new(Queue {
+            files: files.into_iter(),
+            receivers: files_tx,
+        }));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let workers = (0..self.threads)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let cancelled = Arc::clone(&cancelled);
+                let pipeline = self.clone();
+                thread::spawn(move || pipeline.work(&queue, &cancelled))
+            })
+            .collect();
+        Events {
+            files: Some(files_rx),
+            current: None,
+            workers,
+            cancelled,
+        }
+    }
+}
+
+/// Files not yet taken by a worker. Taking a file and queueing its receiver
+/// happen under one lock, which keeps the receivers in input order.
+struct Queue {
+    files: std::vec::IntoIter<PathBuf>,
+    receivers: Sender<Receiver<Event>>,
+}
+
+impl Pipeline {
+    fn work(&self, queue: &Mutex<Queue>, cancelled: &AtomicBool) {
+        loop {
+            let (path, events) = {
+                let mut queue = queue.lock().unwrap();
+                let Some(path) = queue.files.next() else {
+                    return;
+                };
+                let (tx, rx) = sync_channel(self.capacity);
+                if queue.receivers.send(rx).is_err() {
+                    return;
+                }
+                (path, tx)
+            };
+            if cancelled.load(Ordering::Relaxed) || !self.parse_file(&path, &events, cancelled) {
+                return;
+            }
+        }
+    }
+
+    /// Sends the events of one file; false when the consumer has gone away.
+    fn parse_file(&self, path: &Path, events: &SyncSender<Event>, cancelled: &AtomicBool) -> bool {
+        let started = Instant::now();
+        let reader = match RecordReader::open(path) {
+            Ok(reader) => reader.with_audit(self.audit),
+            Err(e) => {
+                let mut summary = FileSummary {
+                    file: Some(path.display().to_string()),
+                    ..Default::default()
+                };
+                *summary.errors.entry(e.kind()).or_insert(0) += 1;
+                let progress = FileProgress {
+                    summary,
+                    bytes: 0,
+                    elapsed: started.elapsed(),
+                };
+                return events.send(Event::Error(e)).is_ok()
+                    && events.send(Event::FileDone(progress)).is_ok();
+            }
+        };
+        let sent = if self.decoders > 0 {
+            let mut reader = reader.parallel(self.decoders);
+            send_records(&mut reader, ParallelReader::dtd_version, events, cancelled)
+                .map(|()| (reader.summary().clone(), reader.position()))
+        } else {
+            let mut reader = reader;
+            send_records(&mut reader, RecordReader::dtd_version, events, cancelled)
+                .map(|()| (reader.summary().clone(), reader.position()))
+        };
+        let Some((summary, bytes)) = sent else {
+            return false;
+        };
+        let progress = FileProgress {
+            summary,
+            bytes,
+            elapsed: started.elapsed(),
+        };
+        events.send(Event::FileDone(progress)).is_ok()
+    }
+}
+
+/// Sends the records of a reader, with its DTD release, which is known once
+/// the first record has been read, ahead of them.
+fn send_records<I>(
+    records: &mut I,
+    dtd_version: fn(&I) -> Option<DtdVersion>,
+    events: &SyncSender<Event>,
+    cancelled: &AtomicBool,
+) -> Option<()>
+where
+    I: Iterator<Item = Result<Record, ParseError>>,
+{
+    let mut first = true;
+    while let Some(record) = records.next() {
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        if std::mem::take(&mut first) {
+            if let Some(version) = dtd_version(records) {
+                events.send(Event::Doctype(version)).ok()?;
+            }
+        }
+        let event = match record {
+            Ok(record) => Event::Record(Box::new(record)),
+            Err(e) => Event::Error(e),
+        };
+        events.send(event).ok()?;
+    }
+    Some(())
+}
+
+/// Events of all files in input order.
+pub struct Events {
+    files: Option<Receiver<Receiver<Event>>>,
+    current: Option<Receiver<Event>>,
+    workers: Vec<JoinHandle<()>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Iterator for Events {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        loop {
+            if let Some(current) = &self.current {
+                match current.recv() {
+                    Ok(event) => return Some(event),
+                    Err(_) => self.current = None,
+                }
+            }
+            match self.files.as_ref()?.recv() {
+                Ok(next) => self.current = Some(next),
+                Err(_) => {
+                    self.files = None;
+                    self.join();
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Events {
+    /// Waits for the workers, passing on a worker panic so that records are
+    /// never lost silently.
+    fn join(&mut self) {
+        for worker in self.workers.drain(..) {
+            if let Err(panic) = worker.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        // dropping the receivers wakes workers blocked on a full channel
+        self.current = None;
+        self.files = None;
+        for worker in self.workers.drain(..) {
+            // a panic in a worker has already been printed
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fmt;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// How much of a date is known.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    Year,
+    Season,
+    Month,
+    Day,
+}
+
+/// A date known to the year, and possibly to the season, month or day.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PartialDate {
+    year: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    month: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    day: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    season: Option<String>,
+}
+
+impl PartialDate {
+    /// Builds a date from the text of `Year`, `Month`, `Day` and `Season`
+    /// elements. Months may be names (`Jul`, `July`) or numbers (`07`);
+    /// parts that cannot be read are dropped, and a day needs a month.
+    pub fn from_parts(
+        year: &str,
+        month: Option<&str>,
+        day: Option<&str>,
+        season: Option<&str>,
+    ) -> Option<PartialDate> {
+        let year = parse_year(year.trim())?;
+        let month = month.and_then(parse_month);
+        let day = match month {
+            Some(month) => day
+                .and_then(|d| d.trim().parse::<u32>().ok())
+                .filter(|&d| NaiveDate::from_ymd_opt(year, month, d).is_some()),
+            None => None,
+        };
+        let season = season
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        Some(PartialDate {
+            year,
+            month,
+            day,
+            season,
+        })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Month number, 1 to 12.
+    pub fn month(&self) -> Option<u32> {
+        self.month
+    }
+
+    pub fn day(&self) -> Option<u32> {
+        self.day
+    }
+
+    /// Season as written, e.g. `Spring` or `Fall`.
+    pub fn season(&self) -> Option<&str> {
+        self.season.as_deref()
+    }
+
+    pub fn precision(&self) -> Precision {
+        match (self.month, self.day, &self.season) {
+            (Some(_), Some(_), _) => Precision::Day,
+            (Some(_), None, _) => Precision::Month,
+            (None, _, Some(_)) => Precision::Season,
+            _ => Precision::Year,
+        }
+    }
+
+    /// First day of the period. A season starts with its first month
+    /// (Spring in March, Summer in June, Autumn in September and Winter in
+    /// December); unknown seasons count as the whole year.
+    pub fn first_day(&self) -> Option<NaiveDate> {
+        let month = self
+            .month
+            .or_else(|| self.season.as_deref().and_then(season_month))
+            .unwrap_or(1);
+        NaiveDate::from_ymd_opt(self.year, month, self.day.unwrap_or(1))
+    }
+
+    /// Last day of the period, e.g. 31 July for `2016 Jul`.
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        if self.day.is_some() {
+            return self.first_day();
+        }
+        let (year, month) = match (self.month, self.season.as_deref().and_then(season_month)) {
+            (Some(month), _) => (self.year, month),
+            // seasons last three months, so Winter ends in the next year
+            (None, Some(12)) => (self.year + 1, 2),
+            (None, Some(first)) => (self.year, first + 2),
+            (None, None) => (self.year, 12),
+        };
+        let next = match month {
+            12 => NaiveDate::from_ymd_opt(year + 1, 1, 1),
+            _ => NaiveDate::from_ymd_opt(year, month + 1, 1),
+        };
+        next.and_then(|d| d.pred_opt())
+    }
+}
+
+impl fmt::Display for PartialDate {
+    /// MEDLINE style: `2016 Jul 4`, `2016 Jul`, `1998 Spring` or `2016`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, " {}", MONTHS[month as usize - 1])?;
+            if let Some(day) = self.day {
+                write!(f, " {}", day)?;
+            }
+        } else if let Some(season) = &self.season {
+            write!(f, " {}", season)?;
+        }
+        Ok(())
+    }
+}
+
+/// Publication date of a journal issue or book: a single partial date, or
+/// the range given by a `MedlineDate` such as `1998 Mar-Apr` or
+/// `2000 Dec-2001 Jan`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PublicationDate {
+    start: PartialDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<PartialDate>,
+}
+
+impl PublicationDate {
+    pub fn new(start: PartialDate) -> PublicationDate {
+        PublicationDate { start, end: None }
+    }
+
+    pub fn range(start: PartialDate, end: PartialDate) -> PublicationDate {
+        PublicationDate {
+            start,
+            end: Some(end),
+        }
+    }
+
+    /// Reads a free-form `MedlineDate`. Besides month and season ranges it
+    /// understands day ranges (`1977 Jul 12-19`), year ranges (`1975-76`)
+    /// and falls back to the first four-digit year found.
+    pub fn parse_medline_date(value: &str) -> Option<PublicationDate> {
+        let mut parts = value.split('-').map(str::trim);
+        let first = parts.next().map(parse_part).unwrap_or_default();
+        let last = parts.next_back().map(parse_part);
+        let Some(start_year) = first.year.or_else(|| last.as_ref().and_then(|l| l.year)) else {
+            return first_year(value).map(|year| PublicationDate::new(PartialDate::of_year(year)));
+        };
+        let start = first.into_date(start_year, None);
+        let end = last.and_then(|last| {
+            let year = match last.year {
+                Some(year) if year < 100 => start_year - start_year % 100 + year,
+                Some(year) => year,
+                None => start_year,
+            };
+            // a bare day continues the month of the start
+            let month = match (last.month, last.day) {
+                (None, Some(_)) => start.month,
+                _ => None,
+            };
+            Some(last.into_date(year, month)).filter(|end| *end != start)
+        });
+        Some(PublicationDate { start, end })
+    }
+
+    pub fn start(&self) -> &PartialDate {
+        &self.start
+    }
+
+    pub fn end(&self) -> Option<&PartialDate> {
+        self.end.as_ref()
+    }
+
+    pub fn is_range(&self) -> bool {
+        self.end.is_some()
+    }
+
+    pub fn year(&self) -> i32 {
+        self.start.year
+    }
+
+    pub fn month(&self) -> Option<u32> {
+        self.start.month
+    }
+
+    pub fn day(&self) -> Option<u32> {
+        self.start.day
+    }
+
+    pub fn season(&self) -> Option<&str> {
+        self.start.season()
+    }
+
+    /// Precision of the start of the date.
+    pub fn precision(&self) -> Precision {
+        self.start.precision()
+    }
+
+    /// First day of the date or range, see `PartialDate::first_day`.
+    pub fn to_naive_date(&self) -> Option<NaiveDate> {
+        self.start.first_day()
+    }
+
+    /// Last day covered by the date or range.
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        self.end.as_ref().unwrap_or(&self.start).last_day()
+    }
+}
+
+impl From<PartialDate> for PublicationDate {
+    fn from(date: PartialDate) -> Self {
+        PublicationDate::new(date)
+    }
+}
+
+impl fmt::Display for PublicationDate {
+    /// MEDLINE style, leaving out the parts of the end that repeat the
+    /// start: `1998 Mar-Apr`, `1977 Jul 12-19`, `2000 Dec-2001 Jan`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.start)?;
+        let Some(end) = &self.end else {
+            return Ok(());
+        };
+        if end.year != self.start.year {
+            return write!(f, "-{}", end);
+        }
+        match (end.month, end.day, &end.season) {
+            (Some(month), Some(day), _) if Some(month) == self.start.month => {
+                write!(f, "-{}", day)
+            }
+            (Some(month), Some(day), _) => write!(f, "-{} {}", MONTHS[month as usize - 1], day),
+            (Some(month), None, _) => write!(f, "-{}", MONTHS[month as usize - 1]),
+            (None, _, Some(season)) => write!(f, "-{}", season),
+            (None, _, None) => write!(f, "-{}", end),
+        }
+    }
+}
+
+impl PartialDate {
+    fn of_year(year: i32) -> PartialDate {
+        PartialDate {
+            year,
+            month: None,
+            day: None,
+            season: None,
+        }
+    }
+}
+
+/// The pieces found in one side of a `MedlineDate` range.
+#[derive(Default)]
+struct Part {
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+    season: Option<String>,
+}
+
+impl Part {
+    fn into_date(self, year: i32, month: Option<u32>) -> PartialDate {
+        let month = self.month.or(month);
+        let day = self
+            .day
+            .filter(|&d| month.is_some_and(|m| NaiveDate::from_ymd_opt(year, m, d).is_some()));
+        PartialDate {
+            year,
+            month,
+            day,
+            season: self.season,
+        }
+    }
+}
+
+fn parse_part(text: &str) -> Part {
+    let mut part = Part::default();
+    for token in text.split_whitespace() {
+        let token = token.trim_matches(|c: char| c == ',' || c == '.');
+        if let Some(year) = parse_year(token) {
+            part.year = Some(year);
+        } else if let Some(month) = month_name(token) {
+            part.month = Some(month);
+        } else if let Some(season) = season_name(token) {
+            part.season = Some(season.to_string());
+        } else if let Ok(number) = token.parse::<i32>() {
+            // two digits after a range dash are an abbreviated year
+            if part.month.is_none() && part.year.is_none() && number > 31 {
+                part.year = Some(number);
+            } else if (1..=31).contains(&number) {
+                part.day = Some(number as u32);
+            }
+        }
+    }
+    part
+}
+
+fn parse_year(text: &str) -> Option<i32> {
+    if text.len() == 4 && text.bytes().all(|b| b.is_ascii_digit()) {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn parse_month(text: &str) -> Option<u32> {
+    let text = text.trim();
+    match text.parse::<u32>() {
+        Ok(month) if (1..=12).contains(&month) => Some(month),
+        Ok(_) => None,
+        Err(_) => month_name(text),
+    }
+}
+
+fn month_name(text: &str) -> Option<u32> {
+    let prefix = text.get(..3)?;
+    MONTHS
+        .iter()
+        .position(|m| m.eq_ignore_ascii_case(prefix))
+        .filter(|_| text.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(|i| i as u32 + 1)
+}
+
+fn season_name(text: &str) -> Option<&str> {
+    ["Spring", "Summer", "Autumn", "Fall", "Winter"]
+        .into_iter()
+        .find(|s| s.eq_ignore_ascii_case(text))
+}
+
+fn season_month(season: &str) -> Option<u32> {
+    match season.to_ascii_lowercase().as_str() {
+        "spring" => Some(3),
+        "summer" => Some(6),
+        "autumn" | "fall" => Some(9),
+        "winter" => Some(12),
+        _ => None,
+    }
+}
+
+fn first_year(text: &str) -> Option<i32> {
+    text.as_bytes()
+        .windows(4)
+        .enumerate()
+        .find(|(i, w)| {
+            w.iter().all(u8::is_ascii_digit)
+                && !text.as_bytes()[i + 4..]
+                    .first()
+                    .is_some_and(u8::is_ascii_digit)
+        })
+        .and_then(|(i, _)| text[i..i + 4].parse().ok())
+}
@@ -0,0 +1,599 @@
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::rich_text::escape_html;
+
+pub const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// A MathML formula from a title or abstract.
+///
+/// The `mml:math` element is kept as the markup it was read from, together
+/// with the element tree parsed from it. Formulas that cannot be parsed keep
+/// their markup only, and are converted by dropping the tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Math {
+    mathml: String,
+    root: Option<MathElement>,
+}
+
+/// A node of a MathML tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathNode {
+    Element(MathElement),
+    Text(String),
+}
+
+/// A MathML element, named without its namespace prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<MathNode>,
+}
+
+impl Math {
+    pub fn new<S: Into<String>>(mathml: S) -> Math {
+        let mathml = mathml.into();
+        let root = parse(&mathml);
+        Math { mathml, root }
+    }
+
+    /// The formula as MathML markup, as it appeared in the record.
+    pub fn mathml(&self) -> &str {
+        &self.mathml
+    }
+
+    /// The parsed `math` element, if the markup is well formed.
+    pub fn root(&self) -> Option<&MathElement> {
+        self.root.as_ref()
+    }
+
+    /// The formula as LaTeX, e.g. `x^{2}<1` or `\frac{a}{b}`. This is a
+    /// best-effort conversion covering the presentation elements found in
+    /// PubMed; content MathML and unknown elements contribute their
+    /// children only.
+    pub fn to_latex(&self) -> Option<String> {
+        let root = self.root.as_ref()?;
+        let mut out = String::new();
+        latex(root, &mut out);
+        Some(out.trim().to_string())
+    }
+
+    /// The formula as linear text for search indexing, e.g. `x^2 < 1` or
+    /// `(a + b)/2`.
+    pub fn to_plain(&self) -> String {
+        match &self.root {
+            Some(root) => collapse_spaces(&linear(root)),
+            None => self.unparsed_text(),
+        }
+    }
+
+    /// The formula as MathML for HTML, without namespace prefixes. Formulas
+    /// that cannot be parsed become their escaped text.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        match &self.root {
+            Some(root) => write_html(root, true, &mut out),
+            None => escape_html(&self.unparsed_text(), &mut out),
+        }
+        out
+    }
+
+    /// The text of markup that cannot be parsed: the tags dropped and the
+    /// entities resolved where they can be.
+    fn unparsed_text(&self) -> String {
+        let text = strip_tags(&self.mathml);
+        match unescape(&text) {
+            Ok(unescaped) => unescaped.into_owned(),
+            Err(_) => text,
+        }
+    }
+}
+
+impl MathNode {
+    pub fn as_element(&self) -> Option<&MathElement> {
+        match self {
+            MathNode::Element(element) => Some(element),
+            MathNode::Text(_) => None,
+        }
+    }
+}
+
+impl MathElement {
+    /// Local name, e.g. `msup`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn attributes(&self) -> &[(String, String)] {
+        &self.attributes
+    }
+
+    pub fn children(&self) -> &[MathNode] {
+        &self.children
+    }
+
+    /// Child elements, skipping text.
+    pub fn elements(&self) -> impl Iterator<Item = &MathElement> {
+        self.children.iter().filter_map(MathNode::as_element)
+    }
+
+    /// All text below the element.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                MathNode::Text(t) => text.push_str(t),
+                MathNode::Element(e) => text.push_str(&e.text()),
+            }
+        }
+        text
+    }
+
+    fn element(&self, index: usize) -> Option<&MathElement> {
+        self.elements().nth(index)
+    }
+}
+
+fn parse(mathml: &str) -> Option<MathElement> {
+    let mut reader = Reader::from_str(mathml);
+    let mut stack: Vec<MathElement> = Vec::new();
+    loop {
+        let done = match reader.read_event().ok()? {
+            Event::Start(e) => {
+                stack.push(element(&e));
+                None
+            }
+            Event::Empty(e) => Some(element(&e)),
+            Event::End(_) => Some(stack.pop()?),
+            Event::Text(t) => {
+                let text = match t.unescape() {
+                    Ok(text) => text.into_owned(),
+                    // entities not predefined by XML are kept as written
+                    Err(_) => String::from_utf8_lossy(&t).into_owned(),
+                };
+                push_text(stack.last_mut(), text.trim());
+                None
+            }
+            Event::CData(t) => {
+                push_text(stack.last_mut(), String::from_utf8_lossy(&t).trim());
+                None
+            }
+            Event::Eof => return None,
+            _ => None,
+        };
+        if let Some(done) = done {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(MathNode::Element(done)),
+                None => return Some(done),
+            }
+        }
+    }
+}
+
+fn element(start: &BytesStart) -> MathElement {
+    let attributes = start
+        .attributes()
+        .flatten()
+        .map(|a| {
+            let value = match a.unescape_value() {
+                Ok(value) => value.into_owned(),
+                Err(_) => String::from_utf8_lossy(&a.value).into_owned(),
+            };
+            (String::from_utf8_lossy(a.key.as_ref()).into_owned(), value)
+        })
+        .collect();
+    MathElement {
+        name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+        attributes,
+        children: Vec::new(),
+    }
+}
+
+fn push_text(parent: Option<&mut MathElement>, text: &str) {
+    if let (Some(parent), false) = (parent, text.is_empty()) {
+        parent.children.push(MathNode::Text(text.to_string()));
+    }
+}
+
+fn strip_tags(markup: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in markup.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text
+}
+
+fn collapse_spaces(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// LaTeX for characters that need a command, or nothing for the invisible
+/// operators.
+fn latex_symbol(c: char) -> Option<&'static str> {
+    let symbol = match c {
+        'α' => "\\alpha",
+        'β' => "\\beta",
+        'γ' => "\\gamma",
+        'δ' => "\\delta",
+        'ε' | 'ϵ' => "\\epsilon",
+        'ζ' => "\\zeta",
+        'η' => "\\eta",
+        'θ' => "\\theta",
+        'ι' => "\\iota",
+        'κ' => "\\kappa",
+        'λ' => "\\lambda",
+        'μ' | 'µ' => "\\mu",
+        'ν' => "\\nu",
+        'ξ' => "\\xi",
+        'π' => "\\pi",
+        'ρ' => "\\rho",
+        'σ' => "\\sigma",
+        'τ' => "\\tau",
+        'υ' => "\\upsilon",
+        'φ' | 'ϕ' => "\\phi",
+        'χ' => "\\chi",
+        'ψ' => "\\psi",
+        'ω' => "\\omega",
+        'Γ' => "\\Gamma",
+        'Δ' => "\\Delta",
+        'Θ' => "\\Theta",
+        'Λ' => "\\Lambda",
+        'Ξ' => "\\Xi",
+        'Π' => "\\Pi",
+        'Σ' => "\\Sigma",
+        'Φ' => "\\Phi",
+        'Ψ' => "\\Psi",
+        'Ω' => "\\Omega",
+        '≤' | '⩽' => "\\leq",
+        '≥' | '⩾' => "\\geq",
+        '≠' => "\\neq",
+        '≈' => "\\approx",
+        '≡' => "\\equiv",
+        '∼' => "\\sim",
+        '∝' => "\\propto",
+        '±' => "\\pm",
+        '∓' => "\\mp",
+        '×' => "\\times",
+        '·' | '⋅' => "\\cdot",
+        '÷' => "\\div",
+        '−' => "-",
+        '→' => "\\to",
+        '←' => "\\leftarrow",
+        '⇒' => "\\Rightarrow",
+        '⇔' => "\\Leftrightarrow",
+        '∞' => "\\infty",
+        '∑' => "\\sum",
+        '∏' => "\\prod",
+        '∫' => "\\int",
+        '∂' => "\\partial",
+        '∇' => "\\nabla",
+        '∈' => "\\in",
+        '∉' => "\\notin",
+        '⊂' => "\\subset",
+        '⊆' => "\\subseteq",
+        '∪' => "\\cup",
+        '∩' => "\\cap",
+        '∀' => "\\forall",
+        '∃' => "\\exists",
+        '°' => "^{\\circ}",
+        '…' => "\\ldots",
+        '⋯' => "\\cdots",
+        '′' => "'",
+        '{' => "\\{",
+        '}' => "\\}",
+        '%' => "\\%",
+        '#' => "\\#",
+        '&' => "\\&",
+        '$' => "\\$",
+        '_' => "\\_",
+        '\u{2061}' | '\u{2062}' | '\u{2063}' | '\u{2064}' => "",
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+const FUNCTIONS: [&str; 18] = [
+    "sin", "cos", "tan", "cot", "sec", "csc", "sinh", "cosh", "tanh", "log", "ln", "lg", "exp",
+    "lim", "max", "min", "det", "sup",
+];
+
+/// Appends LaTeX to `out`, keeping a command such as `\alpha` apart from a
+/// following letter.
+fn push_latex(out: &mut String, latex: &str) {
+    let command_end = out
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .ends_with('\\')
+        && out.ends_with(|c: char| c.is_ascii_alphabetic());
+    if command_end && latex.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        out.push(' ');
+    }
+    out.push_str(latex);
+}
+
+fn latex_chars(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match latex_symbol(c) {
+            Some(symbol) => push_latex(&mut out, symbol),
+            None => push_latex(&mut out, c.encode_utf8(&mut [0; 4])),
+        }
+    }
+    out
+}
+
+fn latex_text(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' | '%' | '#' | '&' | '$' | '_' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str("\\^{}"),
+            '~' => out.push_str("\\~{}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn latex_of(element: Option<&MathElement>) -> String {
+    let mut out = String::new();
+    if let Some(element) = element {
+        latex(element, &mut out);
+    }
+    out
+}
+
+/// A base or script, braced unless it is a single character or command.
+fn braced(latex: String) -> String {
+    let command = latex
+        .strip_prefix('\\')
+        .is_some_and(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic()));
+    if command || latex.chars().count() == 1 {
+        latex
+    } else {
+        format!("{{{}}}", latex)
+    }
+}
+
+fn latex_children(element: &MathElement, out: &mut String) {
+    for child in element.elements() {
+        let mut inner = String::new();
+        latex(child, &mut inner);
+        push_latex(out, &inner);
+    }
+}
+
+fn latex(element: &MathElement, out: &mut String) {
+    let arg = |i| latex_of(element.element(i));
+    match element.name() {
+        "mi" => {
+            let text = element.text();
+            if FUNCTIONS.contains(&text.as_str()) {
+                push_latex(out, &format!("\\{}", text));
+            } else if text.chars().count() > 1 && text.chars().all(|c| c.is_alphabetic()) {
+                push_latex(out, &format!("\\mathrm{{{}}}", text));
+            } else {
+                push_latex(out, &latex_chars(&text));
+            }
+        }
+        "mn" | "mo" => push_latex(out, &latex_chars(&element.text())),
+        "mtext" => push_latex(out, &format!("\\text{{{}}}", latex_text(&element.text()))),
+        "ms" => push_latex(
+            out,
+            &format!("\\text{{\"{}\"}}", latex_text(&element.text())),
+        ),
+        "mspace" => out.push_str("\\,"),
+        "msup" => push_latex(out, &format!("{}^{{{}}}", braced(arg(0)), arg(1))),
+        "msub" => push_latex(out, &format!("{}_{{{}}}", braced(arg(0)), arg(1))),
+        "msubsup" | "munderover" => push_latex(
+            out,
+            &format!("{}_{{{}}}^{{{}}}", braced(arg(0)), arg(1), arg(2)),
+        ),
+        "munder" => push_latex(out, &format!("\\underset{{{}}}{{{}}}", arg(1), arg(0))),
+        "mover" => {
+            let accent = element.element(1).map(MathElement::text);
+            let command = match accent.as_deref() {
+                Some("¯" | "‾" | "_") => Some("\\overline"),
+                Some("^" | "ˆ") => Some("\\hat"),
+                Some("~" | "˜") => Some("\\tilde"),
+                Some("→" | "⃗") => Some("\\vec"),
+                Some("˙" | ".") => Some("\\dot"),
+                Some("¨") => Some("\\ddot"),
+                _ => None,
+            };
+            match command {
+                Some(command) => push_latex(out, &format!("{}{{{}}}", command, arg(0))),
+                None => push_latex(out, &format!("\\overset{{{}}}{{{}}}", arg(1), arg(0))),
+            }
+        }
+        "mfrac" => push_latex(out, &format!("\\frac{{{}}}{{{}}}", arg(0), arg(1))),
+        "msqrt" => {
+            let mut inner = String::new();
+            latex_children(element, &mut inner);
+            push_latex(out, &format!("\\sqrt{{{}}}", inner));
+        }
+        "mroot" => push_latex(out, &format!("\\sqrt[{}]{{{}}}", arg(1), arg(0))),
+        "mfenced" => {
+            let open = element.attribute("open").unwrap_or("(");
+            let close = element.attribute("close").unwrap_or(")");
+            let separator = element.attribute("separators").unwrap_or(",").trim();
+            let items: Vec<String> = element.elements().map(|e| latex_of(Some(e))).collect();
+            push_latex(
+                out,
+                &format!(
+                    "{}{}{}",
+                    latex_chars(open),
+                    items.join(&latex_chars(separator)),
+                    latex_chars(close)
+                ),
+            );
+        }
+        "mtable" => {
+            let rows: Vec<String> = element
+                .elements()
+                .map(|row| {
+                    let cells: Vec<String> = row.elements().map(|c| latex_of(Some(c))).collect();
+                    cells.join(" & ")
+                })
+                .collect();
+            push_latex(
+                out,
+                &format!("\\begin{{matrix}}{}\\end{{matrix}}", rows.join(" \\\\ ")),
+            );
+        }
+        "semantics" => {
+            if let Some(first) = element.element(0) {
+                latex(first, out);
+            }
+        }
+        "annotation" | "annotation-xml" | "mphantom" | "none" | "mprescripts" => (),
+        _ => latex_children(element, out),
+    }
+}
+
+const SPACED_OPERATORS: [&str; 20] = [
+    "=", "<", ">", "≤", "≥", "≠", "≈", "≡", "∼", "+", "-", "−", "±", "∓", "×", "÷", "→", "⇒", "∈",
+    "∝",
+];
+
+fn linear_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| !('\u{2061}'..='\u{2064}').contains(c))
+        .map(|c| if c == '−' { '-' } else { c })
+        .collect()
+}
+
+/// A base, script or fraction part, in parentheses unless it is a single
+/// number or identifier.
+fn operand(text: String) -> String {
+    let text = collapse_spaces(&text);
+    let simple = text.chars().all(|c| c.is_alphanumeric() || c == '.')
+        || (text.starts_with('(') && text.ends_with(')'));
+    if simple && !text.is_empty() {
+        text
+    } else {
+        format!("({})", text)
+    }
+}
+
+fn linear_of(element: Option<&MathElement>) -> String {
+    element.map(linear).unwrap_or_default()
+}
+
+fn linear(element: &MathElement) -> String {
+    let arg = |i| linear_of(element.element(i));
+    match element.name() {
+        "mi" | "mn" | "mtext" | "ms" => linear_text(&element.text()),
+        "mo" => {
+            let text = element.text();
+            if SPACED_OPERATORS.contains(&text.as_str()) {
+                format!(" {} ", linear_text(&text))
+            } else if text == "," || text == ";" {
+                format!("{} ", text)
+            } else {
+                linear_text(&text)
+            }
+        }
+        "mspace" => " ".to_string(),
+        "msup" => format!("{}^{}", operand(arg(0)), operand(arg(1))),
+        "msub" => format!("{}_{}", operand(arg(0)), operand(arg(1))),
+        "msubsup" | "munderover" => format!(
+            "{}_{}^{}",
+            operand(arg(0)),
+            operand(arg(1)),
+            operand(arg(2))
+        ),
+        "munder" => format!("{}_{}", operand(arg(0)), operand(arg(1))),
+        "mover" => match element.attribute("accent") {
+            Some("true") => arg(0),
+            _ => format!("{}^{}", operand(arg(0)), operand(arg(1))),
+        },
+        "mfrac" => format!("{}/{}", operand(arg(0)), operand(arg(1))),
+        "msqrt" => format!(
+            "sqrt({})",
+            collapse_spaces(&element.elements().map(linear).collect::<String>())
+        ),
+        "mroot" => format!(
+            "root({}, {})",
+            collapse_spaces(&arg(1)),
+            collapse_spaces(&arg(0))
+        ),
+        "mfenced" => {
+            let open = element.attribute("open").unwrap_or("(");
+            let close = element.attribute("close").unwrap_or(")");
+            let separator = element.attribute("separators").unwrap_or(",").trim();
+            let items: Vec<String> = element
+                .elements()
+                .map(|e| collapse_spaces(&linear(e)))
+                .collect();
+            format!(
+                "{}{}{}",
+                open,
+                items.join(&format!("{} ", separator)),
+                close
+            )
+        }
+        "mtable" => {
+            let rows: Vec<String> = element
+                .elements()
+                .map(|row| {
+                    let cells: Vec<String> = row
+                        .elements()
+                        .map(|c| collapse_spaces(&linear(c)))
+                        .collect();
+                    cells.join(", ")
+                })
+                .collect();
+            format!("[{}]", rows.join("; "))
+        }
+        "semantics" => arg(0),
+        "annotation" | "annotation-xml" | "mphantom" | "none" | "mprescripts" => String::new(),
+        _ => element.elements().map(linear).collect(),
+    }
+}
+
+fn write_html(element: &MathElement, root: bool, out: &mut String) {
+    out.push('<');
+    out.push_str(&element.name);
+    if root {
+        out.push_str(&format!(" xmlns=\"{}\"", MATHML_NAMESPACE));
+    }
+    for (name, value) in &element.attributes {
+        if name == "xmlns" || name.starts_with("xmlns:") {
+            continue;
+        }
+        out.push(' ');
+        out.push_str(name.rsplit(':').next().unwrap_or(name));
+        out.push_str("=\"");
+        escape_html(value, out);
+        out.push('"');
+    }
+    out.push('>');
+    for child in &element.children {
+        match child {
+            MathNode::Text(text) => escape_html(text, out),
+            MathNode::Element(child) => write_html(child, false, out),
+        }
+    }
+    out.push_str("</");
+    out.push_str(&element.name);
+    out.push('>');
+}
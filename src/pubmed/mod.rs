@@ -1,4 +1,37 @@
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+mod date;
+mod mathml;
+mod rich_text;
+
+pub use date::{PartialDate, Precision, PublicationDate};
+pub use mathml::{Math, MathElement, MathNode, MATHML_NAMESPACE};
+pub(crate) use rich_text::escape_markup;
+pub use rich_text::{Inline, RichText, Run, RunContent, Style, Styles, RICH_TEXT_NEWTYPE};
+
+/// Reads a `Y`/`N` attribute such as `ValidYN`, using the DTD default when
+/// the attribute is absent.
+fn yes_no(flag: &Option<String>, default: bool) -> bool {
+    match flag.as_deref() {
+        Some("Y") => true,
+        Some("N") => false,
+        _ => default,
+    }
+}
+
+fn slice<T>(list: &Option<Vec<T>>) -> &[T] {
+    list.as_deref().unwrap_or(&[])
+}
+
+/// People of the author lists that name editors, or of those that do not.
+fn people(lists: &[AuthorList], editors: bool) -> Vec<&Author> {
+    lists
+        .iter()
+        .filter(|l| l.is_editors() == editors)
+        .flat_map(|l| l.authors())
+        .collect()
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PMID {
@@ -13,7 +46,11 @@ impl PMID {
         str::parse::<u32>(&self.value)
     }
 
-    // pub fn value(&self) -> String { self.value }
+    /// The `Version` attribute as a number; records without a usable version
+    /// are version 1.
+    pub fn version_number(&self) -> u32 {
+        str::parse::<u32>(&self.version).unwrap_or(1)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -41,11 +78,28 @@ pub struct Journal {
 }
 
 impl Journal {
-    fn year(&self) -> Result<u32, std::num::ParseIntError> {
-        match self.journal_issue.year() {
-            Some(v) => str::parse::<u32>(&v),
-            _ => str::parse::<u32>(""),
-        }
+    pub fn issn(&self) -> Option<&ISSN> {
+        self.issn.as_ref()
+    }
+
+    pub fn journal_issue(&self) -> &JournalIssue {
+        &self.journal_issue
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_ref().map(|t| t.value.as_str())
+    }
+
+    pub fn iso_abbreviation(&self) -> Option<&str> {
+        self.abbreviation.as_ref().map(|a| a.value.as_str())
+    }
+
+    pub fn date(&self) -> Option<PublicationDate> {
+        self.journal_issue.pubdate.date()
+    }
+
+    pub fn year(&self) -> Option<i32> {
+        self.journal_issue.pubdate.year()
     }
 }
 
@@ -57,6 +111,17 @@ pub struct ISSN {
     value: String,
 }
 
+impl ISSN {
+    /// `Print` or `Electronic`.
+    pub fn issn_type(&self) -> &str {
+        &self.issn_type
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JournalIssue {
     #[serde(rename = "@CitedMedium")]
@@ -70,8 +135,21 @@ pub struct JournalIssue {
 }
 
 impl JournalIssue {
-    fn year(&self) -> Option<String> {
-        self.pubdate.year()
+    /// `Print` or `Internet`.
+    pub fn cited_medium(&self) -> &str {
+        &self.cited_medium
+    }
+
+    pub fn volume(&self) -> Option<&str> {
+        self.volume.as_ref().map(|v| v.value.as_str())
+    }
+
+    pub fn issue(&self) -> Option<&str> {
+        self.issue.as_ref().map(|i| i.value.as_str())
+    }
+
+    pub fn pub_date(&self) -> &PubDate {
+        &self.pubdate
     }
 }
 
@@ -91,21 +169,52 @@ pub struct Issue {
 pub struct PubDate {
     #[serde(rename = "Year")]
     year_op: Option<String>,
+    #[serde(rename = "Month")]
+    month: Option<Month>,
+    #[serde(rename = "Day")]
+    day: Option<Day>,
+    #[serde(rename = "Season")]
+    season: Option<Season>,
     #[serde(rename = "MedlineDate")]
     medline_date_op: Option<MedlineDate>,
 }
 
 impl PubDate {
-    fn year(&self) -> Option<String> {
-        if self.year_op.is_some() {
-            self.year_op.clone()
-        } else if self.medline_date_op.is_some() {
-            let op = self.medline_date_op.clone();
-            op.map(|v| v.year())
-        } else {
-            self.year_op.clone()
+    /// The date from `Year`, `Month`, `Day` and `Season`, or parsed from a
+    /// `MedlineDate`.
+    pub fn date(&self) -> Option<PublicationDate> {
+        match (&self.year_op, &self.medline_date_op) {
+            (Some(year), _) => {
+                PartialDate::from_parts(year, self.month(), self.day(), self.season())
+                    .map(Into::into)
+            }
+            (None, Some(medline_date)) => medline_date.date(),
+            (None, None) => None,
         }
     }
+
+    /// Month as written, a name such as `Jul` or a number.
+    pub fn month(&self) -> Option<&str> {
+        self.month.as_ref().map(|m| m.value.as_str())
+    }
+
+    pub fn day(&self) -> Option<&str> {
+        self.day.as_ref().map(|d| d.value.as_str())
+    }
+
+    pub fn season(&self) -> Option<&str> {
+        self.season.as_ref().map(|s| s.value.as_str())
+    }
+
+    /// The `Year`, or the first year of a `MedlineDate`.
+    pub fn year(&self) -> Option<i32> {
+        self.date().map(|d| d.year())
+    }
+
+    /// Free-form date such as `1998 Mar-Apr`, used instead of `Year`.
+    pub fn medline_date(&self) -> Option<&str> {
+        self.medline_date_op.as_ref().map(|d| d.value.as_str())
+    }
 }
 
 // <MedlineDate>1998 Mar-Apr</MedlineDate>
@@ -116,19 +225,91 @@ pub struct MedlineDate {
 }
 
 impl MedlineDate {
-    fn year(&self) -> String {
-        let it = self.value.chars();
-        String::from_iter(
-            it.skip_while(|c| !c.is_ascii_digit())
-                .take_while(|c| c.is_ascii_digit()),
-        )
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn date(&self) -> Option<PublicationDate> {
+        PublicationDate::parse_medline_date(&self.value)
+    }
+}
+
+/// The `book`, `part` and `sec` attributes that link a title to a location
+/// in NCBI Bookshelf.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BookshelfLink {
+    #[serde(rename = "@book")]
+    book: Option<String>,
+    #[serde(rename = "@part")]
+    part: Option<String>,
+    #[serde(rename = "@sec")]
+    sec: Option<String>,
+}
+
+impl BookshelfLink {
+    /// `book` attribute: the Bookshelf book.
+    pub fn book(&self) -> Option<&str> {
+        self.book.as_deref()
+    }
+
+    /// `part` attribute: the part of the book, such as a chapter.
+    pub fn part(&self) -> Option<&str> {
+        self.part.as_deref()
+    }
+
+    /// `sec` attribute: the section within the part.
+    pub fn sec(&self) -> Option<&str> {
+        self.sec.as_deref()
+    }
+}
+
+/// A title as it is read. The attributes are plain fields here rather than
+/// a flattened `BookshelfLink`, which would hide unknown attributes from the
+/// audit.
+#[derive(Deserialize)]
+struct LinkedTitle<T> {
+    #[serde(rename = "@book")]
+    book: Option<String>,
+    #[serde(rename = "@part")]
+    part: Option<String>,
+    #[serde(rename = "@sec")]
+    sec: Option<String>,
+    #[serde(rename = "$value")]
+    value: T,
+}
+
+impl<T> LinkedTitle<T> {
+    fn split(self) -> (BookshelfLink, T) {
+        let link = BookshelfLink {
+            book: self.book,
+            part: self.part,
+            sec: self.sec,
+        };
+        (link, self.value)
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "LinkedTitle<RichText>")]
 pub struct AtticleTitle {
+    #[serde(flatten)]
+    link: BookshelfLink,
     #[serde(rename = "$value")]
-    value: String,
+    value: RichText,
+}
+
+impl From<LinkedTitle<RichText>> for AtticleTitle {
+    fn from(title: LinkedTitle<RichText>) -> Self {
+        let (link, value) = title.split();
+        AtticleTitle { link, value }
+    }
+}
+
+impl AtticleTitle {
+    /// The Bookshelf book, part and section the title links to.
+    pub fn link(&self) -> &BookshelfLink {
+        &self.link
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -138,13 +319,39 @@ pub struct AbstractText {
     #[serde(rename = "@NlmCategory")]
     nlm_category: Option<String>,
     #[serde(rename = "$value")]
-    value: String,
+    value: RichText,
+}
+
+impl AbstractText {
+    /// Section label such as `BACKGROUND`, for structured abstracts.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn nlm_category(&self) -> Option<&str> {
+        self.nlm_category.as_deref()
+    }
+
+    pub fn text(&self) -> &RichText {
+        &self.value
+    }
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CopyrightInformation {
     #[serde(rename = "$value")]
     value: String,
 }
+fn abstract_text(texts: &[AbstractText]) -> String {
+    let lines: Vec<String> = texts
+        .iter()
+        .map(|t| match &t.label {
+            Some(label) => format!("{}: {}", label, t.value),
+            None => t.value.to_plain(),
+        })
+        .collect();
+    lines.join("\n")
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Abstract {
     //TODO: AbstractText missing
@@ -154,6 +361,23 @@ pub struct Abstract {
     copyright_information: Option<CopyrightInformation>,
 }
 
+impl Abstract {
+    pub fn texts(&self) -> &[AbstractText] {
+        slice(&self.abstract_text)
+    }
+
+    /// All sections as one text, one line per section with its label.
+    pub fn text(&self) -> String {
+        abstract_text(self.texts())
+    }
+
+    pub fn copyright_information(&self) -> Option<&str> {
+        self.copyright_information
+            .as_ref()
+            .map(|c| c.value.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MedlinePgn {
     #[serde(rename = "$value")]
@@ -162,9 +386,52 @@ pub struct MedlinePgn {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Pagination {
+    #[serde(rename = "StartPage")]
+    start_page: Option<StartPage>,
+    #[serde(rename = "EndPage")]
+    end_page: Option<EndPage>,
     #[serde(rename = "MedlinePgn")]
     medline_pgn: Option<MedlinePgn>,
 }
+
+impl Pagination {
+    pub fn start_page(&self) -> Option<&str> {
+        self.start_page.as_ref().map(|p| p.value.as_str())
+    }
+
+    pub fn end_page(&self) -> Option<&str> {
+        self.end_page.as_ref().map(|p| p.value.as_str())
+    }
+
+    pub fn medline_pgn(&self) -> Option<&str> {
+        self.medline_pgn.as_ref().map(|p| p.value.as_str())
+    }
+
+    /// `MedlinePgn`, or else `StartPage-EndPage` as the current DTD allows
+    /// pagination without `MedlinePgn`.
+    pub fn pages(&self) -> Option<Cow<'_, str>> {
+        if let Some(pages) = self.medline_pgn() {
+            return Some(Cow::Borrowed(pages));
+        }
+        let start = self.start_page()?;
+        Some(match self.end_page() {
+            Some(end) => Cow::Owned(format!("{}-{}", start, end)),
+            None => Cow::Borrowed(start),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StartPage {
+    #[serde(rename = "$value")]
+    value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EndPage {
+    #[serde(rename = "$value")]
+    value: String,
+}
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LastName {
     #[serde(rename = "$value")]
@@ -175,7 +442,7 @@ pub struct ForeName {
     #[serde(rename = "$value")]
     value: String,
 }
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Initials {
     // #[serde(rename = "$value")]
     #[serde(alias = "value")]
@@ -183,6 +450,21 @@ pub struct Initials {
     value: String,
 }
 
+/// Name under which a struct serializes as a newtype when its one field is
+/// the element text but is not named `$value`, as in `Initials`. The XML
+/// writer recognizes it; the others see the struct.
+pub const ELEMENT_TEXT_NEWTYPE: &str = "$rbiblio::ElementText";
+
+impl Serialize for Initials {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Initials<'a> {
+            value: &'a str,
+        }
+        serializer.serialize_newtype_struct(ELEMENT_TEXT_NEWTYPE, &Initials { value: &self.value })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CollectiveName {
     #[serde(rename = "$value")]
@@ -193,6 +475,8 @@ pub struct CollectiveName {
 pub struct Author {
     #[serde(rename = "@ValidYN")]
     valid_yn: Option<String>,
+    #[serde(rename = "@EqualContrib")]
+    equal_contrib: Option<String>,
     #[serde(rename = "@Type")]
     author_type: Option<String>,
     #[serde(rename = "LastName")]
@@ -201,26 +485,68 @@ pub struct Author {
     fore_name: Option<ForeName>,
     #[serde(rename = "Initials")]
     initials: Option<Initials>,
+    #[serde(rename = "Suffix")]
+    suffix: Option<Suffix>,
     #[serde(rename = "CollectiveName")]
     collective_name: Option<CollectiveName>,
+    #[serde(rename = "Identifier")]
+    identifier: Option<Vec<Identifier>>,
     #[serde(rename = "AffiliationInfo")]
     affiliation_info: Option<Vec<AffiliationInfo>>,
 }
 
 impl Author {
-    // fn affiliations(&self) -> Vec<String> {
-    //     match &self.affiliation_info {
-    //         Some(info) => {
-    //             let it = info.affiliation.iter().map(|a| a.value.clone());
-    //             Vec::from_iter(it)
-    //         }
-    //         None => Vec::new(),
-    //     }
-    // }
+    pub fn is_valid(&self) -> bool {
+        yes_no(&self.valid_yn, true)
+    }
+
+    /// True when the author contributed equally with the others marked so.
+    pub fn is_equal_contributor(&self) -> bool {
+        yes_no(&self.equal_contrib, false)
+    }
+
+    /// `authors` or `editors`, only set in book author lists.
+    pub fn author_type(&self) -> Option<&str> {
+        self.author_type.as_deref()
+    }
+
+    pub fn last_name(&self) -> Option<&str> {
+        self.last_name.as_ref().map(|n| n.value.as_str())
+    }
+
+    pub fn fore_name(&self) -> Option<&str> {
+        self.fore_name.as_ref().map(|n| n.value.as_str())
+    }
+
+    pub fn initials(&self) -> Option<&str> {
+        self.initials.as_ref().map(|i| i.value.as_str())
+    }
+
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_ref().map(|s| s.value.as_str())
+    }
+
+    /// Name of a group author, used instead of the personal name.
+    pub fn collective_name(&self) -> Option<&str> {
+        self.collective_name.as_ref().map(|n| n.value.as_str())
+    }
+
+    /// Identifiers of the author, such as an ORCID.
+    pub fn identifiers(&self) -> &[Identifier] {
+        slice(&self.identifier)
+    }
+
+    pub fn affiliations(&self) -> Vec<&str> {
+        self.affiliation_info
+            .iter()
+            .flatten()
+            .flat_map(|info| info.affiliations())
+            .collect()
+    }
 
-    // fn is_validated(&self) -> bool {
-    //     self.valid_flag == "Y"
-    // }
+    pub fn affiliation_info(&self) -> &[AffiliationInfo] {
+        slice(&self.affiliation_info)
+    }
 }
 
 //AffiliationInfo that contain single empty Affiliation will be
@@ -229,6 +555,23 @@ impl Author {
 pub struct AffiliationInfo {
     #[serde(rename = "Affiliation")]
     affiliation: Option<Vec<Affiliation>>,
+    #[serde(rename = "Identifier")]
+    identifier: Option<Vec<Identifier>>,
+}
+
+impl AffiliationInfo {
+    pub fn affiliations(&self) -> Vec<&str> {
+        self.affiliation
+            .iter()
+            .flatten()
+            .map(|a| a.value.as_str())
+            .collect()
+    }
+
+    /// Identifiers of the institution, such as a ROR or GRID ID.
+    pub fn identifiers(&self) -> &[Identifier] {
+        slice(&self.identifier)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -237,24 +580,33 @@ pub struct Affiliation {
     value: String,
 }
 
-impl Author {
-    // fn last_name(&self) -> String { self.the_last_name.value.clone() }
-    // fn fore_name(&self) -> Option<String> { self.the_fore_name.value.clone() }
-    // fn initials(&self) -> String { self.the_initials.value.clone() }
-}
-
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AuthorList {
     #[serde(rename = "@CompleteYN")]
-    complete_flag: String,
+    complete_flag: Option<String>,
+    #[serde(rename = "@Type")]
+    list_type: Option<String>,
     #[serde(rename = "Author")]
     authors: Vec<Author>,
 }
 
 impl AuthorList {
-    // fn is_complete(&self) -> bool {
-    //     self.complete_flag == "Y"
-    // }
+    pub fn is_complete(&self) -> bool {
+        yes_no(&self.complete_flag, true)
+    }
+
+    /// `authors` or `editors`, only set in book author lists.
+    pub fn list_type(&self) -> Option<&str> {
+        self.list_type.as_deref()
+    }
+
+    pub fn is_editors(&self) -> bool {
+        self.list_type() == Some("editors")
+    }
+
+    pub fn authors(&self) -> &[Author] {
+        &self.authors
+    }
 }
 
 // <Language>eng</Language>
@@ -272,6 +624,16 @@ pub struct PublicationType {
     value: String,
 }
 
+impl PublicationType {
+    pub fn ui(&self) -> &str {
+        &self.ui
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PublicationTypeList {
     #[serde(rename = "PublicationType")]
@@ -289,6 +651,21 @@ pub struct ELocationID {
     value: String,
 }
 
+impl ELocationID {
+    /// `doi` or `pii`.
+    pub fn eid_type(&self) -> &str {
+        &self.eid_type
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid_flag != "N"
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GrantID {
     #[serde(rename = "$value")]
@@ -317,6 +694,24 @@ pub struct Grant {
     country: Option<Country>,
 }
 
+impl Grant {
+    pub fn grant_id(&self) -> Option<&str> {
+        self.grant_id.as_ref().and_then(|g| g.value.as_deref())
+    }
+
+    pub fn acronym(&self) -> Option<&str> {
+        self.acronym.as_ref().map(|a| a.value.as_str())
+    }
+
+    pub fn agency(&self) -> Option<&str> {
+        self.agency.as_ref().map(|a| a.value.as_str())
+    }
+
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_ref().map(|c| c.value.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GrantList {
     #[serde(rename = "@CompleteYN")]
@@ -325,14 +720,26 @@ pub struct GrantList {
     grant: Vec<Grant>,
 }
 
+impl GrantList {
+    pub fn is_complete(&self) -> bool {
+        yes_no(&self.complete_yn, true)
+    }
+
+    pub fn grants(&self) -> &[Grant] {
+        &self.grant
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VernacularTitle {
     #[serde(rename = "$value")]
-    value: String,
+    value: RichText,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ArticleDate {
+    #[serde(rename = "@DateType")]
+    date_type: Option<String>,
     #[serde(rename = "Year")]
     year: String,
     #[serde(rename = "Month")]
@@ -341,6 +748,29 @@ pub struct ArticleDate {
     day: String,
 }
 
+impl ArticleDate {
+    /// Only `Electronic` is used.
+    pub fn date_type(&self) -> Option<&str> {
+        self.date_type.as_deref()
+    }
+
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::from_parts(&self.year, Some(&self.month), Some(&self.day), None)
+    }
+
+    pub fn year(&self) -> &str {
+        &self.year
+    }
+
+    pub fn month(&self) -> &str {
+        &self.month
+    }
+
+    pub fn day(&self) -> &str {
+        &self.day
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Article {
     #[serde(rename = "@PubModel")]
@@ -372,16 +802,95 @@ pub struct Article {
 }
 
 impl Article {
-    // fn year(&self) -> Result<u32, std::num::ParseIntError> {
-    //     self.journal.year()
-    // }
+    /// `Print`, `Print-Electronic`, `Electronic`, `Electronic-Print` or
+    /// `Electronic-eCollection`.
+    pub fn pub_model(&self) -> &str {
+        &self.pub_model
+    }
+
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// Publication date of the journal issue.
+    pub fn date(&self) -> Option<PublicationDate> {
+        self.journal.date()
+    }
+
+    pub fn year(&self) -> Option<i32> {
+        self.journal.year()
+    }
+
+    pub fn title(&self) -> Option<&RichText> {
+        self.article_title.as_ref().map(|t| &t.value)
+    }
+
+    pub fn article_title(&self) -> Option<&AtticleTitle> {
+        self.article_title.as_ref()
+    }
+
+    /// Title in the original language, for articles not published in English.
+    pub fn vernacular_title(&self) -> Option<&RichText> {
+        self.vernacular_title.as_ref().map(|t| &t.value)
+    }
+
+    pub fn pages(&self) -> Option<Cow<'_, str>> {
+        self.pagination.as_ref().and_then(|p| p.pages())
+    }
+
+    pub fn pagination(&self) -> Option<&Pagination> {
+        self.pagination.as_ref()
+    }
+
+    pub fn elocation_ids(&self) -> &[ELocationID] {
+        slice(&self.elocation_id)
+    }
+
+    /// The `Abstract` element.
+    pub fn summary(&self) -> Option<&Abstract> {
+        self.summary.as_ref()
+    }
+
+    pub fn author_list(&self) -> Option<&AuthorList> {
+        self.author_list.as_ref()
+    }
+
+    pub fn authors(&self) -> &[Author] {
+        match &self.author_list {
+            Some(list) => &list.authors,
+            None => &[],
+        }
+    }
+
+    pub fn languages(&self) -> Vec<&str> {
+        self.language.iter().map(|l| l.value.as_str()).collect()
+    }
+
+    pub fn data_bank_list(&self) -> Option<&DataBankList> {
+        self.data_bank_list.as_ref()
+    }
+
+    pub fn grant_list(&self) -> Option<&GrantList> {
+        self.grant_list.as_ref()
+    }
 
-    // fn authors(&self) -> Vec<Author> {
-    //     match &self.author_list {
-    //         Some(list) => list.authors.clone(),
-    //         _ => Vec::new(),
-    //     }
-    // }
+    pub fn grants(&self) -> &[Grant] {
+        match &self.grant_list {
+            Some(list) => &list.grant,
+            None => &[],
+        }
+    }
+
+    pub fn publication_types(&self) -> &[PublicationType] {
+        match &self.publication_type_list {
+            Some(list) => &list.publication_type,
+            None => &[],
+        }
+    }
+
+    pub fn article_dates(&self) -> &[ArticleDate] {
+        slice(&self.article_date)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -417,6 +926,25 @@ pub struct MedlineJournalInfo {
     issn_linking: Option<ISSNLinking>,
 }
 
+impl MedlineJournalInfo {
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_ref().map(|c| c.value.as_str())
+    }
+
+    /// The journal title abbreviation used by MEDLINE.
+    pub fn medline_ta(&self) -> Option<&str> {
+        self.medline_ta.as_ref().map(|t| t.value.as_str())
+    }
+
+    pub fn nlm_unique_id(&self) -> &str {
+        &self.nlm_unique_id.value
+    }
+
+    pub fn issn_linking(&self) -> Option<&str> {
+        self.issn_linking.as_ref().map(|i| i.value.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DescriptorName {
     #[serde(rename = "@UI")]
@@ -430,6 +958,24 @@ pub struct DescriptorName {
     value: String,
 }
 
+impl DescriptorName {
+    pub fn ui(&self) -> &str {
+        &self.ui
+    }
+
+    pub fn is_major_topic(&self) -> bool {
+        yes_no(&self.major_topic_yn, false)
+    }
+
+    pub fn descriptor_type(&self) -> Option<&str> {
+        self.descriptor_type.as_deref()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct QualifierName {
     #[serde(rename = "@UI")]
@@ -440,6 +986,20 @@ pub struct QualifierName {
     value: String,
 }
 
+impl QualifierName {
+    pub fn ui(&self) -> &str {
+        &self.ui
+    }
+
+    pub fn is_major_topic(&self) -> bool {
+        yes_no(&self.major_topic_yn, false)
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MeshHeading {
     #[serde(rename = "DescriptorName")]
@@ -448,6 +1008,22 @@ pub struct MeshHeading {
     qualifier_name: Option<Vec<QualifierName>>,
 }
 
+impl MeshHeading {
+    pub fn descriptor(&self) -> &DescriptorName {
+        &self.descriptor_name
+    }
+
+    pub fn qualifiers(&self) -> &[QualifierName] {
+        slice(&self.qualifier_name)
+    }
+
+    /// True when the descriptor or any of its qualifiers is a major topic.
+    pub fn is_major_topic(&self) -> bool {
+        self.descriptor_name.is_major_topic()
+            || self.qualifiers().iter().any(|q| q.is_major_topic())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MeshHeadingList {
     #[serde(rename = "MeshHeading")]
@@ -468,6 +1044,16 @@ pub struct NameOfSubstance {
     value: String,
 }
 
+impl NameOfSubstance {
+    pub fn ui(&self) -> &str {
+        &self.ui
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Chemical {
     #[serde(rename = "RegistryNumber")]
@@ -476,6 +1062,17 @@ pub struct Chemical {
     name_of_substance: NameOfSubstance,
 }
 
+impl Chemical {
+    /// CAS registry or EC number, `0` when there is none.
+    pub fn registry_number(&self) -> &str {
+        &self.registry_number.value
+    }
+
+    pub fn substance(&self) -> &NameOfSubstance {
+        &self.name_of_substance
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChemicalList {
     #[serde(rename = "Chemical")]
@@ -490,6 +1087,16 @@ pub struct Keyword {
     value: Option<String>,
 }
 
+impl Keyword {
+    pub fn is_major_topic(&self) -> bool {
+        yes_no(&self.major_topic_yn, false)
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KeywordList {
     #[serde(rename = "@Owner")]
@@ -498,6 +1105,17 @@ pub struct KeywordList {
     keyword: Option<Vec<Keyword>>,
 }
 
+impl KeywordList {
+    /// `NOTNLM` for author keywords, or the indexing organisation.
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    pub fn keywords(&self) -> &[Keyword] {
+        slice(&self.keyword)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DataBankName {
     #[serde(rename = "$value")]
@@ -524,6 +1142,21 @@ pub struct DataBank {
     accession_number_list: AccessionNumberList,
 }
 
+impl DataBank {
+    pub fn name(&self) -> Option<&str> {
+        self.data_bank_name.as_ref().map(|n| n.value.as_str())
+    }
+
+    pub fn accession_numbers(&self) -> Vec<&str> {
+        self.accession_number_list
+            .accession_number
+            .iter()
+            .flatten()
+            .map(|a| a.value.as_str())
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DataBankList {
     #[serde(rename = "@CompleteYN")]
@@ -532,6 +1165,16 @@ pub struct DataBankList {
     data_bank: Vec<DataBank>,
 }
 
+impl DataBankList {
+    pub fn is_complete(&self) -> bool {
+        self.complete_flag != "N"
+    }
+
+    pub fn data_banks(&self) -> &[DataBank] {
+        &self.data_bank
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Year {
     #[serde(rename = "$value")]
@@ -557,6 +1200,24 @@ pub struct DateCompleted {
     #[serde(rename = "Day")]
     day: Day,
 }
+
+impl DateCompleted {
+    pub fn year(&self) -> &str {
+        &self.year.value
+    }
+
+    pub fn month(&self) -> &str {
+        &self.month.value
+    }
+
+    pub fn day(&self) -> &str {
+        &self.day.value
+    }
+
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::from_parts(self.year(), Some(self.month()), Some(self.day()), None)
+    }
+}
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DateRevised {
     #[serde(rename = "Year")]
@@ -567,12 +1228,47 @@ pub struct DateRevised {
     day: Day,
 }
 
+impl DateRevised {
+    pub fn year(&self) -> &str {
+        &self.year.value
+    }
+
+    pub fn month(&self) -> &str {
+        &self.month.value
+    }
+
+    pub fn day(&self) -> &str {
+        &self.day.value
+    }
+
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::from_parts(self.year(), Some(self.month()), Some(self.day()), None)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SupplMeshName {
-    #[serde(rename = "Type")]
+    #[serde(rename = "@Type")]
     supp_mesh_name_type: Option<String>,
     #[serde(rename = "@UI")]
     ui: String,
+    #[serde(rename = "$value")]
+    value: String,
+}
+
+impl SupplMeshName {
+    /// `Disease`, `Protocol` or `Organism`.
+    pub fn supplement_type(&self) -> Option<&str> {
+        self.supp_mesh_name_type.as_deref()
+    }
+
+    pub fn ui(&self) -> &str {
+        &self.ui
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -612,6 +1308,25 @@ pub struct CommentsCorrections {
     note: Option<Note>,
 }
 
+impl CommentsCorrections {
+    /// Relation such as `CommentOn`, `ErratumIn` or `RetractionOf`.
+    pub fn ref_type(&self) -> &str {
+        &self.ref_type
+    }
+
+    pub fn ref_source(&self) -> Option<&str> {
+        self.ref_source.as_ref().and_then(|r| r.value.as_deref())
+    }
+
+    pub fn pmid(&self) -> Option<&PMID> {
+        self.pmid.as_ref()
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_ref().and_then(|n| n.note.as_deref())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CommentsCorrectionsList {
     #[serde(rename = "CommentsCorrections")]
@@ -642,6 +1357,24 @@ pub struct PersonalNameSubject {
     suffix: Option<String>,
 }
 
+impl PersonalNameSubject {
+    pub fn last_name(&self) -> &str {
+        &self.last_name.value
+    }
+
+    pub fn fore_name(&self) -> Option<&str> {
+        self.fore_name.as_deref()
+    }
+
+    pub fn initials(&self) -> Option<&str> {
+        self.initials.as_deref()
+    }
+
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PersonalNameSubjectList {
     #[serde(rename = "PersonalNameSubject")]
@@ -656,6 +1389,16 @@ pub struct OtherID {
     value: String,
 }
 
+impl OtherID {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OtherAbstract {
     #[serde(rename = "@Type")]
@@ -669,6 +1412,64 @@ pub struct OtherAbstract {
     copyright_information: Option<CopyrightInformation>,
 }
 
+impl OtherAbstract {
+    /// Who wrote the abstract, e.g. `Publisher` or `NASA`.
+    pub fn abstract_type(&self) -> &str {
+        &self.other_abstract_type
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn texts(&self) -> &[AbstractText] {
+        slice(&self.abstract_text)
+    }
+
+    /// All sections as one text, one line per section with its label.
+    pub fn text(&self) -> String {
+        abstract_text(self.texts())
+    }
+
+    pub fn copyright_information(&self) -> Option<&str> {
+        self.copyright_information
+            .as_ref()
+            .map(|c| c.value.as_str())
+    }
+}
+
+// <CoiStatement>The authors declare no conflicts of interest.</CoiStatement>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoiStatement {
+    #[serde(rename = "$value")]
+    value: RichText,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpaceFlightMission {
+    #[serde(rename = "$value")]
+    value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GeneralNote {
+    #[serde(rename = "@Owner")]
+    owner: Option<String>,
+    #[serde(rename = "$value")]
+    value: String,
+}
+
+impl GeneralNote {
+    /// `NLM`, `NASA`, `PIP`, `KIE`, `HSR` or `HMD`; `NLM` when absent.
+    pub fn owner(&self) -> &str {
+        self.owner.as_deref().unwrap_or("NLM")
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MedlineCitation {
     #[serde(rename = "@Status")]
@@ -693,7 +1494,7 @@ pub struct MedlineCitation {
     date_completed: Option<DateCompleted>,
 
     #[serde(rename = "DateRevised")]
-    date_revised: Option<DateCompleted>,
+    date_revised: Option<DateRevised>,
 
     #[serde(rename = "Article")]
     article: Article,
@@ -733,13 +1534,177 @@ pub struct MedlineCitation {
 
     #[serde(rename = "KeywordList")]
     keyword_list: Option<Vec<KeywordList>>,
-    //TODO: CoiStatement?, SpaceFlightMission*, InvestigatorList?, GeneralNote*
+
+    #[serde(rename = "CoiStatement")]
+    coi_statement: Option<CoiStatement>,
+
+    #[serde(rename = "SpaceFlightMission")]
+    space_flight_mission: Option<Vec<SpaceFlightMission>>,
+
+    #[serde(rename = "InvestigatorList")]
+    investigator_list: Option<InvestigatorList>,
+
+    #[serde(rename = "GeneralNote")]
+    general_note: Option<Vec<GeneralNote>>,
 }
 
 impl MedlineCitation {
-    // fn id(&self) -> Result<u32, std::num::ParseIntError> {
-    //     self.pmid.id()
-    // }
+    pub fn id(&self) -> Result<u32, std::num::ParseIntError> {
+        self.pmid.id()
+    }
+
+    pub fn pmid(&self) -> &PMID {
+        &self.pmid
+    }
+
+    /// `Completed`, `In-Process`, `PubMed-not-MEDLINE`, `In-Data-Review`,
+    /// `Publisher`, `MEDLINE` or `OLDMEDLINE`.
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn version_id(&self) -> Option<&str> {
+        self.version_id.as_deref()
+    }
+
+    pub fn version_date(&self) -> Option<&str> {
+        self.version_date.as_deref()
+    }
+
+    pub fn indexing_method(&self) -> Option<&str> {
+        self.indexing_method.as_deref()
+    }
+
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    pub fn date_completed(&self) -> Option<&DateCompleted> {
+        self.date_completed.as_ref()
+    }
+
+    pub fn date_revised(&self) -> Option<&DateRevised> {
+        self.date_revised.as_ref()
+    }
+
+    pub fn article(&self) -> &Article {
+        &self.article
+    }
+
+    pub fn medline_journal_info(&self) -> &MedlineJournalInfo {
+        &self.medline_journal_info
+    }
+
+    pub fn nlm_unique_id(&self) -> &str {
+        &self.medline_journal_info.nlm_unique_id.value
+    }
+
+    pub fn chemicals(&self) -> &[Chemical] {
+        match &self.chemical_list {
+            Some(list) => &list.chemical,
+            None => &[],
+        }
+    }
+
+    pub fn suppl_mesh_names(&self) -> &[SupplMeshName] {
+        match &self.suppl_mesh_list {
+            Some(list) => &list.suppl_mesh_name,
+            None => &[],
+        }
+    }
+
+    pub fn citation_subsets(&self) -> Vec<&str> {
+        self.citation_subset
+            .iter()
+            .flatten()
+            .map(|c| c.value.as_str())
+            .collect()
+    }
+
+    pub fn comments_corrections(&self) -> &[CommentsCorrections] {
+        match &self.comments_corrections_list {
+            Some(list) => &list.comments_corrections,
+            None => &[],
+        }
+    }
+
+    pub fn gene_symbols(&self) -> Vec<&str> {
+        self.gene_symbol_list
+            .iter()
+            .flat_map(|list| list.gene_symbol.iter().map(|g| g.value.as_str()))
+            .collect()
+    }
+
+    pub fn mesh_headings(&self) -> &[MeshHeading] {
+        match &self.mesh_heading_list {
+            Some(list) => &list.mesh_heading,
+            None => &[],
+        }
+    }
+
+    pub fn mesh_descriptor_uis(&self) -> Vec<&str> {
+        self.mesh_headings()
+            .iter()
+            .map(|h| h.descriptor_name.ui.as_str())
+            .collect()
+    }
+
+    pub fn number_of_references(&self) -> Option<&str> {
+        self.number_of_references.as_deref()
+    }
+
+    pub fn personal_name_subjects(&self) -> &[PersonalNameSubject] {
+        match &self.personal_name_subject_list {
+            Some(list) => &list.personal_name_subject,
+            None => &[],
+        }
+    }
+
+    pub fn other_ids(&self) -> &[OtherID] {
+        slice(&self.other_id)
+    }
+
+    pub fn other_abstracts(&self) -> &[OtherAbstract] {
+        slice(&self.other_abstract)
+    }
+
+    pub fn keyword_lists(&self) -> &[KeywordList] {
+        slice(&self.keyword_list)
+    }
+
+    /// Keywords of all keyword lists.
+    pub fn keywords(&self) -> Vec<&str> {
+        self.keyword_lists()
+            .iter()
+            .flat_map(|list| list.keywords())
+            .filter_map(|k| k.value())
+            .collect()
+    }
+
+    /// Conflict of interest statement of the authors.
+    pub fn coi_statement(&self) -> Option<&RichText> {
+        self.coi_statement.as_ref().map(|c| &c.value)
+    }
+
+    pub fn space_flight_missions(&self) -> Vec<&str> {
+        self.space_flight_mission
+            .iter()
+            .flatten()
+            .map(|m| m.value.as_str())
+            .collect()
+    }
+
+    /// Investigators of a study, listed apart from the authors.
+    pub fn investigators(&self) -> &[Investigator] {
+        match &self.investigator_list {
+            Some(list) => &list.investigator,
+            None => &[],
+        }
+    }
+
+    pub fn general_notes(&self) -> &[GeneralNote] {
+        slice(&self.general_note)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -751,20 +1716,27 @@ pub struct ArticleId {
 }
 
 impl ArticleId {
-    // fn is_pubmed_id(&self) -> bool {
-    //     self.id_type == "pubmed"
-    // }
+    /// `pubmed`, `doi`, `pmc`, `pii`, `mid`, `bookaccession` and others.
+    pub fn id_type(&self) -> &str {
+        &self.id_type
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn is_pubmed_id(&self) -> bool {
+        self.id_type == "pubmed"
+    }
 
-    // fn id(&self) -> Option<u32> {
-    //     if self.is_pubmed_id() {
-    //         match str::parse::<u32>(&self.value) {
-    //             Ok(i) => Some(i),
-    //             Err(_e) => None,
-    //         }
-    //     } else {
-    //         None
-    //     }
-    // }
+    /// The PMID, for identifiers of type `pubmed`.
+    pub fn pubmed_id(&self) -> Option<u32> {
+        if self.is_pubmed_id() {
+            self.value.as_deref().and_then(|v| v.trim().parse().ok())
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -774,12 +1746,21 @@ pub struct ArticleIdList {
 }
 
 impl ArticleIdList {
-    // fn pubmed_id(&self) -> Option<u32> {
-    //     let it = self.article_ids.iter();
-    //     let mut found = it.filter(|aid| aid.is_pubmed_id()).map(|aid| aid.id());
-    //     let r = found.next().flatten();
-    //     r
-    // }
+    pub fn ids(&self) -> &[ArticleId] {
+        slice(&self.article_ids)
+    }
+
+    /// First identifier of the given `IdType`, e.g. `"doi"` or `"pmc"`.
+    pub fn find(&self, id_type: &str) -> Option<&str> {
+        self.ids()
+            .iter()
+            .find(|aid| aid.id_type == id_type)
+            .and_then(|aid| aid.value.as_deref())
+    }
+
+    pub fn pubmed_id(&self) -> Option<u32> {
+        self.ids().iter().find_map(|aid| aid.pubmed_id())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -791,16 +1772,24 @@ pub struct Reference {
 }
 
 impl Reference {
-    // fn pubmed_id(&self) -> Option<u32> {
-    //     let mut found = self.article_id_list.iter().map(|list| list.pubmed_id());
-    //     let r = found.next().flatten();
-    //     r
-    // }
+    pub fn citation(&self) -> Option<&str> {
+        self.citation.as_deref()
+    }
+
+    pub fn article_id_list(&self) -> Option<&ArticleIdList> {
+        self.article_id_list.as_ref()
+    }
+
+    pub fn pubmed_id(&self) -> Option<u32> {
+        self.article_id_list
+            .as_ref()
+            .and_then(|list| list.pubmed_id())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReferenceList {
-    #[serde(rename = "@Title")]
+    #[serde(rename = "Title")]
     title: Option<String>,
     #[serde(rename = "Reference")]
     reference: Option<Vec<Reference>>,
@@ -809,19 +1798,30 @@ pub struct ReferenceList {
 }
 
 impl ReferenceList {
-    // fn pubmed_ids(&self) -> Vec<u32> {
-    //     if self.references.is_some() {
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn references(&self) -> &[Reference] {
+        slice(&self.reference)
+    }
 
-    //         let it = self.references.as_ref().unwrap().iter();
-    //         let found = it
-    //             .map(|i| i.pubmed_id())
-    //             .filter(|op| op.is_some())
-    //             .map(|op| op.unwrap());
-    //         Vec::from_iter(found)
-    //     } else {
-    //         Vec::new()
-    //     }
-    // }
+    pub fn reference_lists(&self) -> &[ReferenceList] {
+        slice(&self.reference_list)
+    }
+
+    /// PMIDs of the references of this list and its nested lists.
+    pub fn pubmed_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .references()
+            .iter()
+            .filter_map(|r| r.pubmed_id())
+            .collect();
+        for list in self.reference_lists() {
+            ids.extend(list.pubmed_ids());
+        }
+        ids
+    }
 }
 
 // <PublicationStatus>ppublish</PublicationStatus>
@@ -865,12 +1865,53 @@ pub struct PubMedPubDate {
     second: Option<Second>,
 }
 
+impl PubMedPubDate {
+    /// Event such as `received`, `accepted`, `pubmed`, `medline` or `entrez`.
+    pub fn pub_status(&self) -> &str {
+        &self.pub_status
+    }
+
+    pub fn year(&self) -> &str {
+        &self.year.value
+    }
+
+    pub fn month(&self) -> &str {
+        &self.month.value
+    }
+
+    pub fn day(&self) -> &str {
+        &self.day.value
+    }
+
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::from_parts(self.year(), Some(self.month()), Some(self.day()), None)
+    }
+
+    pub fn hour(&self) -> Option<&str> {
+        self.hour.as_ref().map(|h| h.value.as_str())
+    }
+
+    pub fn minute(&self) -> Option<&str> {
+        self.minute.as_ref().map(|m| m.value.as_str())
+    }
+
+    pub fn second(&self) -> Option<&str> {
+        self.second.as_ref().map(|s| s.value.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct History {
     #[serde(rename = "PubMedPubDate")]
     pubmed_pub_date: Vec<PubMedPubDate>,
 }
 
+impl History {
+    pub fn dates(&self) -> &[PubMedPubDate] {
+        &self.pubmed_pub_date
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Param {
     #[serde(rename = "@Name")]
@@ -878,13 +1919,33 @@ pub struct Param {
     #[serde(rename = "$value")]
     value: String,
 }
+
+impl Param {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Object {
-    #[serde(rename = "Type")]
+    #[serde(rename = "@Type")]
     object_type: String,
     #[serde(rename = "Param")]
     param: Option<Vec<Param>>,
 }
+
+impl Object {
+    pub fn object_type(&self) -> &str {
+        &self.object_type
+    }
+
+    pub fn params(&self) -> &[Param] {
+        slice(&self.param)
+    }
+}
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ObjectList {
     #[serde(rename = "Object")]
@@ -895,33 +1956,52 @@ pub struct ObjectList {
 pub struct PubmedData {
     #[serde(rename = "History")]
     history: Option<History>,
-    #[serde(rename = "ReferenceList")]
-    reference_list: Option<Vec<ReferenceList>>,
     #[serde(rename = "PublicationStatus")]
     publication_status: PublicationStatus,
     #[serde(rename = "ArticleIdList")]
     article_id_list: ArticleIdList,
     #[serde(rename = "ObjectList")]
     object_list: Option<ObjectList>,
+    #[serde(rename = "ReferenceList")]
+    reference_list: Option<Vec<ReferenceList>>,
 }
 
 impl PubmedData {
-    // fn pubmed_references(&self) -> Vec<u32> {
-    //     match &self.reference_list {
-    //         Some(list) => {
-    //         let mut v:Vec<u32> = Vec::new();
-    //         for rlist in list.iter() {
-    //             let mut pmids = rlist.pubmed_ids();
-    //             v.append(&mut pmids);
-    //         }
-    //         v },
-    //         _ => Vec::new()
-    //     }
-    // }
+    /// Dates of the publication history, e.g. when it was received.
+    pub fn history(&self) -> &[PubMedPubDate] {
+        match &self.history {
+            Some(history) => &history.pubmed_pub_date,
+            None => &[],
+        }
+    }
+
+    pub fn reference_lists(&self) -> &[ReferenceList] {
+        slice(&self.reference_list)
+    }
+
+    /// PMIDs of all cited references.
+    pub fn pubmed_references(&self) -> Vec<u32> {
+        self.reference_lists()
+            .iter()
+            .flat_map(|list| list.pubmed_ids())
+            .collect()
+    }
+
+    /// `ppublish`, `epublish`, `aheadofprint` and others.
+    pub fn publication_status(&self) -> &str {
+        &self.publication_status.value
+    }
+
+    pub fn article_id_list(&self) -> &ArticleIdList {
+        &self.article_id_list
+    }
 
-    // fn pmid(&self) -> u32 {
-    //     self.article_id_list.pubmed_id().unwrap()
-    // }
+    pub fn objects(&self) -> &[Object] {
+        match &self.object_list {
+            Some(list) => &list.object,
+            None => &[],
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -933,13 +2013,52 @@ pub struct PubmedArticle {
 }
 
 impl PubmedArticle {
-    // fn pubmed_id(&self) -> Result<u32, ParseIntError> {
-    //     self.medline_citation.id()
-    // }
+    pub fn pubmed_id(&self) -> Result<u32, std::num::ParseIntError> {
+        self.medline_citation.id()
+    }
+
+    pub fn medline_citation(&self) -> &MedlineCitation {
+        &self.medline_citation
+    }
 
-    // fn pubmed_references(&self) -> Vec<u32> {
-    //     self.pubmed_data.pubmed_references()
-    // }
+    pub fn article(&self) -> &Article {
+        &self.medline_citation.article
+    }
+
+    pub fn pubmed_data(&self) -> Option<&PubmedData> {
+        self.pubmed_data.as_ref()
+    }
+
+    /// DOI from `ArticleIdList`, falling back to the article's `ELocationID`.
+    pub fn doi(&self) -> Option<&str> {
+        self.pubmed_data
+            .as_ref()
+            .and_then(|data| data.article_id_list.find("doi"))
+            .or_else(|| {
+                self.medline_citation
+                    .article
+                    .elocation_id
+                    .iter()
+                    .flatten()
+                    .find(|e| e.eid_type == "doi")
+                    .map(|e| e.value.as_str())
+            })
+    }
+
+    /// PubMed Central ID such as `PMC4867133`.
+    pub fn pmc(&self) -> Option<&str> {
+        self.pubmed_data
+            .as_ref()
+            .and_then(|data| data.article_id_list.find("pmc"))
+    }
+
+    /// PMIDs of all cited references.
+    pub fn pubmed_references(&self) -> Vec<u32> {
+        match &self.pubmed_data {
+            Some(data) => data.pubmed_references(),
+            None => Vec::new(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -958,13 +2077,41 @@ pub struct PublisherLocation {
 pub struct Publisher {
     #[serde(rename = "PublisherName")]
     publisher_name: PublisherName,
+    #[serde(rename = "PublisherLocation")]
     publisher_location: Option<PublisherLocation>,
 }
 
+impl Publisher {
+    pub fn name(&self) -> &str {
+        &self.publisher_name.value
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        self.publisher_location.as_ref().map(|l| l.value.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "LinkedTitle<RichText>")]
 pub struct BookTitle {
+    #[serde(flatten)]
+    link: BookshelfLink,
     #[serde(rename = "$value")]
-    value: String,
+    value: RichText,
+}
+
+impl From<LinkedTitle<RichText>> for BookTitle {
+    fn from(title: LinkedTitle<RichText>) -> Self {
+        let (link, value) = title.split();
+        BookTitle { link, value }
+    }
+}
+
+impl BookTitle {
+    /// The Bookshelf book, part and section the title links to.
+    pub fn link(&self) -> &BookshelfLink {
+        &self.link
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -978,25 +2125,69 @@ pub struct EndingDate {
     #[serde(rename = "Year")]
     year: Year,
     #[serde(rename = "Month")]
-    month: Month,
+    month: Option<Month>,
     #[serde(rename = "Day")]
     day: Option<Day>,
     #[serde(rename = "Season")]
     season: Option<Season>,
 }
 
+impl EndingDate {
+    pub fn year(&self) -> &str {
+        &self.year.value
+    }
+
+    pub fn month(&self) -> Option<&str> {
+        self.month.as_ref().map(|m| m.value.as_str())
+    }
+
+    pub fn day(&self) -> Option<&str> {
+        self.day.as_ref().map(|d| d.value.as_str())
+    }
+
+    pub fn season(&self) -> Option<&str> {
+        self.season.as_ref().map(|s| s.value.as_str())
+    }
+
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::from_parts(self.year(), self.month(), self.day(), self.season())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BeginningDate {
     #[serde(rename = "Year")]
     year: Year,
     #[serde(rename = "Month")]
-    month: Month,
+    month: Option<Month>,
     #[serde(rename = "Day")]
     day: Option<Day>,
     #[serde(rename = "Season")]
     season: Option<Season>,
 }
 
+impl BeginningDate {
+    pub fn year(&self) -> &str {
+        &self.year.value
+    }
+
+    pub fn month(&self) -> Option<&str> {
+        self.month.as_ref().map(|m| m.value.as_str())
+    }
+
+    pub fn day(&self) -> Option<&str> {
+        self.day.as_ref().map(|d| d.value.as_str())
+    }
+
+    pub fn season(&self) -> Option<&str> {
+        self.season.as_ref().map(|s| s.value.as_str())
+    }
+
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::from_parts(self.year(), self.month(), self.day(), self.season())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Suffix {
     #[serde(rename = "$value")]
@@ -1005,14 +2196,27 @@ pub struct Suffix {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Identifier {
-    #[serde(rename = "Source")]
+    #[serde(rename = "@Source")]
     source: String,
     #[serde(rename = "$value")]
     value: String,
 }
 
+impl Identifier {
+    /// Identifier scheme such as `ORCID`.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Investigator {
+    #[serde(rename = "@ValidYN")]
+    valid_yn: Option<String>,
     #[serde(rename = "LastName")]
     lastname: LastName,
     #[serde(rename = "ForeName")]
@@ -1021,16 +2225,56 @@ pub struct Investigator {
     initials: Option<Initials>,
     #[serde(rename = "Suffix")]
     suffix: Option<Suffix>,
-    #[serde(rename = "")]
+    #[serde(rename = "Identifier")]
     identifier: Option<Vec<Identifier>>,
-    #[serde(rename = "")]
+    #[serde(rename = "AffiliationInfo")]
     affilication_info: Option<Vec<AffiliationInfo>>,
 }
 
+impl Investigator {
+    pub fn is_valid(&self) -> bool {
+        yes_no(&self.valid_yn, true)
+    }
+
+    pub fn last_name(&self) -> &str {
+        &self.lastname.value
+    }
+
+    pub fn fore_name(&self) -> Option<&str> {
+        self.fore_name.as_ref().map(|n| n.value.as_str())
+    }
+
+    pub fn initials(&self) -> Option<&str> {
+        self.initials.as_ref().map(|i| i.value.as_str())
+    }
+
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_ref().map(|s| s.value.as_str())
+    }
+
+    pub fn identifiers(&self) -> &[Identifier] {
+        slice(&self.identifier)
+    }
+
+    pub fn affiliations(&self) -> Vec<&str> {
+        self.affilication_info
+            .iter()
+            .flatten()
+            .flat_map(|info| info.affiliations())
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InvestigatorList {
     #[serde(rename = "Investigator")]
-    publisher: Investigator,
+    investigator: Vec<Investigator>,
+}
+
+impl InvestigatorList {
+    pub fn investigators(&self) -> &[Investigator] {
+        &self.investigator
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1104,6 +2348,94 @@ pub struct Book {
     report_number: Option<ReportNumber>,
 }
 
+impl Book {
+    pub fn publisher(&self) -> &Publisher {
+        &self.publisher
+    }
+
+    pub fn title(&self) -> &RichText {
+        &self.book_title.value
+    }
+
+    /// The `BookTitle` element, with its Bookshelf link.
+    pub fn book_title(&self) -> &BookTitle {
+        &self.book_title
+    }
+
+    pub fn pub_date(&self) -> &PubDate {
+        &self.pub_date
+    }
+
+    pub fn date(&self) -> Option<PublicationDate> {
+        self.pub_date.date()
+    }
+
+    pub fn beginning_date(&self) -> Option<&BeginningDate> {
+        self.beginning_date.as_ref()
+    }
+
+    pub fn ending_date(&self) -> Option<&EndingDate> {
+        self.ending_date.as_ref()
+    }
+
+    pub fn author_lists(&self) -> &[AuthorList] {
+        slice(&self.author_list)
+    }
+
+    /// Authors of the book as a whole, from its lists that do not name
+    /// editors.
+    pub fn authors(&self) -> Vec<&Author> {
+        people(self.author_lists(), false)
+    }
+
+    pub fn editors(&self) -> Vec<&Author> {
+        people(self.author_lists(), true)
+    }
+
+    pub fn investigators(&self) -> &[Investigator] {
+        match &self.investigator_list {
+            Some(list) => &list.investigator,
+            None => &[],
+        }
+    }
+
+    pub fn volume(&self) -> Option<&str> {
+        self.volume.as_ref().map(|v| v.value.as_str())
+    }
+
+    pub fn volume_title(&self) -> Option<&str> {
+        self.volume_title.as_ref().map(|v| v.value.as_str())
+    }
+
+    pub fn edition(&self) -> Option<&str> {
+        self.edition.as_ref().map(|e| e.value.as_str())
+    }
+
+    pub fn collection_title(&self) -> Option<&str> {
+        self.collection_title.as_ref().map(|c| c.value.as_str())
+    }
+
+    pub fn isbns(&self) -> Vec<&str> {
+        self.isbn
+            .iter()
+            .flatten()
+            .map(|i| i.value.as_str())
+            .collect()
+    }
+
+    pub fn elocation_ids(&self) -> &[ELocationID] {
+        slice(&self.elocation_id)
+    }
+
+    pub fn medium(&self) -> Option<&str> {
+        self.medium.as_ref().map(|m| m.value.as_str())
+    }
+
+    pub fn report_number(&self) -> Option<&str> {
+        self.report_number.as_ref().map(|r| r.value.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LocationLabel {
     #[serde(rename = "@Type")]
@@ -1112,25 +2444,92 @@ pub struct LocationLabel {
     value: String,
 }
 
+impl LocationLabel {
+    /// `chapter`, `section`, `appendix`, `part` and others.
+    pub fn label_type(&self) -> Option<&str> {
+        self.location_label_type.as_deref()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "LinkedTitle<RichText>")]
 pub struct ArticleTitle {
+    #[serde(flatten)]
+    link: BookshelfLink,
     #[serde(rename = "$value")]
-    value: String,
+    value: RichText,
+}
+
+impl From<LinkedTitle<RichText>> for ArticleTitle {
+    fn from(title: LinkedTitle<RichText>) -> Self {
+        let (link, value) = title.split();
+        ArticleTitle { link, value }
+    }
+}
+
+impl ArticleTitle {
+    /// The Bookshelf book, part and section the title links to.
+    pub fn link(&self) -> &BookshelfLink {
+        &self.link
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "LinkedTitle<String>")]
 pub struct SectionTitle {
+    #[serde(flatten)]
+    link: BookshelfLink,
     #[serde(rename = "$value")]
     value: String,
 }
 
+impl From<LinkedTitle<String>> for SectionTitle {
+    fn from(title: LinkedTitle<String>) -> Self {
+        let (link, value) = title.split();
+        SectionTitle { link, value }
+    }
+}
+
+impl SectionTitle {
+    /// The Bookshelf book, part and section the title links to.
+    pub fn link(&self) -> &BookshelfLink {
+        &self.link
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Section {
+    #[serde(rename = "LocationLabel")]
     location_label: Option<LocationLabel>,
+    #[serde(rename = "SectionTitle")]
     section_title: SectionTitle,
+    #[serde(rename = "Section")]
     section: Option<Vec<Section>>,
 }
 
+impl Section {
+    pub fn location_label(&self) -> Option<&LocationLabel> {
+        self.location_label.as_ref()
+    }
+
+    pub fn title(&self) -> &str {
+        &self.section_title.value
+    }
+
+    /// The `SectionTitle` element, with its Bookshelf link.
+    pub fn section_title(&self) -> &SectionTitle {
+        &self.section_title
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        slice(&self.section)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Item {
     #[serde(rename = "$value")]
@@ -1139,10 +2538,22 @@ pub struct Item {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ItemList {
+    #[serde(rename = "@ListType")]
+    list_type: String,
     #[serde(rename = "Item")]
     item: Vec<Item>,
 }
 
+impl ItemList {
+    pub fn list_type(&self) -> &str {
+        &self.list_type
+    }
+
+    pub fn items(&self) -> Vec<&str> {
+        self.item.iter().map(|i| i.value.as_str()).collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Sections {
     #[serde(rename = "Section")]
@@ -1163,6 +2574,28 @@ pub struct ContributionDate {
     season: Option<Season>,
 }
 
+impl ContributionDate {
+    pub fn year(&self) -> &str {
+        &self.year.value
+    }
+
+    pub fn month(&self) -> Option<&str> {
+        self.month.as_ref().map(|m| m.value.as_str())
+    }
+
+    pub fn day(&self) -> Option<&str> {
+        self.day.as_ref().map(|d| d.value.as_str())
+    }
+
+    pub fn season(&self) -> Option<&str> {
+        self.season.as_ref().map(|s| s.value.as_str())
+    }
+
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::from_parts(self.year(), self.month(), self.day(), self.season())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BookDocument {
     #[serde(rename = "PMID")]
@@ -1205,6 +2638,130 @@ pub struct BookDocument {
     reference_list: Option<Vec<ReferenceList>>,
 }
 
+impl BookDocument {
+    pub fn pmid(&self) -> &PMID {
+        &self.pmid
+    }
+
+    pub fn article_id_list(&self) -> &ArticleIdList {
+        &self.article_id_list
+    }
+
+    pub fn book(&self) -> &Book {
+        &self.book
+    }
+
+    pub fn location_labels(&self) -> &[LocationLabel] {
+        slice(&self.location_label)
+    }
+
+    /// Title of the chapter or section, when the document is part of a book.
+    pub fn title(&self) -> Option<&RichText> {
+        self.article_title.as_ref().map(|t| &t.value)
+    }
+
+    /// The `ArticleTitle` element, with its Bookshelf link.
+    pub fn article_title(&self) -> Option<&ArticleTitle> {
+        self.article_title.as_ref()
+    }
+
+    pub fn vernacular_title(&self) -> Option<&RichText> {
+        self.vernacular_title.as_ref().map(|t| &t.value)
+    }
+
+    pub fn pages(&self) -> Option<Cow<'_, str>> {
+        self.pagination.as_ref().and_then(|p| p.pages())
+    }
+
+    pub fn languages(&self) -> Vec<&str> {
+        self.language
+            .iter()
+            .flatten()
+            .map(|l| l.value.as_str())
+            .collect()
+    }
+
+    pub fn author_lists(&self) -> &[AuthorList] {
+        slice(&self.author_list)
+    }
+
+    pub fn investigators(&self) -> &[Investigator] {
+        match &self.investigator_list {
+            Some(list) => &list.investigator,
+            None => &[],
+        }
+    }
+
+    /// Authors of the document itself, from its lists that do not name
+    /// editors. The authors of the book are on [`Book::authors`].
+    pub fn authors(&self) -> Vec<&Author> {
+        people(self.author_lists(), false)
+    }
+
+    /// Editors named on the document itself.
+    pub fn editors(&self) -> Vec<&Author> {
+        people(self.author_lists(), true)
+    }
+
+    /// Authors to credit the record to: those of the document, followed by
+    /// those of the book when the document is the whole book rather than a
+    /// chapter of it.
+    pub fn primary_authors(&self) -> Vec<&Author> {
+        let mut authors = self.authors();
+        if self.title().is_none() {
+            authors.extend(self.book().authors());
+        }
+        authors
+    }
+
+    /// Editors of the document followed by those of its book.
+    pub fn all_editors(&self) -> Vec<&Author> {
+        let mut editors = self.editors();
+        editors.extend(self.book().editors());
+        editors
+    }
+
+    pub fn publication_types(&self) -> &[PublicationType] {
+        slice(&self.publication_type)
+    }
+
+    /// The `Abstract` element.
+    pub fn summary(&self) -> Option<&Abstract> {
+        self.summary.as_ref()
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        match &self.sections {
+            Some(sections) => &sections.section,
+            None => &[],
+        }
+    }
+
+    pub fn keyword_lists(&self) -> &[KeywordList] {
+        slice(&self.keyword_list)
+    }
+
+    pub fn contribution_date(&self) -> Option<&ContributionDate> {
+        self.contribution_date.as_ref()
+    }
+
+    pub fn date_revised(&self) -> Option<&DateRevised> {
+        self.date_revised.as_ref()
+    }
+
+    pub fn grant_list(&self) -> Option<&GrantList> {
+        self.grant_list.as_ref()
+    }
+
+    pub fn item_lists(&self) -> &[ItemList] {
+        slice(&self.item_list)
+    }
+
+    pub fn reference_lists(&self) -> &[ReferenceList] {
+        slice(&self.reference_list)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PubmedBookData {
     #[serde(rename = "History")]
@@ -1217,7 +2774,31 @@ pub struct PubmedBookData {
     article_id_list: ArticleIdList,
 
     #[serde(rename = "ObjectList")]
-    object_list: ObjectList,
+    object_list: Option<ObjectList>,
+}
+
+impl PubmedBookData {
+    pub fn history(&self) -> &[PubMedPubDate] {
+        match &self.history {
+            Some(history) => &history.pubmed_pub_date,
+            None => &[],
+        }
+    }
+
+    pub fn publication_status(&self) -> &str {
+        &self.publication_status.value
+    }
+
+    pub fn article_id_list(&self) -> &ArticleIdList {
+        &self.article_id_list
+    }
+
+    pub fn objects(&self) -> &[Object] {
+        match &self.object_list {
+            Some(list) => &list.object,
+            None => &[],
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1228,6 +2809,20 @@ pub struct PubmedBookArticle {
     pubmed_book_data: Option<PubmedBookData>,
 }
 
+impl PubmedBookArticle {
+    pub fn pubmed_id(&self) -> Result<u32, std::num::ParseIntError> {
+        self.book_document.pmid.id()
+    }
+
+    pub fn book_document(&self) -> &BookDocument {
+        &self.book_document
+    }
+
+    pub fn pubmed_book_data(&self) -> Option<&PubmedBookData> {
+        self.pubmed_book_data.as_ref()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DeleteCitation {
     #[serde(rename = "PMID")]
@@ -1240,4 +2835,14 @@ pub struct DeleteDocument {
     pub pmid: Option<Vec<PMID>>,
 }
 
-
+/// One top-level entry of a `PubmedArticleSet`.
+// nearly every record is an article, so boxing the large variants would only
+// add an allocation per record
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Record {
+    Article(PubmedArticle),
+    BookArticle(PubmedBookArticle),
+    DeleteCitation(DeleteCitation),
+    DeleteDocument(DeleteDocument),
+}
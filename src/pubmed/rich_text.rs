@@ -0,0 +1,525 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt;
+
+use super::mathml::Math;
+
+/// Text with the inline markup PubMed allows in titles, abstracts and a few
+/// other elements: `<i>`, `<b>`, `<sup>`, `<sub>`, `<u>`, `DispFormula` and
+/// MathML.
+///
+/// `read_article` keeps inline elements as tags inside the element text, so
+/// the text of `Effect of <i>CYP3A5</i>` arrives as that very string. It is
+/// parsed into a tree of `Inline` nodes here, and serialized back to the same
+/// string, which keeps JSON and BSON output unchanged. Anything that looks
+/// like a tag but is not one of the inline elements stays text, so
+/// `p <0.05` survives. Text that would read as an inline element, such as an
+/// escaped `&lt;b&gt;` in the XML, is written `&lt;b>` in the markup, and a
+/// literal `&lt;` or `&amp;` as `&amp;lt;` or `&amp;amp;`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RichText {
+    nodes: Vec<Inline>,
+}
+
+/// A node of rich text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Styled(Style, Vec<Inline>),
+    /// Contents of a `DispFormula` element.
+    Formula(Vec<Inline>),
+    Math(Math),
+}
+
+/// Inline elements that change the look of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Italic,
+    Bold,
+    Superscript,
+    Subscript,
+    Underline,
+}
+
+impl Style {
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Style::Italic => "i",
+            Style::Bold => "b",
+            Style::Superscript => "sup",
+            Style::Subscript => "sub",
+            Style::Underline => "u",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Style> {
+        match tag {
+            "i" => Some(Style::Italic),
+            "b" => Some(Style::Bold),
+            "sup" => Some(Style::Superscript),
+            "sub" => Some(Style::Subscript),
+            "u" => Some(Style::Underline),
+            _ => None,
+        }
+    }
+}
+
+/// The styles in effect for a run of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Styles {
+    pub italic: bool,
+    pub bold: bool,
+    pub superscript: bool,
+    pub subscript: bool,
+    pub underline: bool,
+    /// Inside a `DispFormula`.
+    pub formula: bool,
+}
+
+impl Styles {
+    fn with(mut self, style: Style) -> Styles {
+        match style {
+            Style::Italic => self.italic = true,
+            Style::Bold => self.bold = true,
+            Style::Superscript => self.superscript = true,
+            Style::Subscript => self.subscript = true,
+            Style::Underline => self.underline = true,
+        }
+        self
+    }
+}
+
+/// A stretch of text, or a formula, with the same styles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Run<'a> {
+    pub styles: Styles,
+    pub content: RunContent<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunContent<'a> {
+    Text(&'a str),
+    Math(&'a Math),
+}
+
+impl RichText {
+    /// Parses text with inline tags, as produced by `read_article`; `&lt;`
+    /// and `&amp;` stand for `<` and `&`.
+    pub fn parse(markup: &str) -> RichText {
+        RichText {
+            nodes: Parser::default().parse(markup),
+        }
+    }
+
+    pub fn nodes(&self) -> &[Inline] {
+        &self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The text flattened into runs of uniform style.
+    pub fn runs(&self) -> Vec<Run<'_>> {
+        let mut runs = Vec::new();
+        collect_runs(&self.nodes, Styles::default(), &mut runs);
+        runs
+    }
+
+    /// The text with inline tags, as it was read.
+    pub fn to_markup(&self) -> String {
+        let mut out = String::new();
+        write_markup(&self.nodes, &mut Vec::new(), &mut out);
+        out
+    }
+
+    /// XML content: inline elements as elements, MathML as read, and text
+    /// escaped.
+    pub fn to_xml(&self) -> String {
+        let mut out = String::new();
+        write_xml(&self.nodes, &mut out);
+        out
+    }
+
+    /// The text without any markup.
+    pub fn to_plain(&self) -> String {
+        let mut out = String::new();
+        for run in self.runs() {
+            match run.content {
+                RunContent::Text(text) => out.push_str(text),
+                RunContent::Math(math) => out.push_str(&math.to_plain()),
+            }
+        }
+        out
+    }
+
+    /// HTML with `<i>`, `<b>`, `<sup>`, `<sub>` and `<u>` elements; formulas
+    /// become unprefixed MathML, which browsers render.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        write_html(&self.nodes, &mut out);
+        out
+    }
+
+    /// Markdown using `*` and `**` for italic and bold, inline HTML for the
+    /// styles Markdown has no syntax for and `$...$` LaTeX for formulas.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        write_markdown(&self.nodes, &mut out);
+        out
+    }
+}
+
+impl fmt::Display for RichText {
+    /// Plain text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_plain())
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(markup: &str) -> Self {
+        RichText::parse(markup)
+    }
+}
+
+/// Name under which rich text serializes as a newtype struct. Serializers
+/// that write the markup as is, such as the XML writer, recognize it; the
+/// others see the markup string.
+pub const RICH_TEXT_NEWTYPE: &str = "$rbiblio::RichText";
+
+impl Serialize for RichText {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(RICH_TEXT_NEWTYPE, &self.to_markup())
+    }
+}
+
+impl<'de> Deserialize<'de> for RichText {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|markup| RichText::parse(&markup))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Open {
+    Style(Style),
+    Formula,
+}
+
+#[derive(Default)]
+struct Parser {
+    stack: Vec<(Open, Vec<Inline>)>,
+    nodes: Vec<Inline>,
+    text: String,
+}
+
+impl Parser {
+    fn parse(mut self, markup: &str) -> Vec<Inline> {
+        let mut rest = markup;
+        while let Some(at) = rest.find(['<', '&']) {
+            self.text.push_str(&rest[..at]);
+            rest = &rest[at..];
+            if let Some((c, len)) = entity(rest) {
+                self.text.push(c);
+                rest = &rest[len..];
+            } else if rest.starts_with('&') {
+                self.text.push('&');
+                rest = &rest[1..];
+            } else if let Some(end) = math_end(rest) {
+                self.flush();
+                self.nodes.push(Inline::Math(Math::new(&rest[..end])));
+                rest = &rest[end..];
+            } else if let Some((tag, closing, len)) = inline_tag(rest) {
+                if closing {
+                    self.close(tag, &rest[..len]);
+                } else {
+                    self.flush();
+                    let parent = std::mem::take(&mut self.nodes);
+                    self.stack.push((tag, parent));
+                }
+                rest = &rest[len..];
+            } else {
+                self.text.push('<');
+                rest = &rest[1..];
+            }
+        }
+        self.text.push_str(rest);
+        self.flush();
+        // elements left open are closed at the end of the text
+        while let Some((open, parent)) = self.stack.pop() {
+            let children = std::mem::replace(&mut self.nodes, parent);
+            self.nodes.push(node(open, children));
+        }
+        self.nodes
+    }
+
+    fn close(&mut self, tag: Open, literal: &str) {
+        if self.stack.last().map(|(open, _)| *open) != Some(tag) {
+            // a stray end tag is text
+            self.text.push_str(literal);
+            return;
+        }
+        self.flush();
+        let (open, parent) = self.stack.pop().unwrap();
+        let children = std::mem::replace(&mut self.nodes, parent);
+        if !children.is_empty() {
+            self.nodes.push(node(open, children));
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.text.is_empty() {
+            return;
+        }
+        let text = std::mem::take(&mut self.text);
+        match self.nodes.last_mut() {
+            Some(Inline::Text(last)) => last.push_str(&text),
+            _ => self.nodes.push(Inline::Text(text)),
+        }
+    }
+}
+
+/// The character escaped by the `&lt;` or `&amp;` at the start of `s`.
+fn entity(s: &str) -> Option<(char, usize)> {
+    if s.starts_with("&lt;") {
+        Some(('<', 4))
+    } else if s.starts_with("&amp;") {
+        Some(('&', 5))
+    } else {
+        None
+    }
+}
+
+/// Escapes text so that `RichText::parse` reads it back as text: the `<` of
+/// an inline tag or MathML element becomes `&lt;`, and the `&` of a literal
+/// `&lt;` or `&amp;` becomes `&amp;`. With `open`, an end tag is only escaped
+/// within an element it would close, as others are read as text anyway.
+fn escape_text(text: &str, open: Option<&[Open]>, out: &mut String) {
+    let mut rest = text;
+    while let Some(at) = rest.find(['<', '&']) {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let escape = if rest.starts_with('&') {
+            entity(rest).is_some()
+        } else if math_end(rest).is_some() {
+            true
+        } else {
+            match (inline_tag(rest), open) {
+                (Some((_, false, _)), _) | (Some((_, true, _)), None) => true,
+                (Some((tag, true, _)), Some(open)) => open.contains(&tag),
+                (None, _) => false,
+            }
+        };
+        match (escape, rest.starts_with('&')) {
+            (true, true) => out.push_str("&amp;"),
+            (true, false) => out.push_str("&lt;"),
+            (false, true) => out.push('&'),
+            (false, false) => out.push('<'),
+        }
+        rest = &rest[1..];
+    }
+    out.push_str(rest);
+}
+
+/// Escapes element text for the markup `read_article` produces, see
+/// `RichText`.
+pub(crate) fn escape_markup(text: &str) -> Cow<'_, str> {
+    let mut out = String::new();
+    escape_text(text, None, &mut out);
+    if out == text {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(out)
+    }
+}
+
+fn node(open: Open, children: Vec<Inline>) -> Inline {
+    match open {
+        Open::Style(style) => Inline::Styled(style, children),
+        Open::Formula => Inline::Formula(children),
+    }
+}
+
+/// Recognises `<i>`, `</i>` and the other inline tags at the start of `s`.
+fn inline_tag(s: &str) -> Option<(Open, bool, usize)> {
+    let end = s.find('>')?;
+    let name = &s[1..end];
+    let (closing, name) = match name.strip_prefix('/') {
+        Some(name) => (true, name),
+        None => (false, name),
+    };
+    let open = match name {
+        "DispFormula" => Open::Formula,
+        name => Open::Style(Style::from_tag(name)?),
+    };
+    Some((open, closing, end + 1))
+}
+
+/// Length of the `mml:math` (or unprefixed `math`) element at the start of
+/// `s`.
+fn math_end(s: &str) -> Option<usize> {
+    let (after, end) = match s.strip_prefix("<mml:math") {
+        Some(after) => (after, "</mml:math>"),
+        None => (s.strip_prefix("<math")?, "</math>"),
+    };
+    if !after.starts_with(['>', ' ', '/', '\n', '\t']) {
+        return None;
+    }
+    if let Some(tag_end) = s.find('>') {
+        if s[..tag_end].ends_with('/') {
+            return Some(tag_end + 1);
+        }
+    }
+    s.find(end).map(|at| at + end.len())
+}
+
+fn collect_runs<'a>(nodes: &'a [Inline], styles: Styles, runs: &mut Vec<Run<'a>>) {
+    for node in nodes {
+        match node {
+            Inline::Text(text) => runs.push(Run {
+                styles,
+                content: RunContent::Text(text),
+            }),
+            Inline::Styled(style, children) => collect_runs(children, styles.with(*style), runs),
+            Inline::Formula(children) => collect_runs(
+                children,
+                Styles {
+                    formula: true,
+                    ..styles
+                },
+                runs,
+            ),
+            Inline::Math(math) => runs.push(Run {
+                styles,
+                content: RunContent::Math(math),
+            }),
+        }
+    }
+}
+
+fn write_markup(nodes: &[Inline], open: &mut Vec<Open>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Inline::Text(text) => escape_text(text, Some(open), out),
+            Inline::Styled(style, children) => {
+                out.push_str(&format!("<{}>", style.tag()));
+                open.push(Open::Style(*style));
+                write_markup(children, open, out);
+                open.pop();
+                out.push_str(&format!("</{}>", style.tag()));
+            }
+            Inline::Formula(children) => {
+                out.push_str("<DispFormula>");
+                open.push(Open::Formula);
+                write_markup(children, open, out);
+                open.pop();
+                out.push_str("</DispFormula>");
+            }
+            Inline::Math(math) => out.push_str(math.mathml()),
+        }
+    }
+}
+
+fn write_xml(nodes: &[Inline], out: &mut String) {
+    for node in nodes {
+        match node {
+            Inline::Text(text) => {
+                for c in text.chars() {
+                    match c {
+                        '&' => out.push_str("&amp;"),
+                        '<' => out.push_str("&lt;"),
+                        '>' => out.push_str("&gt;"),
+                        c => out.push(c),
+                    }
+                }
+            }
+            Inline::Styled(style, children) => {
+                out.push_str(&format!("<{}>", style.tag()));
+                write_xml(children, out);
+                out.push_str(&format!("</{}>", style.tag()));
+            }
+            Inline::Formula(children) => {
+                out.push_str("<DispFormula>");
+                write_xml(children, out);
+                out.push_str("</DispFormula>");
+            }
+            Inline::Math(math) => out.push_str(math.mathml()),
+        }
+    }
+}
+
+pub(super) fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn write_html(nodes: &[Inline], out: &mut String) {
+    for node in nodes {
+        match node {
+            Inline::Text(text) => escape_html(text, out),
+            Inline::Styled(style, children) => {
+                out.push_str(&format!("<{}>", style.tag()));
+                write_html(children, out);
+                out.push_str(&format!("</{}>", style.tag()));
+            }
+            Inline::Formula(children) => {
+                out.push_str("<span class=\"disp-formula\">");
+                write_html(children, out);
+                out.push_str("</span>");
+            }
+            Inline::Math(math) => out.push_str(&math.to_html()),
+        }
+    }
+}
+
+fn escape_markdown(text: &str, out: &mut String) {
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+fn write_markdown(nodes: &[Inline], out: &mut String) {
+    for node in nodes {
+        match node {
+            Inline::Text(text) => escape_markdown(text, out),
+            Inline::Styled(style, children) => {
+                let (open, close) = match style {
+                    Style::Italic => ("*", "*"),
+                    Style::Bold => ("**", "**"),
+                    Style::Superscript => ("<sup>", "</sup>"),
+                    Style::Subscript => ("<sub>", "</sub>"),
+                    Style::Underline => ("<u>", "</u>"),
+                };
+                let mut inner = String::new();
+                write_markdown(children, &mut inner);
+                // emphasis must not start or end with white space
+                let trimmed = inner.trim();
+                if trimmed.is_empty() {
+                    out.push_str(&inner);
+                    continue;
+                }
+                let start = inner.len() - inner.trim_start().len();
+                out.push_str(&inner[..start]);
+                out.push_str(open);
+                out.push_str(trimmed);
+                out.push_str(close);
+                out.push_str(&inner[start + trimmed.len()..]);
+            }
+            Inline::Formula(children) => write_markdown(children, out),
+            Inline::Math(math) => match math.to_latex() {
+                Some(latex) => out.push_str(&format!("${}$", latex)),
+                None => escape_markdown(&math.to_plain(), out),
+            },
+        }
+    }
+}
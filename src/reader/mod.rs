@@ -0,0 +1,695 @@
+use flate2::read::GzDecoder;
+use quick_xml::Writer;
+use quick_xml::{
+    de::Deserializer,
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader,
+};
+use serde::de::DeserializeOwned;
+use serde_path_to_error::Segment;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::dtd::DtdVersion;
+use crate::error::{ErrorContext, ParseError};
+use crate::pubmed::{escape_markup, DeleteDocument, PubmedArticle, Record};
+use crate::report::FileSummary;
+
+mod parallel;
+
+pub use parallel::ParallelReader;
+
+/// Top-level elements of a `PubmedArticleSet` (or `BookDocumentSet`) that
+/// are read as records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    Article,
+    BookArticle,
+    DeleteCitation,
+    DeleteDocument,
+}
+
+impl RecordKind {
+    fn from_tag(name: &[u8]) -> Option<RecordKind> {
+        match name {
+            b"PubmedArticle" => Some(RecordKind::Article),
+            b"PubmedBookArticle" => Some(RecordKind::BookArticle),
+            b"DeleteCitation" => Some(RecordKind::DeleteCitation),
+            b"DeleteDocument" => Some(RecordKind::DeleteDocument),
+            _ => None,
+        }
+    }
+
+    pub fn tag(&self) -> &'static str {
+        match self {
+            RecordKind::Article => "PubmedArticle",
+            RecordKind::BookArticle => "PubmedBookArticle",
+            RecordKind::DeleteCitation => "DeleteCitation",
+            RecordKind::DeleteDocument => "DeleteDocument",
+        }
+    }
+}
+
+/// Copies the record started by `tag` into `output`, keeping the element
+/// path in `path` so that a failure can be located.
+fn read_article<R: BufRead>(
+    reader: &mut quick_xml::Reader<R>,
+    tag: &str,
+    output: &mut Vec<u8>,
+    path: &mut Vec<String>,
+) -> Result<(), quick_xml::Error> {
+    let mut depth: u32 = 0;
+    let mut buf: Vec<u8> = Vec::new();
+    let mut writer = Writer::new(output);
+    let pubmed_article_end_tag = BytesEnd::new(tag);
+
+    match writer.write_event(Event::Start(BytesStart::new(tag))) {
+        Ok(_) => loop {
+            buf.clear();
+            match reader.read_event_into(&mut buf) {
+                Ok(event) => match event {
+                    Event::Start(e) if e.local_name().as_ref() == b"i" => {
+                        let t = Event::Text(BytesText::new("<i>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::Start(e) if e.local_name().as_ref() == b"b" => {
+                        let t = Event::Text(BytesText::new("<b>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::Start(e) if e.local_name().as_ref() == b"sup" => {
+                        let t = Event::Text(BytesText::new("<sup>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::Start(e) if e.local_name().as_ref() == b"sub" => {
+                        let t = Event::Text(BytesText::new("<sub>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::Start(e) if e.local_name().as_ref() == b"u" => {
+                        let t = Event::Text(BytesText::new("<u>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+
+                    Event::End(e) if e.local_name().as_ref() == b"i" => {
+                        let t = Event::Text(BytesText::new("</i>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::End(e) if e.local_name().as_ref() == b"b" => {
+                        let t = Event::Text(BytesText::new("</b>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::End(e) if e.local_name().as_ref() == b"sup" => {
+                        let t = Event::Text(BytesText::new("</sup>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::End(e) if e.local_name().as_ref() == b"sub" => {
+                        let t = Event::Text(BytesText::new("</sub>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::End(e) if e.local_name().as_ref() == b"u" => {
+                        let t = Event::Text(BytesText::new("</u>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+
+                    Event::Start(e) if is_mathml(&e) => {
+                        let mathml = read_mathml(reader, e)?;
+                        writer.write_event(Event::Text(BytesText::new(&mathml)))?;
+                    }
+                    Event::Empty(e) if is_mathml(&e) => {
+                        let mut mathml = Writer::new(Vec::new());
+                        mathml.write_event(Event::Empty(e))?;
+                        let mathml = String::from_utf8_lossy(&mathml.into_inner()).into_owned();
+                        writer.write_event(Event::Text(BytesText::new(&mathml)))?;
+                    }
+
+                    Event::Start(e) if e.local_name().as_ref() == b"DispFormula" => {
+                        let t = Event::Text(BytesText::new("<DispFormula>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::End(e) if e.local_name().as_ref() == b"DispFormula" => {
+                        let t = Event::Text(BytesText::new("</DispFormula>"));
+                        match writer.write_event(t) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+
+                    Event::Start(e) => {
+                        depth += 1;
+                        path.push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+                        match writer.write_event(Event::Start(e)) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::Text(t) if depth > 0 => write_text(&mut writer, t)?,
+                    Event::CData(t) => {
+                        let text = String::from_utf8_lossy(&t);
+                        let text = escape_markup(&text);
+                        writer.write_event(Event::Text(BytesText::new(&text)))?;
+                    }
+                    // inline markup without text has nothing to keep
+                    Event::Empty(e)
+                        if matches!(
+                            e.local_name().as_ref(),
+                            b"i" | b"b" | b"sup" | b"sub" | b"u" | b"DispFormula"
+                        ) => {}
+                    // copied so that the model, and audits, see them
+                    Event::Empty(e) => writer.write_event(Event::Empty(e))?,
+                    Event::End(e) if e == pubmed_article_end_tag => {
+                        match writer.write_event(Event::End(e)) {
+                            Ok(_) => return Ok(()),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    Event::End(e) => {
+                        depth -= 1;
+                        path.pop();
+                        match writer.write_event(Event::End(e)) {
+                            Ok(_) => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    _ => (),
+                },
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Copies element text, escaping what would read as inline markup once it
+/// is unescaped; see `RichText`. Text that XML escapes nothing in cannot
+/// hold any.
+fn write_text<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    text: BytesText,
+) -> Result<(), quick_xml::Error> {
+    if !text.contains(&b'&') {
+        return writer.write_event(Event::Text(text));
+    }
+    let unescaped = text.unescape()?;
+    match escape_markup(&unescaped) {
+        Cow::Borrowed(_) => writer.write_event(Event::Text(text.clone())),
+        Cow::Owned(escaped) => writer.write_event(Event::Text(BytesText::new(&escaped))),
+    }
+}
+
+pub(crate) fn is_mathml(e: &BytesStart) -> bool {
+    e.name().prefix().is_some_and(|p| p.as_ref() == b"mml") || e.name().as_ref() == b"math"
+}
+
+/// Copies the MathML element started by `start` verbatim, attributes, empty
+/// elements and escaped text included. It is stored as text in the record,
+/// so that formulas reach `RichText` as the markup they were written in.
+fn read_mathml<R: BufRead>(
+    reader: &mut quick_xml::Reader<R>,
+    start: BytesStart,
+) -> Result<String, quick_xml::Error> {
+    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+    let mut mathml = Writer::new(Vec::new());
+    mathml.write_event(Event::Start(start))?;
+    let mut buf = Vec::new();
+    let mut depth = 1;
+    while depth > 0 {
+        buf.clear();
+        let event = reader.read_event_into(&mut buf)?;
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            Event::Eof => return Err(quick_xml::Error::UnexpectedEof(name)),
+            _ => (),
+        }
+        mathml.write_event(event)?;
+    }
+    Ok(String::from_utf8_lossy(&mathml.into_inner()).into_owned())
+}
+
+/// Buffered reader over a gzipped baseline or update file.
+pub type GzFileReader = BufReader<GzDecoder<BufReader<File>>>;
+
+/// Streams every record out of a `PubmedArticleSet` document: articles,
+/// book articles and the `DeleteCitation`/`DeleteDocument` lists found in
+/// update files.
+///
+/// Each record is copied out of the XML stream by `read_article` and then
+/// deserialized on its own, so memory use is bounded by the largest record
+/// rather than by the size of the file. A record that fails to deserialize
+/// is reported as an error and reading continues with the next one. End
+/// tags are only checked when a record is deserialized, so a mismatched tag
+/// costs one record; other malformed XML ends the file.
+pub struct RecordReader<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    record: Vec<u8>,
+    path: Vec<String>,
+    file: Option<Arc<str>>,
+    dtd_version: Option<DtdVersion>,
+    audit: bool,
+    summary: FileSummary,
+    last_error_at: Option<usize>,
+    done: bool,
+}
+
+impl RecordReader<GzFileReader> {
+    /// Opens a `.xml.gz` file such as `pubmed24n0001.xml.gz`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        let path = path.as_ref();
+        let name = path.display().to_string();
+        let file = File::open(path).map_err(|source| ParseError::Io {
+            context: Box::new(ErrorContext {
+                file: Some(name.clone()),
+                ..Default::default()
+            }),
+            source,
+        })?;
+        let decoder = GzDecoder::new(BufReader::new(file));
+        Ok(RecordReader::from_reader(BufReader::new(decoder)).with_file_name(name))
+    }
+}
+
+impl<R: BufRead> RecordReader<R> {
+    /// Reads uncompressed XML from any buffered source.
+    pub fn from_reader(reader: R) -> Self {
+        let mut reader = Reader::from_reader(reader);
+        reader.check_end_names(false);
+        RecordReader {
+            reader,
+            buf: Vec::new(),
+            record: Vec::new(),
+            path: Vec::new(),
+            file: None,
+            dtd_version: None,
+            audit: false,
+            summary: FileSummary::default(),
+            last_error_at: None,
+            done: false,
+        }
+    }
+
+    /// Sets the file name used in error contexts and the summary.
+    pub fn with_file_name<S: Into<String>>(mut self, name: S) -> Self {
+        let name = name.into();
+        self.file = Some(Arc::from(name.as_str()));
+        self.summary.file = Some(name);
+        self
+    }
+
+    /// Records in the summary every element and attribute path that the
+    /// model does not map, see `FileSummary::unmapped`. Decoding is slower
+    /// in this mode.
+    pub fn with_audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Counts of the records and errors read so far.
+    pub fn summary(&self) -> &FileSummary {
+        &self.summary
+    }
+
+    /// The DTD release named by the `DOCTYPE`, once it has been read.
+    pub fn dtd_version(&self) -> Option<DtdVersion> {
+        self.dtd_version
+    }
+
+    /// Bytes of decompressed XML consumed so far.
+    pub fn position(&self) -> u64 {
+        self.reader.buffer_position() as u64
+    }
+
+    fn context(&self, offset: usize) -> Box<ErrorContext> {
+        Box::new(ErrorContext {
+            file: self.file.as_deref().map(String::from),
+            offset: offset as u64,
+            pmid: find_pmid(&self.record),
+            path: self.path.join("/"),
+        })
+    }
+
+    fn xml_error(&mut self, source: quick_xml::Error) -> ParseError {
+        let position = self.reader.buffer_position();
+        let context = self.context(position);
+        // stop when the input itself fails or the reader no longer advances
+        let fatal = matches!(
+            source,
+            quick_xml::Error::Io(_) | quick_xml::Error::UnexpectedEof(_)
+        ) || self.last_error_at == Some(position);
+        self.last_error_at = Some(position);
+        if fatal {
+            self.done = true;
+        }
+        match source {
+            quick_xml::Error::Io(e) => ParseError::Io {
+                context,
+                source: std::io::Error::new(e.kind(), e.to_string()),
+            },
+            source => ParseError::Xml { context, source },
+        }
+    }
+
+    fn scan_record(&mut self, kind: RecordKind, offset: usize) -> Result<RawRecord, ParseError> {
+        self.record.clear();
+        self.path.clear();
+        self.path.push(kind.tag().to_string());
+        if let Err(e) = read_article(
+            &mut self.reader,
+            kind.tag(),
+            &mut self.record,
+            &mut self.path,
+        ) {
+            return Err(self.xml_error(e));
+        }
+        Ok(RawRecord {
+            kind,
+            offset: offset as u64,
+            file: self.file.clone(),
+            xml: std::mem::take(&mut self.record),
+        })
+    }
+
+    /// Reads the next record without deserializing it, so that decoding can
+    /// happen elsewhere. Does not update the summary.
+    pub fn next_raw(&mut self) -> Option<Result<RawRecord, ParseError>> {
+        if self.done {
+            return None;
+        }
+        loop {
+            self.buf.clear();
+            let offset = self.reader.buffer_position();
+            let kind = match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Eof) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok(Event::DocType(doctype)) => {
+                    self.dtd_version = DtdVersion::from_doctype(&String::from_utf8_lossy(&doctype));
+                    self.summary.dtd = self.dtd_version.map(|v| v.to_string());
+                    continue;
+                }
+                Ok(Event::Start(e)) => match RecordKind::from_tag(e.name().as_ref()) {
+                    Some(kind) => kind,
+                    None => continue,
+                },
+                // <DeleteDocument/> carries no PMIDs
+                Ok(Event::Empty(e)) if e.name().as_ref() == b"DeleteDocument" => {
+                    return Some(Ok(RawRecord {
+                        kind: RecordKind::DeleteDocument,
+                        offset: offset as u64,
+                        file: self.file.clone(),
+                        xml: Vec::new(),
+                    }));
+                }
+                Ok(_) => continue,
+                Err(e) => {
+                    self.record.clear();
+                    self.path.clear();
+                    return Some(Err(self.xml_error(e)));
+                }
+            };
+            return Some(self.scan_record(kind, offset));
+        }
+    }
+
+    /// Decodes records on `threads` worker threads while this reader keeps
+    /// scanning the XML.
+    pub fn parallel(self, threads: usize) -> ParallelReader
+    where
+        R: Send + 'static,
+    {
+        ParallelReader::new(self, threads)
+    }
+}
+
+impl<R: BufRead> Iterator for RecordReader<R> {
+    type Item = Result<Record, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.next_raw()? {
+            Ok(raw) if self.audit => {
+                let (record, unmapped) = raw.decode_audited();
+                self.summary.add_unmapped(unmapped, raw.pmid().as_deref());
+                record
+            }
+            raw => raw.and_then(|raw| raw.decode()),
+        };
+        self.summary.add(&record);
+        Some(record)
+    }
+}
+
+/// A record copied out of the XML stream but not deserialized yet.
+///
+/// Scanning the stream has to happen on one thread, while decoding, which
+/// takes most of the time, can run on many; see `ParallelReader`.
+#[derive(Debug, Clone)]
+pub struct RawRecord {
+    kind: RecordKind,
+    offset: u64,
+    file: Option<Arc<str>>,
+    xml: Vec<u8>,
+}
+
+impl RawRecord {
+    pub fn kind(&self) -> RecordKind {
+        self.kind
+    }
+
+    /// Byte offset of the record in the decompressed XML.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The record as standalone XML, with inline markup escaped into text.
+    /// Empty for a `<DeleteDocument/>`.
+    pub fn xml(&self) -> &[u8] {
+        &self.xml
+    }
+
+    /// PMID of the record, the first one for deletions.
+    pub fn pmid(&self) -> Option<String> {
+        find_pmid(&self.xml)
+    }
+
+    pub fn decode(&self) -> Result<Record, ParseError> {
+        self.decode_with(None)
+    }
+
+    /// Decodes the record, also returning the paths of the elements and
+    /// attributes it has that the model does not map, once per occurrence,
+    /// e.g. `PubmedArticle/MedlineCitation/Article/@Lang`.
+    pub fn decode_audited(&self) -> (Result<Record, ParseError>, Vec<String>) {
+        let mut unmapped = Vec::new();
+        let record = self.decode_with(Some(&mut unmapped));
+        (record, unmapped)
+    }
+
+    fn decode_with(&self, unmapped: Option<&mut Vec<String>>) -> Result<Record, ParseError> {
+        if self.xml.is_empty() {
+            return Ok(Record::DeleteDocument(DeleteDocument { pmid: None }));
+        }
+        decode(self.kind, &self.xml, unmapped).map_err(|e| {
+            let mut context = Box::new(ErrorContext {
+                file: self.file.as_deref().map(String::from),
+                offset: self.offset,
+                pmid: find_pmid(&self.xml),
+                path: self.kind.tag().to_string(),
+            });
+            match e {
+                DecodeError::Utf8(source) => ParseError::Utf8 { context, source },
+                DecodeError::Deserialize { path, message } => {
+                    context.path = path;
+                    ParseError::Deserialize { context, message }
+                }
+            }
+        })
+    }
+}
+
+/// Streams only the `PubmedArticle` records of a file, skipping books and
+/// deletions.
+pub struct PubmedReader<R: BufRead> {
+    records: RecordReader<R>,
+}
+
+impl PubmedReader<GzFileReader> {
+    /// Opens a `.xml.gz` file such as `pubmed24n0001.xml.gz`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        Ok(PubmedReader {
+            records: RecordReader::open(path)?,
+        })
+    }
+}
+
+impl<R: BufRead> PubmedReader<R> {
+    /// Reads uncompressed XML from any buffered source.
+    pub fn from_reader(reader: R) -> Self {
+        PubmedReader {
+            records: RecordReader::from_reader(reader),
+        }
+    }
+
+    /// Sets the file name used in error contexts and the summary.
+    pub fn with_file_name<S: Into<String>>(self, name: S) -> Self {
+        PubmedReader {
+            records: self.records.with_file_name(name),
+        }
+    }
+
+    /// Counts of all records and errors read so far, including the records
+    /// that were skipped.
+    pub fn summary(&self) -> &FileSummary {
+        self.records.summary()
+    }
+}
+
+impl<R: BufRead> Iterator for PubmedReader<R> {
+    type Item = Result<PubmedArticle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.records.next()? {
+                Ok(Record::Article(article)) => return Some(Ok(article)),
+                Ok(_) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+enum DecodeError {
+    Utf8(std::str::Utf8Error),
+    Deserialize { path: String, message: String },
+}
+
+fn decode(
+    kind: RecordKind,
+    bytes: &[u8],
+    unmapped: Option<&mut Vec<String>>,
+) -> Result<Record, DecodeError> {
+    let xml = std::str::from_utf8(bytes).map_err(DecodeError::Utf8)?;
+    let tag = kind.tag();
+    let record = match kind {
+        RecordKind::Article => Record::Article(deserialize(tag, xml, unmapped)?),
+        RecordKind::BookArticle => Record::BookArticle(deserialize(tag, xml, unmapped)?),
+        RecordKind::DeleteCitation => Record::DeleteCitation(deserialize(tag, xml, unmapped)?),
+        RecordKind::DeleteDocument => Record::DeleteDocument(deserialize(tag, xml, unmapped)?),
+    };
+    Ok(record)
+}
+
+/// Element path of a key serde ignored: serde drops unknown fields
+/// silently, which is how elements and attributes added to the DTD go
+/// unnoticed.
+fn unmapped_path(tag: &str, path: &serde_ignored::Path) -> String {
+    fn push(path: &serde_ignored::Path, out: &mut String) {
+        match path {
+            serde_ignored::Path::Root => (),
+            serde_ignored::Path::Map { parent, key } => {
+                push(parent, out);
+                out.push('/');
+                out.push_str(key);
+            }
+            serde_ignored::Path::Seq { parent, .. }
+            | serde_ignored::Path::Some { parent }
+            | serde_ignored::Path::NewtypeStruct { parent }
+            | serde_ignored::Path::NewtypeVariant { parent } => push(parent, out),
+        }
+    }
+    let mut out = String::from(tag);
+    push(path, &mut out);
+    out
+}
+
+fn deserialize<T: DeserializeOwned>(
+    tag: &str,
+    xml: &str,
+    unmapped: Option<&mut Vec<String>>,
+) -> Result<T, DecodeError> {
+    let mut deserializer = Deserializer::from_str(xml);
+    let value = match unmapped {
+        Some(unmapped) => serde_ignored::deserialize(&mut deserializer, |path| {
+            unmapped.push(unmapped_path(tag, &path))
+        }),
+        None => T::deserialize(&mut deserializer),
+    };
+    if let Ok(value) = value {
+        return Ok(value);
+    }
+    // tracking the path slows deserialization down, so only failed records
+    // are deserialized a second time to find where they fail
+    let mut deserializer = Deserializer::from_str(xml);
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let mut path = String::from(tag);
+        for segment in e.path().iter() {
+            match segment {
+                Segment::Seq { index } => path.push_str(&format!("[{}]", index)),
+                Segment::Map { key } => {
+                    path.push('/');
+                    path.push_str(key);
+                }
+                Segment::Enum { variant } => {
+                    path.push('/');
+                    path.push_str(variant);
+                }
+                Segment::Unknown => (),
+            }
+        }
+        DecodeError::Deserialize {
+            path,
+            message: e.into_inner().to_string(),
+        }
+    })
+}
+
+/// Text of the first `PMID` element of a (possibly partial) record.
+fn find_pmid(xml: &[u8]) -> Option<String> {
+    let start = find(xml, b"<PMID")?;
+    let text = start + find(&xml[start..], b">")? + 1;
+    let end = text + find(&xml[text..], b"</PMID>")?;
+    std::str::from_utf8(&xml[text..end])
+        .ok()
+        .map(|pmid| pmid.trim().to_string())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use super::{RawRecord, RecordReader};
+use crate::dtd::DtdVersion;
+use crate::error::ParseError;
+use crate::pubmed::Record;
+use crate::report::FileSummary;
+
+/// Records scanned together and handed to one decoder at a time, which keeps
+/// channel traffic low compared to the cost of decoding.
+const BATCH_SIZE: usize = 64;
+
+/// Batches per decoder that may be scanned ahead of the one being yielded.
+const WINDOW: usize = 4;
+
+struct RawBatch {
+    seq: u64,
+    position: u64,
+    dtd: Option<DtdVersion>,
+    records: Vec<Result<RawRecord, ParseError>>,
+}
+
+struct Batch {
+    seq: u64,
+    position: u64,
+    dtd: Option<DtdVersion>,
+    records: Vec<Result<Record, ParseError>>,
+    /// Unmapped paths and PMID of each record, in audit mode.
+    unmapped: Vec<(Vec<String>, Option<String>)>,
+}
+
+/// Reads one file with a scanning thread and a pool of decoder threads.
+///
+/// The scanning thread runs the `RecordReader` tokenizer and sends batches
+/// of `RawRecord`s to the decoders; batches are numbered and put back in
+/// order before they are yielded, so the records come out exactly as from
+/// `RecordReader`. The scanner waits once it is `WINDOW` batches per decoder
+/// ahead of the batch being yielded, so at most that many batches are held
+/// in memory even when one decoder is slow.
+///
+/// ```no_run
+/// use rbiblio::RecordReader;
+///
+/// let reader = RecordReader::open("pubmed24n0001.xml.gz")?.parallel(8);
+/// for record in reader {
+///     println!("{:?}", record?);
+/// }
+/// # Ok::<(), rbiblio::ParseError>(())
+/// ```
+pub struct ParallelReader {
+    results: Option<Receiver<Batch>>,
+    credits: Option<Sender<()>>,
+    pending: BTreeMap<u64, Batch>,
+    next_seq: u64,
+    current: std::vec::IntoIter<Result<Record, ParseError>>,
+    summary: FileSummary,
+    position: u64,
+    dtd: Option<DtdVersion>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ParallelReader {
+    pub fn new<R: BufRead + Send + 'static>(reader: RecordReader<R>, threads: usize) -> Self {
+        let threads = threads.max(1);
+        let summary = FileSummary {
+            file: reader.summary().file.clone(),
+            ..Default::default()
+        };
+        let (raw_tx, raw_rx) = sync_channel(threads * 2);
+        let (results_tx, results_rx) = sync_channel(threads * 2);
+        let (credits_tx, credits_rx) = channel();
+        let raw_rx = Arc::new(Mutex::new(raw_rx));
+        let audit = reader.audit;
+        let window = (threads * WINDOW) as u64;
+        let mut handles = vec![thread::spawn(move || {
+            scan(reader, raw_tx, &credits_rx, window)
+        })];
+        for _ in 0..threads {
+            let raw_rx = Arc::clone(&raw_rx);
+            let results_tx = results_tx.clone();
+            handles.push(thread::spawn(move || decode(&raw_rx, &results_tx, audit)));
+        }
+        ParallelReader {
+            results: Some(results_rx),
+            credits: Some(credits_tx),
+            pending: BTreeMap::new(),
+            next_seq: 0,
+            current: Vec::new().into_iter(),
+            summary,
+            position: 0,
+            dtd: None,
+            threads: handles,
+        }
+    }
+
+    /// Counts of the records and errors yielded so far.
+    pub fn summary(&self) -> &FileSummary {
+        &self.summary
+    }
+
+    /// Bytes of decompressed XML scanned up to the records yielded so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The DTD release named in the `DOCTYPE`, once the first record has
+    /// been yielded, see `RecordReader::dtd_version`.
+    pub fn dtd_version(&self) -> Option<DtdVersion> {
+        self.dtd
+    }
+
+    /// Waits for the threads, passing on a panic so that records are never
+    /// lost silently.
+    fn join(&mut self) {
+        for handle in self.threads.drain(..) {
+            if let Err(panic) = handle.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+/// Scans batches, taking a credit for each one more than `window` ahead of
+/// the first; the reader hands a credit back for each batch it yields.
+fn scan<R: BufRead>(
+    mut reader: RecordReader<R>,
+    raw: SyncSender<RawBatch>,
+    credits: &Receiver<()>,
+    window: u64,
+) {
+    for seq in 0.. {
+        if seq >= window && credits.recv().is_err() {
+            return;
+        }
+        let records: Vec<_> = std::iter::from_fn(|| reader.next_raw())
+            .take(BATCH_SIZE)
+            .collect();
+        if records.is_empty() {
+            return;
+        }
+        let batch = RawBatch {
+            seq,
+            position: reader.position(),
+            dtd: reader.dtd_version(),
+            records,
+        };
+        if raw.send(batch).is_err() {
+            return;
+        }
+    }
+}
+
+fn decode(raw: &Mutex<Receiver<RawBatch>>, results: &SyncSender<Batch>, audit: bool) {
+    loop {
+        let Ok(batch) = raw.lock().unwrap().recv() else {
+            return;
+        };
+        let mut unmapped = Vec::new();
+        let records = batch
+            .records
+            .into_iter()
+            .map(|record| match record {
+                Ok(raw) if audit => {
+                    let (record, paths) = raw.decode_audited();
+                    unmapped.push((paths, raw.pmid()));
+                    record
+                }
+                record => record.and_then(|raw| raw.decode()),
+            })
+            .collect();
+        let batch = Batch {
+            seq: batch.seq,
+            position: batch.position,
+            dtd: batch.dtd,
+            records,
+            unmapped,
+        };
+        if results.send(batch).is_err() {
+            return;
+        }
+    }
+}
+
+impl Iterator for ParallelReader {
+    type Item = Result<Record, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.current.next() {
+                self.summary.add(&record);
+                return Some(record);
+            }
+            if let Some(batch) = self.pending.remove(&self.next_seq) {
+                self.next_seq += 1;
+                if let Some(credits) = &self.credits {
+                    let _ = credits.send(());
+                }
+                self.position = batch.position;
+                self.dtd = batch.dtd;
+                self.summary.dtd = batch.dtd.map(|v| v.to_string());
+                for (paths, pmid) in batch.unmapped {
+                    self.summary.add_unmapped(paths, pmid.as_deref());
+                }
+                self.current = batch.records.into_iter();
+                continue;
+            }
+            match self.results.as_ref()?.recv() {
+                Ok(batch) => {
+                    self.pending.insert(batch.seq, batch);
+                }
+                Err(_) => {
+                    self.results = None;
+                    self.credits = None;
+                    self.join();
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ParallelReader {
+    fn drop(&mut self) {
+        // the decoders stop when they cannot send, and the scanner when no
+        // decoder is left to receive or no credit can come back
+        self.results = None;
+        self.credits = None;
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
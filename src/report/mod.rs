@@ -0,0 +1,161 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::error::{ErrorContext, ParseError};
+use crate::pubmed::Record;
+
+/// Record and error counts for one input file.
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct FileSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// DTD release named by the file's `DOCTYPE`, e.g. `pubmed_250101`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dtd: Option<String>,
+    pub articles: u64,
+    pub book_articles: u64,
+    pub delete_citations: u64,
+    pub delete_documents: u64,
+    /// Errors by `ParseError::kind`.
+    pub errors: BTreeMap<&'static str, u64>,
+    /// Element and attribute paths the model does not map, e.g.
+    /// `PubmedArticle/MedlineCitation/@Lang`, when reading in audit mode.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub unmapped: BTreeMap<String, UnmappedPath>,
+}
+
+/// Number of PMIDs kept as examples for each unmapped path.
+pub const EXAMPLE_PMIDS: usize = 5;
+
+/// How often an unmapped path was seen, and the first records it was seen
+/// in.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct UnmappedPath {
+    pub count: u64,
+    pub pmids: Vec<String>,
+}
+
+impl UnmappedPath {
+    fn add_pmid(&mut self, pmid: &str) {
+        if self.pmids.len() < EXAMPLE_PMIDS && !self.pmids.iter().any(|p| p == pmid) {
+            self.pmids.push(pmid.to_string());
+        }
+    }
+}
+
+impl FileSummary {
+    pub fn add(&mut self, record: &Result<Record, ParseError>) {
+        match record {
+            Ok(Record::Article(_)) => self.articles += 1,
+            Ok(Record::BookArticle(_)) => self.book_articles += 1,
+            Ok(Record::DeleteCitation(_)) => self.delete_citations += 1,
+            Ok(Record::DeleteDocument(_)) => self.delete_documents += 1,
+            Err(e) => *self.errors.entry(e.kind()).or_insert(0) += 1,
+        }
+    }
+
+    /// Counts the unmapped paths of one record.
+    pub fn add_unmapped(&mut self, paths: Vec<String>, pmid: Option<&str>) {
+        for path in paths {
+            let entry = self.unmapped.entry(path).or_default();
+            entry.count += 1;
+            if let Some(pmid) = pmid {
+                entry.add_pmid(pmid);
+            }
+        }
+    }
+
+    /// Adds the counts of another summary, e.g. to total several files.
+    pub fn merge(&mut self, other: &FileSummary) {
+        self.articles += other.articles;
+        self.book_articles += other.book_articles;
+        self.delete_citations += other.delete_citations;
+        self.delete_documents += other.delete_documents;
+        for (kind, count) in &other.errors {
+            *self.errors.entry(kind).or_insert(0) += count;
+        }
+        for (path, unmapped) in &other.unmapped {
+            let entry = self.unmapped.entry(path.clone()).or_default();
+            entry.count += unmapped.count;
+            for pmid in &unmapped.pmids {
+                entry.add_pmid(pmid);
+            }
+        }
+    }
+
+    pub fn records(&self) -> u64 {
+        self.articles + self.book_articles + self.delete_citations + self.delete_documents
+    }
+
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+#[derive(Serialize)]
+struct ReportEntry<'a> {
+    kind: &'static str,
+    #[serde(flatten)]
+    context: &'a ErrorContext,
+    message: String,
+}
+
+/// Writes parse errors as JSON lines:
+///
+/// `{"kind":"deserialize","file":"pubmed24n0001.xml.gz","offset":1234,"pmid":"1","path":"PubmedArticle/MedlineCitation","message":"missing field `Article`"}`
+pub struct ErrorReport<W: Write> {
+    out: W,
+    written: u64,
+}
+
+impl<W: Write> ErrorReport<W> {
+    pub fn new(out: W) -> Self {
+        ErrorReport { out, written: 0 }
+    }
+
+    pub fn write(&mut self, error: &ParseError) -> std::io::Result<()> {
+        let entry = ReportEntry {
+            kind: error.kind(),
+            context: error.context(),
+            message: error.message(),
+        };
+        serde_json::to_writer(&mut self.out, &entry)?;
+        self.out.write_all(b"\n")?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Number of errors written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Receives the record errors of a run, e.g. an `ErrorReport` or a writer
+/// of plain lines.
+pub trait ErrorHandler: Send {
+    fn write(&mut self, error: &ParseError) -> std::io::Result<()>;
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> ErrorHandler for ErrorReport<W> {
+    fn write(&mut self, error: &ParseError) -> std::io::Result<()> {
+        ErrorReport::write(self, error)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        ErrorReport::flush(self)
+    }
+}
@@ -0,0 +1,298 @@
+use std::fs::{read_dir, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use bson::{doc, DateTime, Document};
+use mongodb::options::ReplaceOptions;
+use mongodb::Collection;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::db::{ArticleStore, DbError, WriteSummary};
+use crate::error::ParseError;
+use crate::pubmed::Record;
+use crate::reader::RecordReader;
+use crate::report::ErrorHandler;
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Db(#[from] DbError),
+    #[error("mongodb error: {0}")]
+    Mongo(#[from] mongodb::error::Error),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("{0}: no .md5 file next to it")]
+    MissingChecksum(String),
+    #[error("{file}: md5 is {actual}, expected {expected}")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// A `.xml.gz` file of the local mirror together with its `.md5` companion.
+#[derive(Debug, Clone)]
+pub struct MirrorFile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl MirrorFile {
+    fn md5_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".md5");
+        PathBuf::from(path)
+    }
+
+    /// Checksum published by NLM, e.g. `MD5(pubmed24n0001.xml.gz)= 0123...`.
+    pub fn expected_md5(&self) -> Result<String, SyncError> {
+        let text = match std::fs::read_to_string(self.md5_path()) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(SyncError::MissingChecksum(self.name.clone()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        // `md5sum` writes the hash first, NLM writes it after `=`
+        let hash = match text.split_once('=') {
+            Some((_, hash)) => hash.trim(),
+            None => text.split_whitespace().next().unwrap_or(""),
+        };
+        Ok(hash.to_ascii_lowercase())
+    }
+
+    pub fn actual_md5(&self) -> Result<String, SyncError> {
+        let mut file = File::open(&self.path)?;
+        let mut context = md5::Context::new();
+        let mut buf = vec![0u8; 1 << 16];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            context.consume(&buf[..n]);
+        }
+        Ok(format!("{:x}", context.compute()))
+    }
+
+    /// Returns the checksum after comparing it with the `.md5` file.
+    pub fn verify(&self) -> Result<String, SyncError> {
+        let expected = self.expected_md5()?;
+        let actual = self.actual_md5()?;
+        if expected != actual {
+            return Err(SyncError::ChecksumMismatch {
+                file: self.name.clone(),
+                expected,
+                actual,
+            });
+        }
+        Ok(actual)
+    }
+}
+
+fn list_gz(dir: &Path) -> Result<Vec<MirrorFile>, std::io::Error> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if name.ends_with(".xml.gz") => name.to_string(),
+            _ => continue,
+        };
+        if path.is_file() {
+            files.push(MirrorFile { name, path });
+        }
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+// "pubmed24n0001.xml.gz" -> "pubmed24n"
+fn release_prefix(name: &str) -> &str {
+    name.trim_end_matches(".xml.gz")
+        .trim_end_matches(|c: char| c.is_ascii_digit())
+}
+
+/// Files of a mirror laid out like the NLM FTP site (`baseline/` and
+/// `updatefiles/`) in the order they must be applied: baseline files first,
+/// then update files, each sorted by name.
+///
+/// Update files from an older release than the newest baseline are skipped,
+/// since that baseline already contains them.
+pub fn mirror_files(root: &Path) -> Result<Vec<MirrorFile>, std::io::Error> {
+    let mut files = list_gz(&root.join("baseline"))?;
+    let prefix = files.last().map(|f| release_prefix(&f.name).to_string());
+    let updates = list_gz(&root.join("updatefiles"))?;
+    files.extend(updates.into_iter().filter(|f| {
+        prefix
+            .as_deref()
+            .is_none_or(|p| release_prefix(&f.name) == p)
+    }));
+    Ok(files)
+}
+
+/// What applying one file changed in the store.
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct FileOutcome {
+    pub name: String,
+    pub articles: u64,
+    pub stale: u64,
+    pub deleted: u64,
+    pub skipped: u64,
+    pub errors: u64,
+}
+
+impl FileOutcome {
+    fn add(&mut self, summary: WriteSummary) {
+        self.articles += summary.written;
+        self.stale += summary.stale;
+        self.deleted += summary.deleted;
+    }
+}
+
+/// Applies new mirror files to an `ArticleStore`, recording every applied
+/// file by name and MD5 in a state collection.
+///
+/// Records that fail to deserialize are counted, and passed to the error
+/// handler when one is set; malformed XML aborts the file.
+pub struct Synchronizer {
+    store: ArticleStore,
+    state: Collection<Document>,
+    errors: Option<Box<dyn ErrorHandler>>,
+}
+
+impl Synchronizer {
+    pub fn new(store: ArticleStore, state_collection: &str) -> Synchronizer {
+        let state = store.database().collection(state_collection);
+        Synchronizer {
+            store,
+            state,
+            errors: None,
+        }
+    }
+
+    /// Passes record errors to `errors`, e.g. an `ErrorReport`.
+    pub fn with_error_handler(mut self, errors: impl ErrorHandler + 'static) -> Self {
+        self.errors = Some(Box::new(errors));
+        self
+    }
+
+    pub fn store(&self) -> &ArticleStore {
+        &self.store
+    }
+
+    /// MD5 recorded for a file, if it has been applied.
+    pub async fn applied_md5(&self, name: &str) -> Result<Option<String>, SyncError> {
+        let state = self.state.find_one(doc! { "_id": name }, None).await?;
+        Ok(state.and_then(|s| s.get_str("md5").ok().map(String::from)))
+    }
+
+    /// Applies every file of the mirror that has not been applied with the
+    /// same checksum yet. Stops at the first file that cannot be read, so
+    /// update files are never applied out of order.
+    ///
+    /// Files are compared by their `.md5` file first; only new or changed
+    /// files are hashed.
+    pub async fn run(&mut self, root: &Path) -> Result<Vec<FileOutcome>, SyncError> {
+        let mut outcomes = Vec::new();
+        for file in mirror_files(root)? {
+            let expected = file.expected_md5()?;
+            if self.applied_md5(&file.name).await?.as_deref() == Some(expected.as_str()) {
+                continue;
+            }
+            let md5 = file.verify()?;
+            let outcome = self.apply(&file).await?;
+            self.mark_applied(&outcome, &md5).await?;
+            outcomes.push(outcome);
+        }
+        Ok(outcomes)
+    }
+
+    /// Loads one file: articles are upserted, `DeleteCitation` and
+    /// `DeleteDocument` PMIDs are removed and book articles are skipped.
+    pub async fn apply(&mut self, file: &MirrorFile) -> Result<FileOutcome, SyncError> {
+        let mut outcome = FileOutcome {
+            name: file.name.clone(),
+            ..Default::default()
+        };
+        let reader = RecordReader::open(&file.path)?.with_file_name(file.name.as_str());
+        for record in reader {
+            self.apply_record(record, &mut outcome).await?;
+        }
+        self.finish_file(&mut outcome).await?;
+        Ok(outcome)
+    }
+
+    /// Applies one record of a file, for callers that read files themselves
+    /// such as the parallel pipeline. Call `finish_file` after the last one.
+    pub async fn apply_record(
+        &mut self,
+        record: Result<Record, ParseError>,
+        outcome: &mut FileOutcome,
+    ) -> Result<(), SyncError> {
+        match record {
+            Ok(Record::Article(article)) => {
+                let summary = self.store.upsert(&article).await?;
+                outcome.add(summary);
+            }
+            Ok(Record::DeleteCitation(deletion)) => {
+                let pmids: Vec<u32> = deletion.pmid.iter().filter_map(|p| p.id().ok()).collect();
+                let summary = self.store.delete(&pmids).await?;
+                outcome.add(summary);
+            }
+            Ok(Record::DeleteDocument(deletion)) => {
+                let pmids: Vec<u32> = deletion
+                    .pmid
+                    .iter()
+                    .flatten()
+                    .filter_map(|p| p.id().ok())
+                    .collect();
+                let summary = self.store.delete(&pmids).await?;
+                outcome.add(summary);
+            }
+            Ok(Record::BookArticle(_)) => outcome.skipped += 1,
+            Err(e) if e.is_record_error() => {
+                if let Some(errors) = &mut self.errors {
+                    errors.write(&e)?;
+                }
+                outcome.errors += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        Ok(())
+    }
+
+    /// Writes the pending upserts of a file and flushes the error handler.
+    pub async fn finish_file(&mut self, outcome: &mut FileOutcome) -> Result<(), SyncError> {
+        let summary = self.store.flush().await?;
+        outcome.add(summary);
+        if let Some(errors) = &mut self.errors {
+            errors.flush()?;
+        }
+        Ok(())
+    }
+
+    async fn mark_applied(&self, outcome: &FileOutcome, md5: &str) -> Result<(), SyncError> {
+        let state = doc! {
+            "_id": &outcome.name,
+            "md5": md5,
+            "applied_at": DateTime::now(),
+            "articles": outcome.articles as i64,
+            "stale": outcome.stale as i64,
+            "deleted": outcome.deleted as i64,
+            "skipped": outcome.skipped as i64,
+            "errors": outcome.errors as i64,
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+        self.state
+            .replace_one(doc! { "_id": &outcome.name }, state, options)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+
+use rbiblio::report::EXAMPLE_PMIDS;
+use rbiblio::{FileSummary, RecordReader, UnmappedPath};
+
+fn article(pmid: u32) -> String {
+    format!(
+        r#"<PubmedArticle>
+    <MedlineCitation Status="MEDLINE" Owner="NLM" Reviewed="Y">
+        <PMID Version="1">{}</PMID>
+        <Article PubModel="Print">
+            <Journal>
+                <JournalIssue CitedMedium="Print">
+                    <PubDate><Year>2020</Year></PubDate>
+                </JournalIssue>
+                <Title>Journal</Title>
+            </Journal>
+            <ArticleTitle>Title.</ArticleTitle>
+            <Language>eng</Language>
+            <FundingStatement>Funded.</FundingStatement>
+        </Article>
+        <MedlineJournalInfo>
+            <MedlineTA>Journal</MedlineTA>
+            <NlmUniqueID>0000001</NlmUniqueID>
+        </MedlineJournalInfo>
+    </MedlineCitation>
+</PubmedArticle>"#,
+        pmid
+    )
+}
+
+fn document(pmids: impl Iterator<Item = u32>) -> String {
+    let articles: String = pmids.map(article).collect();
+    format!("<PubmedArticleSet>{}</PubmedArticleSet>", articles)
+}
+
+fn audit(xml: String) -> FileSummary {
+    let mut reader = RecordReader::from_reader(Cursor::new(xml)).with_audit(true);
+    for record in reader.by_ref() {
+        record.unwrap();
+    }
+    reader.summary().clone()
+}
+
+#[test]
+fn unmapped_elements_and_attributes_are_counted() {
+    let summary = audit(document(1..=7));
+
+    let paths: Vec<&str> = summary.unmapped.keys().map(String::as_str).collect();
+    assert_eq!(
+        paths,
+        [
+            "PubmedArticle/MedlineCitation/@Reviewed",
+            "PubmedArticle/MedlineCitation/Article/FundingStatement",
+        ]
+    );
+    let expected = UnmappedPath {
+        count: 7,
+        pmids: ["1", "2", "3", "4", "5"].map(String::from).to_vec(),
+    };
+    assert_eq!(expected.pmids.len(), EXAMPLE_PMIDS);
+    for unmapped in summary.unmapped.values() {
+        assert_eq!(unmapped, &expected);
+    }
+    assert_eq!(summary.articles, 7);
+}
+
+#[test]
+fn unmapped_empty_elements_are_counted() {
+    let xml = document(1..=1).replace(
+        "<Language>eng</Language>",
+        "<NewEmptyFlag Value=\"Y\"/><Language>eng</Language>",
+    );
+    let summary = audit(xml);
+    let paths: Vec<&str> = summary.unmapped.keys().map(String::as_str).collect();
+    assert_eq!(
+        paths,
+        [
+            "PubmedArticle/MedlineCitation/@Reviewed",
+            "PubmedArticle/MedlineCitation/Article/FundingStatement",
+            "PubmedArticle/MedlineCitation/Article/NewEmptyFlag",
+        ]
+    );
+
+    // attributes of empty elements the model maps
+    let xml = document(1..=1).replace(
+        "<Language>eng</Language>",
+        "<Pagination Kind=\"online\"/><Language>eng</Language>",
+    );
+    let summary = audit(xml);
+    assert!(summary
+        .unmapped
+        .contains_key("PubmedArticle/MedlineCitation/Article/Pagination/@Kind"));
+}
+
+#[test]
+fn unmapped_title_attributes_are_counted() {
+    let xml = document(1..=1).replace("<ArticleTitle>", "<ArticleTitle book=\"gene\" Lang=\"en\">");
+    let summary = audit(xml);
+    let paths: Vec<&str> = summary.unmapped.keys().map(String::as_str).collect();
+    assert!(paths.contains(&"PubmedArticle/MedlineCitation/Article/ArticleTitle/@Lang"));
+    assert!(!paths.iter().any(|p| p.ends_with("@book")));
+}
+
+#[test]
+fn unmapped_paths_are_only_recorded_when_auditing() {
+    let mut reader = RecordReader::from_reader(Cursor::new(document(1..=2)));
+    assert_eq!(reader.by_ref().count(), 2);
+    assert!(reader.summary().unmapped.is_empty());
+}
+
+#[test]
+fn merged_summaries_keep_counts_and_example_pmids() {
+    let mut total = audit(document(1..=3));
+    total.merge(&audit(document(3..=6)));
+
+    let unmapped = &total.unmapped["PubmedArticle/MedlineCitation/@Reviewed"];
+    assert_eq!(unmapped.count, 7);
+    assert_eq!(unmapped.pmids, ["1", "2", "3", "4", "5"]);
+}
+
+#[test]
+fn fixtures_map_every_path() {
+    for name in [
+        "pubmed_articles.xml",
+        "pubmed_articles_250101.xml",
+        "pubmed_books.xml",
+        "pubmed_book_authors.xml",
+    ] {
+        let file = File::open(format!("tests/fixtures/{}", name)).unwrap();
+        let mut reader = RecordReader::from_reader(BufReader::new(file)).with_audit(true);
+        assert!(reader.by_ref().all(|record| record.is_ok()));
+        assert!(reader.summary().unmapped.is_empty(), "{}", name);
+    }
+}
+
+#[test]
+fn parallel_reader_audits_like_the_sequential_one() {
+    let xml = document(1..=40);
+    let expected = audit(xml.clone());
+
+    let reader = RecordReader::from_reader(Cursor::new(xml)).with_audit(true);
+    let mut parallel = reader.parallel(3);
+    assert!(parallel.by_ref().all(|record| record.is_ok()));
+    assert_eq!(parallel.summary().unmapped, expected.unmapped);
+}
@@ -0,0 +1,101 @@
+use chrono::NaiveDate;
+
+use rbiblio::pubmed::{PartialDate, Precision, PubDate, PublicationDate};
+
+fn medline(value: &str) -> PublicationDate {
+    PublicationDate::parse_medline_date(value).unwrap()
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn month_ranges_within_a_year() {
+    let date = medline("1998 Mar-Apr");
+    assert_eq!(date.year(), 1998);
+    assert_eq!(date.month(), Some(3));
+    assert_eq!(date.precision(), Precision::Month);
+    assert_eq!(date.end().unwrap().month(), Some(4));
+    assert_eq!(date.to_naive_date(), Some(ymd(1998, 3, 1)));
+    assert_eq!(date.last_day(), Some(ymd(1998, 4, 30)));
+    assert_eq!(date.to_string(), "1998 Mar-Apr");
+}
+
+#[test]
+fn ranges_across_years() {
+    let date = medline("2000 Dec-2001 Jan");
+    assert_eq!(date.start().year(), 2000);
+    assert_eq!(date.end().unwrap().year(), 2001);
+    assert_eq!(date.last_day(), Some(ymd(2001, 1, 31)));
+    assert_eq!(date.to_string(), "2000 Dec-2001 Jan");
+
+    let years = medline("1975-76");
+    assert_eq!(years.end().unwrap().year(), 1976);
+    assert_eq!(years.precision(), Precision::Year);
+    assert_eq!(years.to_string(), "1975-1976");
+}
+
+#[test]
+fn day_and_season_ranges() {
+    let days = medline("1977 Jul 12-19");
+    assert_eq!(days.precision(), Precision::Day);
+    assert_eq!(days.end().unwrap().day(), Some(19));
+    assert_eq!(days.end().unwrap().month(), Some(7));
+    assert_eq!(days.to_string(), "1977 Jul 12-19");
+
+    let seasons = medline("1989 Fall-Winter");
+    assert_eq!(seasons.season(), Some("Fall"));
+    assert_eq!(seasons.precision(), Precision::Season);
+    assert_eq!(seasons.to_naive_date(), Some(ymd(1989, 9, 1)));
+    assert_eq!(seasons.last_day(), Some(ymd(1990, 2, 28)));
+}
+
+#[test]
+fn unusual_medline_dates_fall_back_to_the_year() {
+    let date = medline("2nd Quart 2001");
+    assert_eq!(date.year(), 2001);
+    assert!(!date.is_range());
+    assert_eq!(PublicationDate::parse_medline_date("n.d."), None);
+}
+
+#[test]
+fn dates_from_elements() {
+    let date = PartialDate::from_parts("2016", Some("Jul"), Some("04"), None).unwrap();
+    assert_eq!(date.precision(), Precision::Day);
+    assert_eq!(date.first_day(), Some(ymd(2016, 7, 4)));
+    assert_eq!(date.to_string(), "2016 Jul 4");
+
+    let numeric = PartialDate::from_parts("2016", Some("07"), None, None).unwrap();
+    assert_eq!(numeric.month(), Some(7));
+    assert_eq!(numeric.last_day(), Some(ymd(2016, 7, 31)));
+
+    // an impossible day is dropped rather than making the date invalid
+    let invalid = PartialDate::from_parts("2015", Some("Feb"), Some("30"), None).unwrap();
+    assert_eq!(invalid.precision(), Precision::Month);
+
+    assert_eq!(PartialDate::from_parts("", None, None, None), None);
+}
+
+#[test]
+fn pub_date_elements_are_all_read() {
+    let full: PubDate = quick_xml::de::from_str(
+        "<PubDate><Year>2016</Year><Month>Jul</Month><Day>4</Day></PubDate>",
+    )
+    .unwrap();
+    assert_eq!(full.month(), Some("Jul"));
+    assert_eq!(full.day(), Some("4"));
+    assert_eq!(full.date().unwrap().to_string(), "2016 Jul 4");
+
+    let season: PubDate =
+        quick_xml::de::from_str("<PubDate><Year>2002</Year><Season>Winter</Season></PubDate>")
+            .unwrap();
+    assert_eq!(season.season(), Some("Winter"));
+    assert_eq!(season.date().unwrap().precision(), Precision::Season);
+
+    let medline: PubDate =
+        quick_xml::de::from_str("<PubDate><MedlineDate>1998 Mar-Apr</MedlineDate></PubDate>")
+            .unwrap();
+    assert_eq!(medline.year(), Some(1998));
+    assert_eq!(medline.month(), None);
+}
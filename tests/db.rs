@@ -0,0 +1,103 @@
+//! These tests need a running mongod; run them with
+//! `MONGODB_URI=mongodb://localhost:27017 cargo test -- --ignored`.
+
+use std::fs::File;
+use std::io::BufReader;
+
+use bson::doc;
+use rbiblio::db::{ArticleStore, Config};
+use rbiblio::pubmed::PubmedArticle;
+use rbiblio::PubmedReader;
+
+fn fixture_articles() -> Vec<PubmedArticle> {
+    let file = File::open("tests/fixtures/pubmed_articles.xml").unwrap();
+    PubmedReader::from_reader(BufReader::new(file))
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+async fn store(name: &str) -> ArticleStore {
+    let config = Config {
+        uri: std::env::var("MONGODB_URI").unwrap_or_else(|_| Config::default().uri),
+        database: format!("rbiblio_test_{}", name),
+        collection: String::from("articles"),
+        batch_size: 1,
+    };
+    let store = ArticleStore::connect(&config).await.unwrap();
+    store.database().drop(None).await.unwrap();
+    store
+}
+
+#[tokio::test]
+#[ignore = "requires a local mongod"]
+async fn upserts_are_keyed_by_pmid() {
+    let mut store = store("upsert").await;
+    let articles = fixture_articles();
+    for article in articles.iter().chain(articles.iter()) {
+        store.upsert(article).await.unwrap();
+    }
+    store.flush().await.unwrap();
+
+    let collection = store.collection();
+    assert_eq!(collection.count_documents(None, None).await.unwrap(), 2);
+    let stored = collection
+        .find_one(doc! { "_id": 27150001_i64 }, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.get_str("doi").unwrap(), "10.1093/ndt/gfw079");
+    assert_eq!(stored.get_str("nlm_unique_id").unwrap(), "8706402");
+    assert_eq!(stored.get_array("mesh_ui").unwrap().len(), 2);
+
+    store.database().drop(None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a local mongod"]
+async fn keeps_latest_version_and_honours_deletions() {
+    let mut store = store("versions").await;
+    let articles = fixture_articles();
+    let xml = std::fs::read_to_string("tests/fixtures/pubmed_articles.xml")
+        .unwrap()
+        .replace(
+            r#"<PMID Version="1">27150001"#,
+            r#"<PMID Version="2">27150001"#,
+        );
+    let newer = PubmedReader::from_reader(xml.as_bytes())
+        .next()
+        .unwrap()
+        .unwrap();
+
+    store.upsert(&newer).await.unwrap();
+    store.flush().await.unwrap();
+    store.upsert(&articles[0]).await.unwrap();
+    let summary = store.flush().await.unwrap();
+    assert_eq!(summary.stale, 1);
+
+    let collection = store.collection();
+    let stored = collection
+        .find_one(doc! { "_id": 27150001_i64 }, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.get_i64("version").unwrap(), 2);
+
+    assert_eq!(store.delete(&[27150001]).await.unwrap().deleted, 1);
+    assert_eq!(collection.count_documents(None, None).await.unwrap(), 0);
+
+    store.database().drop(None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a local mongod"]
+async fn creates_lookup_indexes() {
+    let store = store("indexes").await;
+    store.create_indexes().await.unwrap();
+
+    let names = store.collection().list_index_names().await.unwrap();
+    for name in ["_id_", "doi_1", "mesh_ui_1", "nlm_unique_id_1"] {
+        assert!(names.iter().any(|n| n == name), "missing index {}", name);
+    }
+
+    store.database().drop(None).await.unwrap();
+}
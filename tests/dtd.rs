@@ -0,0 +1,282 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use rbiblio::dtd::{AttributeDefault, ContentModel, Dtd, DtdValidator, DtdVersion};
+use rbiblio::export::Format;
+use rbiblio::{ParseError, RecordReader};
+
+fn validate(dtd: &Dtd, xml: &[u8]) -> Vec<ParseError> {
+    DtdValidator::from_reader(dtd, xml).collect()
+}
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(format!("tests/fixtures/{}", name)).unwrap()
+}
+
+/// PMID, path and message of each violation.
+fn found(errors: &[ParseError]) -> Vec<(Option<&str>, &str, String)> {
+    errors
+        .iter()
+        .map(|e| {
+            assert_eq!(e.kind(), "dtd", "{}", e);
+            let context = e.context();
+            (context.pmid.as_deref(), context.path.as_str(), e.message())
+        })
+        .collect()
+}
+
+#[test]
+fn bundled_dtd_declarations() {
+    let dtd = Dtd::pubmed_190101();
+    assert_eq!(
+        dtd.element("PubDate").unwrap().to_string(),
+        "((Year, ((Month, Day?) | Season)?) | MedlineDate)"
+    );
+    assert_eq!(
+        dtd.element("AbstractText"),
+        Some(&ContentModel::Mixed(
+            ["b", "i", "sup", "sub", "u", "mml:math", "DispFormula"]
+                .map(String::from)
+                .to_vec()
+        ))
+    );
+    assert_eq!(dtd.element("Year"), Some(&ContentModel::Mixed(Vec::new())));
+
+    let ref_type = dtd.attribute("CommentsCorrections", "RefType").unwrap();
+    assert_eq!(ref_type.default, AttributeDefault::Required);
+    assert!(ref_type
+        .values
+        .as_ref()
+        .unwrap()
+        .contains(&"ErratumIn".to_string()));
+    let date_type = dtd.attribute("ArticleDate", "DateType").unwrap();
+    assert_eq!(
+        date_type.default,
+        AttributeDefault::Fixed("Electronic".into())
+    );
+    // entity-declared attributes are expanded
+    assert!(dtd.attribute("ArticleTitle", "book").is_some());
+}
+
+#[test]
+fn fixture_violations() {
+    let dtd = Dtd::pubmed_190101();
+    assert!(validate(&dtd, &fixture("pubmed_books.xml")).is_empty());
+    // the second article has no MedlineTA
+    assert_eq!(
+        found(&validate(&dtd, &fixture("pubmed_articles.xml"))),
+        [(
+            Some("10000002"),
+            "PubmedArticle/MedlineCitation/MedlineJournalInfo",
+            "content (NlmUniqueID) does not match (Country?, MedlineTA, NlmUniqueID?, ISSNLinking?)"
+                .to_string()
+        )]
+    );
+}
+
+#[test]
+fn invalid_records() {
+    let xml = r#"<?xml version="1.0"?>
+<PubmedArticleSet>
+  <PubmedArticle>
+    <MedlineCitation Status="Bogus" Owner="NLM">
+      <PMID Version="1">123</PMID>
+      <Article PubModel="Print">
+        <Journal>
+          <JournalIssue CitedMedium="Print"><PubDate><Year>2020</Year></PubDate></JournalIssue>
+        </Journal>
+        <Pagination><MedlinePgn>1-2</MedlinePgn></Pagination>
+        <Language>eng</Language>
+        <PublicationTypeList><PublicationType UI="D1">Journal Article</PublicationType></PublicationTypeList>
+      </Article>
+      <MedlineJournalInfo><MedlineTA>J</MedlineTA></MedlineJournalInfo>
+      <CommentsCorrectionsList>
+        <CommentsCorrections RefType="MentionedIn"><RefSource>X</RefSource></CommentsCorrections>
+        <CommentsCorrections><RefSource>Y</RefSource></CommentsCorrections>
+      </CommentsCorrectionsList>
+      <Unknown/>
+    </MedlineCitation>
+    <PubmedData>
+      <PublicationStatus>ppublish</PublicationStatus>
+      <ArticleIdList><ArticleId IdType="isbn">1</ArticleId></ArticleIdList>
+    </PubmedData>
+  </PubmedArticle>
+  <DeleteCitation><PMID Version="1">9</PMID><Note>x</Note></DeleteCitation>
+</PubmedArticleSet>"#;
+    let dtd = Dtd::pubmed_190101();
+    let errors = validate(&dtd, xml.as_bytes());
+    let mut found = found(&errors);
+    found.sort();
+    let article = Some("123");
+    assert_eq!(
+        found,
+        [
+            (
+                article,
+                "PubmedArticle/MedlineCitation",
+                "attribute Status is \"Bogus\", expected one of: Completed, In-Process, \
+                 PubMed-not-MEDLINE, In-Data-Review, Publisher, MEDLINE, OLDMEDLINE"
+                    .to_string()
+            ),
+            (
+                article,
+                "PubmedArticle/MedlineCitation",
+                "content (PMID, Article, MedlineJournalInfo, CommentsCorrectionsList, Unknown) \
+                 does not match (PMID, DateCompleted?, DateRevised?, Article, MedlineJournalInfo, \
+                 ChemicalList?, SupplMeshList?, CitationSubset*, CommentsCorrectionsList?, \
+                 GeneSymbolList?, MeshHeadingList?, NumberOfReferences?, PersonalNameSubjectList?, \
+                 OtherID*, OtherAbstract*, KeywordList*, CoiStatement?, SpaceFlightMission*, \
+                 InvestigatorList?, GeneralNote*)"
+                    .to_string()
+            ),
+            (
+                article,
+                "PubmedArticle/MedlineCitation/Article",
+                "content (Journal, Pagination, Language, PublicationTypeList) does not match \
+                 (Journal, ArticleTitle, ((Pagination, ELocationID*) | ELocationID+), Abstract?, \
+                 AuthorList?, Language+, DataBankList?, GrantList?, PublicationTypeList, \
+                 VernacularTitle?, ArticleDate*)"
+                    .to_string()
+            ),
+            (
+                article,
+                "PubmedArticle/MedlineCitation/CommentsCorrectionsList/CommentsCorrections",
+                "attribute RefType is \"MentionedIn\", expected one of: AssociatedDataset, \
+                 AssociatedPublication, CommentIn, CommentOn, CorrectedandRepublishedIn, \
+                 CorrectedandRepublishedFrom, ErratumIn, ErratumFor, ExpressionOfConcernIn, \
+                 ExpressionOfConcernFor, RepublishedIn, RepublishedFrom, RetractedandRepublishedIn, \
+                 RetractedandRepublishedFrom, RetractionIn, RetractionOf, UpdateIn, UpdateOf, \
+                 SummaryForPatientsIn, OriginalReportIn, ReprintIn, ReprintOf, Cites"
+                    .to_string()
+            ),
+            (
+                article,
+                "PubmedArticle/MedlineCitation/CommentsCorrectionsList/CommentsCorrections",
+                "missing required attribute RefType".to_string()
+            ),
+            (
+                article,
+                "PubmedArticle/MedlineCitation/Unknown",
+                "element Unknown is not declared".to_string()
+            ),
+            (
+                article,
+                "PubmedArticle/PubmedData/ArticleIdList/ArticleId",
+                "attribute IdType is \"isbn\", expected one of: doi, pii, pmcpid, pmpid, pmc, \
+                 mid, sici, pubmed, medline, pmcid, pmcbook, bookaccession"
+                    .to_string()
+            ),
+            (
+                Some("9"),
+                "DeleteCitation",
+                "content (PMID, Note) does not match (PMID+)".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn xml_export_follows_the_dtd() {
+    let mut xml = Vec::new();
+    {
+        let mut writer = Format::Xml.writer(&mut xml).unwrap();
+        for name in ["pubmed_articles.xml", "pubmed_books.xml"] {
+            let file = File::open(format!("tests/fixtures/{}", name)).unwrap();
+            for record in RecordReader::from_reader(BufReader::new(file)) {
+                writer.write_record(&record.unwrap()).unwrap();
+            }
+        }
+        // a DeleteDocument belongs to a BookDocumentSet and is left out
+        let deletion = "<DeleteDocument><PMID Version=\"1\">20301296</PMID></DeleteDocument>";
+        let record = RecordReader::from_reader(deletion.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap();
+        let error = writer.write_record(&record).unwrap_err();
+        assert!(error.is_record_error());
+        assert_eq!(
+            error.to_string(),
+            "DeleteDocument of PMIDs 20301296 has no place in xml output"
+        );
+        writer.finish().unwrap();
+    }
+    assert!(String::from_utf8_lossy(&xml).contains("<DeleteCitation>"));
+    assert!(!String::from_utf8_lossy(&xml).contains("DeleteDocument"));
+    let dtd = Dtd::pubmed_190101();
+    let errors = validate(&dtd, &xml);
+    // only the violation carried over from the input
+    assert_eq!(found(&errors).len(), 1, "{:#?}", found(&errors));
+    assert_eq!(errors[0].context().pmid.as_deref(), Some("10000002"));
+}
+
+#[test]
+fn doctype_versions() {
+    assert_eq!(
+        DtdVersion::from_doctype(
+            r#"PubmedArticleSet PUBLIC "-//NLM//DTD PubMedArticle, 1st January 2025//EN" "https://dtd.nlm.nih.gov/ncbi/pubmed/out/pubmed_250101.dtd""#
+        ),
+        Some(DtdVersion::PUBMED_250101)
+    );
+    assert_eq!(
+        DtdVersion::from_doctype(
+            r#"PubmedArticleSet PUBLIC "-//NLM//DTD PubMedArticle, 1st June 2018//EN" "x.dtd""#
+        ),
+        Some(DtdVersion::new(2018, 6, 1))
+    );
+    assert_eq!(DtdVersion::from_doctype("PubmedArticleSet"), None);
+    assert_eq!(
+        DtdVersion::PUBMED_250101.public_id(),
+        "-//NLM//DTD PubMedArticle, 1st January 2025//EN"
+    );
+    assert_eq!(
+        DtdVersion::new(2023, 3, 22).public_id(),
+        "-//NLM//DTD PubMedArticle, 22nd March 2023//EN"
+    );
+    assert_eq!(
+        DtdVersion::PUBMED_250101.system_id(),
+        "https://dtd.nlm.nih.gov/ncbi/pubmed/out/pubmed_250101.dtd"
+    );
+    for day in [11, 12, 13, 21, 23, 31] {
+        let version = DtdVersion::new(2024, 12, day);
+        let public = format!("PubmedArticleSet PUBLIC \"{}\"", version.public_id());
+        assert_eq!(DtdVersion::from_doctype(&public), Some(version));
+    }
+    assert_eq!(DtdVersion::PUBMED_190101.to_string(), "pubmed_190101");
+    assert!(DtdVersion::PUBMED_190101 < DtdVersion::PUBMED_250101);
+    for &version in DtdVersion::BUNDLED {
+        assert!(Dtd::bundled(version).is_some(), "{}", version);
+    }
+    assert!(Dtd::bundled(DtdVersion::new(2024, 1, 1)).is_none());
+
+    for (name, version) in [
+        ("pubmed_articles.xml", DtdVersion::PUBMED_190101),
+        ("pubmed_articles_250101.xml", DtdVersion::PUBMED_250101),
+    ] {
+        let xml = fixture(name);
+        let mut reader = RecordReader::from_reader(&xml[..]);
+        assert_eq!(reader.dtd_version(), None);
+        assert!(reader.by_ref().all(|r| r.is_ok()));
+        assert_eq!(reader.dtd_version(), Some(version));
+        let dtd = Dtd::pubmed_190101();
+        let mut validator = DtdValidator::from_reader(&dtd, &xml[..]);
+        validator.by_ref().for_each(drop);
+        assert_eq!(validator.version(), Some(version));
+
+        let mut parallel = RecordReader::from_reader(std::io::Cursor::new(xml)).parallel(2);
+        assert!(parallel.by_ref().all(|r| r.is_ok()));
+        assert_eq!(parallel.summary().dtd, Some(version.to_string()));
+    }
+}
+
+#[test]
+fn current_records_conform_to_the_bundled_dtd() {
+    let dtd = Dtd::bundled(DtdVersion::PUBMED_250101).unwrap();
+    let errors = validate(&dtd, &fixture("pubmed_articles_250101.xml"));
+    assert!(errors.is_empty(), "{:#?}", found(&errors));
+
+    // elements of current files that the 2019 DTD already declares, such as
+    // ORCID identifiers and StartPage, are checked as before
+    let dtd = Dtd::pubmed_190101();
+    let errors = validate(&dtd, &fixture("pubmed_articles_250101.xml"));
+    assert!(errors.is_empty(), "{:#?}", found(&errors));
+}
@@ -0,0 +1,820 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use arrow_array::{Array, RecordBatch, StringArray, UInt32Array};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rbiblio::dtd::DtdVersion;
+use rbiblio::export::delimited::{Column, DelimitedWriter};
+use rbiblio::export::fields::FieldPath;
+use rbiblio::export::ndjson::{json_schema, JsonAuthor, JsonRecord, SCHEMA_VERSION};
+use rbiblio::export::parquet::{ParquetWriter, TABLES};
+use rbiblio::export::xml::XmlWriter;
+use rbiblio::export::{Format, RecordWriter};
+use rbiblio::RecordReader;
+
+/// Exports the records of all fixtures through one writer.
+fn export(format: Format, fixtures: &[&str]) -> String {
+    let mut out = Vec::new();
+    {
+        let mut writer = format.writer(&mut out).unwrap();
+        for fixture in fixtures {
+            let file = File::open(format!("tests/fixtures/{}", fixture)).unwrap();
+            for record in RecordReader::from_reader(BufReader::new(file)) {
+                writer.write_record(&record.unwrap()).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+    }
+    String::from_utf8(out).unwrap()
+}
+
+/// Exports the records of one XML document.
+fn export_xml(format: Format, xml: &str) -> String {
+    let mut out = Vec::new();
+    {
+        let mut writer = format.writer(&mut out).unwrap();
+        for record in RecordReader::from_reader(xml.as_bytes()) {
+            writer.write_record(&record.unwrap()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    String::from_utf8(out).unwrap()
+}
+
+fn tags<'a>(record: &'a str, tag: &str) -> Vec<&'a str> {
+    let prefix = format!("{}  - ", tag);
+    record
+        .lines()
+        .filter_map(|line| line.strip_prefix(prefix.as_str()))
+        .collect()
+}
+
+#[test]
+fn ris_articles() {
+    let ris = export(Format::Ris, &["pubmed_articles.xml"]);
+    let records: Vec<&str> = ris.split_terminator("ER  - \r\n\r\n").collect();
+    assert_eq!(records.len(), 2);
+    assert!(ris.split_terminator('\n').all(|line| line.ends_with('\r')));
+    assert!(ris.ends_with("\r\n"));
+
+    let first = records[0];
+    assert!(first.starts_with("TY  - JOUR\r\n"));
+    assert_eq!(
+        tags(first, "TI"),
+        ["Effect of CYP3A5 genotype on tacrolimus levels in H2O-restricted patients."]
+    );
+    assert_eq!(
+        tags(first, "AU"),
+        [
+            "Müller, Anna Maria",
+            "Smith, John",
+            "Transplant Study Group"
+        ]
+    );
+    assert_eq!(tags(first, "PY"), ["2016"]);
+    assert_eq!(tags(first, "DA"), ["2016/07/04/"]);
+    assert_eq!(tags(first, "VL"), ["31"]);
+    assert_eq!(tags(first, "IS"), ["7"]);
+    assert_eq!(tags(first, "SP"), ["1170"]);
+    assert_eq!(tags(first, "EP"), ["1177"]);
+    assert_eq!(tags(first, "DO"), ["10.1093/ndt/gfw079"]);
+    assert_eq!(tags(first, "AN"), ["27150001"]);
+    assert_eq!(tags(first, "C2"), ["PMC4900001"]);
+    assert_eq!(tags(first, "J2"), ["Nephrol. Dial. Transplant."]);
+    let keywords = tags(first, "KW");
+    assert!(keywords.contains(&"CYP3A5"));
+    assert!(keywords.contains(&"Kidney Transplantation/methods"));
+    assert!(tags(first, "AB")[0].starts_with("BACKGROUND: Tacrolimus levels"));
+
+    let second = records[1];
+    assert_eq!(tags(second, "DA"), ["1998/03//"]);
+    assert_eq!(tags(second, "TI"), ["Solving x^2 < 1 quickly."]);
+
+    // StartPage and EndPage without MedlinePgn
+    let ris = export(Format::Ris, &["pubmed_articles_250101.xml"]);
+    let records: Vec<&str> = ris.split_terminator("ER  - \r\n\r\n").collect();
+    assert_eq!(tags(records[1], "SP"), ["101"]);
+    assert_eq!(tags(records[1], "EP"), ["110"]);
+}
+
+#[test]
+fn ris_books() {
+    let ris = export(Format::Ris, &["pubmed_books.xml"]);
+    assert_eq!(ris.matches("ER  - ").count(), 1);
+    assert!(ris.starts_with("TY  - CHAP\r\n"));
+    assert_eq!(tags(&ris, "TI"), ["CFTR-Related Disorders"]);
+    assert_eq!(tags(&ris, "T2"), ["GeneReviews®"]);
+    assert_eq!(tags(&ris, "AU"), ["Ong, Thida"]);
+    assert_eq!(tags(&ris, "A2"), ["Adam, Margaret P"]);
+    assert_eq!(tags(&ris, "PB"), ["University of Washington, Seattle"]);
+    assert_eq!(tags(&ris, "CY"), ["Seattle (WA)"]);
+    assert_eq!(tags(&ris, "PY"), ["1993"]);
+    assert_eq!(tags(&ris, "AN"), ["20301295"]);
+
+    let whole_book = std::fs::read_to_string("tests/fixtures/pubmed_books.xml")
+        .unwrap()
+        .replace(
+            "<ArticleTitle book=\"gene\" part=\"cf\">CFTR-Related Disorders</ArticleTitle>",
+            "",
+        );
+    let ris = export_xml(Format::Ris, &whole_book);
+    assert!(ris.starts_with("TY  - BOOK\r\n"));
+    assert_eq!(tags(&ris, "TI"), ["GeneReviews®"]);
+    assert!(tags(&ris, "T2").is_empty());
+
+    // the authors of the book are not authors of its chapter
+    let ris = export(Format::Ris, &["pubmed_book_authors.xml"]);
+    assert!(ris.starts_with("TY  - CHAP\r\n"));
+    assert_eq!(tags(&ris, "AU"), ["Ong, Thida"]);
+    assert_eq!(tags(&ris, "A2"), ["Redact, Eddie"]);
+
+    let whole_book = std::fs::read_to_string("tests/fixtures/pubmed_book_authors.xml")
+        .unwrap()
+        .replace(
+            "<ArticleTitle book=\"physio\" part=\"ch3\">Renal Clearance</ArticleTitle>",
+            "",
+        );
+    let ris = export_xml(Format::Ris, &whole_book);
+    assert!(ris.starts_with("TY  - BOOK\r\n"));
+    assert_eq!(tags(&ris, "AU"), ["Ong, Thida", "Bookwriter, Bea"]);
+}
+
+#[test]
+fn unknown_formats_are_rejected() {
+    let error = "endnote".parse::<Format>().unwrap_err();
+    assert!(error.contains(
+        "expected one of: json, ndjson, ris, bibtex, biblatex, csl-json, medline, parquet, xml, csv, tsv"
+    ));
+}
+
+#[test]
+fn bibtex_entries() {
+    let bib = export(Format::Bibtex, &["pubmed_articles.xml", "pubmed_books.xml"]);
+    assert!(bib.starts_with("@article{muller2016effect,\n"));
+    assert!(bib.contains(
+        "  title = {Effect of \\textit{{CYP3A5}} genotype on tacrolimus levels in {H}\\textsubscript{2}{O-restricted} patients.},\n"
+    ));
+    assert!(bib.contains(
+        "  author = {Müller, Anna Maria and Smith, John and {Transplant Study Group}},\n"
+    ));
+    assert!(bib.contains("  month = jul,\n"));
+    assert!(bib.contains("  pages = {1170--1177},\n"));
+    assert!(!bib.contains("doi = "));
+
+    // no authors, and the formula in math mode
+    assert!(bib.contains("@article{anon1998solving,\n  title = {Solving $x^{2}<1$ quickly.},\n"));
+
+    assert!(bib.contains("@incollection{ong1993cftr,\n"));
+    assert!(bib.contains("  booktitle = {{GeneReviews}\\textsuperscript{®}},\n"));
+    assert!(bib.contains("  editor = {Adam, Margaret P},\n"));
+    assert!(bib.contains("  address = {Seattle (WA)},\n"));
+}
+
+#[test]
+fn bibtex_keys_are_disambiguated() {
+    let bib = export(Format::Bibtex, &["pubmed_articles.xml"; 3]);
+    let keys: Vec<&str> = bib
+        .lines()
+        .filter_map(|l| l.strip_prefix("@article{"))
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "muller2016effect,",
+            "anon1998solving,",
+            "muller2016effecta,",
+            "anon1998solvinga,",
+            "muller2016effectb,",
+            "anon1998solvingb,"
+        ]
+    );
+}
+
+#[test]
+fn biblatex_fields() {
+    let bib = export(
+        Format::Biblatex,
+        &["pubmed_articles.xml", "pubmed_books.xml"],
+    );
+    assert!(bib.contains("  journaltitle = {Nephrology, dialysis"));
+    assert!(bib.contains("  shortjournal = {Nephrol. Dial. Transplant.},\n"));
+    assert!(bib.contains("  date = {2016-07-04},\n"));
+    assert!(bib.contains("  date = {1998-03/1998-04},\n"));
+    assert!(bib.contains("  doi = {10.1093/ndt/gfw079},\n"));
+    assert!(bib.contains("  eprint = {27150001},\n  eprinttype = {pubmed},\n"));
+    assert!(bib.contains("  pmcid = {PMC4900001},\n"));
+    assert!(bib.contains("  location = {Seattle (WA)},\n"));
+    assert!(!bib.contains("month = "));
+
+    let bib = export(Format::Biblatex, &["pubmed_book_authors.xml"]);
+    assert!(bib.starts_with("@incollection{ong2011renal,\n"));
+    assert!(bib.contains("  author = {Ong, Thida},\n"));
+    assert!(bib.contains("  editor = {Redact, Eddie},\n"));
+    assert!(bib.contains("  bookauthor = {Bookwriter, Bea},\n"));
+    let bib = export(Format::Bibtex, &["pubmed_book_authors.xml"]);
+    assert!(bib.contains("  author = {Ong, Thida},\n"));
+    assert!(!bib.contains("Bookwriter"));
+}
+
+#[test]
+fn csl_json_items() {
+    let csl = export(Format::Csl, &["pubmed_articles.xml", "pubmed_books.xml"]);
+    let items: serde_json::Value = serde_json::from_str(&csl).unwrap();
+    let items = items.as_array().unwrap();
+    assert_eq!(items.len(), 3);
+
+    let article = &items[0];
+    assert_eq!(article["id"], "27150001");
+    assert_eq!(article["type"], "article-journal");
+    assert_eq!(
+        article["title"],
+        "Effect of <i>CYP3A5</i> genotype on tacrolimus levels in H<sub>2</sub>O-restricted patients."
+    );
+    assert_eq!(
+        article["container-title-short"],
+        "Nephrol. Dial. Transplant."
+    );
+    assert_eq!(article["author"][0]["family"], "Müller");
+    assert_eq!(article["author"][0]["given"], "Anna Maria");
+    assert_eq!(article["author"][2]["literal"], "Transplant Study Group");
+    assert_eq!(
+        article["issued"]["date-parts"],
+        serde_json::json!([[2016, 7, 4]])
+    );
+    assert_eq!(article["page"], "1170-1177");
+    assert_eq!(article["DOI"], "10.1093/ndt/gfw079");
+    assert_eq!(article["PMID"], "27150001");
+    assert_eq!(article["PMCID"], "PMC4900001");
+
+    let range = &items[1];
+    assert_eq!(
+        range["issued"]["date-parts"],
+        serde_json::json!([[1998, 3], [1998, 4]])
+    );
+    assert!(range.get("PMCID").is_none());
+
+    let chapter = &items[2];
+    assert_eq!(chapter["type"], "chapter");
+    assert_eq!(chapter["container-title"], "GeneReviews<sup>®</sup>");
+    assert_eq!(chapter["editor"][0]["family"], "Adam");
+    assert_eq!(chapter["publisher-place"], "Seattle (WA)");
+    assert!(chapter.get("container-author").is_none());
+
+    let csl = export(Format::Csl, &["pubmed_book_authors.xml"]);
+    let items: serde_json::Value = serde_json::from_str(&csl).unwrap();
+    let chapter = &items[0];
+    assert_eq!(chapter["type"], "chapter");
+    assert_eq!(chapter["author"].as_array().unwrap().len(), 1);
+    assert_eq!(chapter["author"][0]["family"], "Ong");
+    assert_eq!(chapter["container-author"][0]["family"], "Bookwriter");
+    assert_eq!(chapter["editor"][0]["family"], "Redact");
+
+    let empty = export(Format::Csl, &[]);
+    assert_eq!(empty, "[]\n");
+}
+
+#[test]
+fn medline_records() {
+    let medline = export(
+        Format::Medline,
+        &["pubmed_articles.xml", "pubmed_books.xml"],
+    );
+    let records: Vec<&str> = medline.split_terminator("\n\n").collect();
+    assert_eq!(records.len(), 3);
+    assert!(medline.lines().all(|line| line.chars().count() <= 82));
+
+    let first = records[0];
+    assert!(first.starts_with("PMID- 27150001\nOWN - NLM\nSTAT- MEDLINE\n"));
+    assert!(first.contains("\nIS  - 0931-0509 (Linking)\n"));
+    assert!(first.contains("\nFAU - Müller, Anna Maria\nAU  - Müller AM\nAD  - Department"));
+    assert!(first.contains("\nCN  - Transplant Study Group\n"));
+    assert!(first.contains("\nMH  - Humans\nMH  - *Kidney Transplantation/methods\n"));
+    assert!(first.contains("\nPT  - Journal Article\n"));
+    assert!(first.contains("\nEDAT- 2016/05/06 06:00\n"));
+    assert!(
+        first.contains("\nPHST- 2015/10/12 00:00 [received]\nPHST- 2016/05/06 06:00 [pubmed]\n")
+    );
+    assert!(first.contains("\nAID - 10.1093/ndt/gfw079 [doi]\n"));
+    assert!(first.contains(
+        "\nJT  - Nephrology, dialysis, transplantation : official publication of the European\n      Dialysis"
+    ));
+    assert!(first.ends_with(
+        "SO  - Nephrol Dial Transplant. 2016 Jul 4;31(7):1170-7. doi: 10.1093/ndt/gfw079."
+    ));
+
+    assert!(records[1].contains("\nDP  - 1998 Mar-Apr\n"));
+    assert!(records[1].ends_with("SO  - Journal of formulas. 1998 Mar-Apr;12:45-50."));
+
+    let book = records[2];
+    assert!(book.starts_with("PMID- 20301295\n"));
+    assert!(book.contains("\nTI  - CFTR-Related Disorders\nBTI - GeneReviews®\n"));
+    assert!(book.contains("\nFED - Adam, Margaret P\nED  - Adam MP\n"));
+    assert!(book.contains("\nLID - NBK1116 [bookaccession]\n"));
+
+    let chapter = export(Format::Medline, &["pubmed_book_authors.xml"]);
+    assert!(chapter.contains("\nFAU - Ong, Thida\nAU  - Ong T\nFED - Redact, Eddie\n"));
+    assert_eq!(tags(&chapter, "AU"), ["Ong T"]);
+
+    assert_eq!("nbib".parse::<Format>().unwrap(), Format::Medline);
+}
+
+/// Row group count and rows of a table written by the Parquet writer.
+fn parquet_table(dir: &Path, table: &str) -> (usize, RecordBatch) {
+    let file = File::open(dir.join(format!("{}.parquet", table))).unwrap();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    let row_groups = builder.metadata().num_row_groups();
+    let mut batches = builder.build().unwrap();
+    let batch = batches.next().unwrap().unwrap();
+    assert!(batches.next().is_none());
+    (row_groups, batch)
+}
+
+fn strings(batch: &RecordBatch, column: &str) -> Vec<Option<String>> {
+    let array = batch.column_by_name(column).unwrap();
+    let array = array.as_any().downcast_ref::<StringArray>().unwrap();
+    array.iter().map(|v| v.map(str::to_string)).collect()
+}
+
+fn numbers(batch: &RecordBatch, column: &str) -> Vec<Option<u32>> {
+    let array = batch.column_by_name(column).unwrap();
+    let array = array.as_any().downcast_ref::<UInt32Array>().unwrap();
+    array.iter().collect()
+}
+
+#[test]
+fn parquet_tables() {
+    let dir = std::env::temp_dir().join("rbiblio_export_parquet");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    {
+        let open = |table: &str| File::create(dir.join(format!("{}.parquet", table)));
+        let mut writer = ParquetWriter::new(open, 2).unwrap();
+        for fixture in [
+            "pubmed_articles.xml",
+            "pubmed_books.xml",
+            "pubmed_book_authors.xml",
+        ] {
+            let file = File::open(format!("tests/fixtures/{}", fixture)).unwrap();
+            for record in RecordReader::from_reader(BufReader::new(file)) {
+                writer.write_record(&record.unwrap()).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+    }
+    for table in TABLES {
+        assert!(dir.join(format!("{}.parquet", table)).is_file());
+    }
+
+    let (row_groups, articles) = parquet_table(&dir, "articles");
+    assert_eq!(row_groups, 2);
+    assert_eq!(
+        numbers(&articles, "pmid"),
+        [
+            Some(27150001),
+            Some(10000002),
+            Some(20301295),
+            Some(21250001)
+        ]
+    );
+    assert_eq!(
+        strings(&articles, "record_type"),
+        [
+            Some("article".into()),
+            Some("article".into()),
+            Some("book".into()),
+            Some("book".into())
+        ]
+    );
+    assert_eq!(
+        strings(&articles, "pub_date_text")[1].as_deref(),
+        Some("1998 Mar-Apr")
+    );
+    assert_eq!(
+        strings(&articles, "doi")[0].as_deref(),
+        Some("10.1093/ndt/gfw079")
+    );
+    assert_eq!(
+        strings(&articles, "book_title")[2].as_deref(),
+        Some("GeneReviews®")
+    );
+
+    let (_, authors) = parquet_table(&dir, "authors");
+    assert_eq!(
+        strings(&authors, "last_name"),
+        [
+            Some("Müller".into()),
+            Some("Smith".into()),
+            None,
+            Some("Ong".into()),
+            Some("Adam".into()),
+            Some("Ong".into()),
+            Some("Bookwriter".into()),
+            Some("Redact".into())
+        ]
+    );
+    assert_eq!(
+        strings(&authors, "role")[3..],
+        [
+            Some("author".into()),
+            Some("editor".into()),
+            Some("author".into()),
+            Some("author".into()),
+            Some("editor".into())
+        ]
+    );
+    assert_eq!(
+        strings(&authors, "level")[3..],
+        [
+            Some("document".into()),
+            Some("book".into()),
+            Some("document".into()),
+            Some("book".into()),
+            Some("book".into())
+        ]
+    );
+    assert_eq!(
+        strings(&authors, "collective_name")[2].as_deref(),
+        Some("Transplant Study Group")
+    );
+
+    let (_, affiliations) = parquet_table(&dir, "affiliations");
+    assert_eq!(numbers(&affiliations, "pmid")[0], Some(27150001));
+    assert_eq!(numbers(&affiliations, "author_position")[0], Some(1));
+
+    let (_, mesh) = parquet_table(&dir, "mesh");
+    assert_eq!(
+        strings(&mesh, "descriptor"),
+        [Some("Humans".into()), Some("Kidney Transplantation".into())]
+    );
+    assert_eq!(strings(&mesh, "qualifier"), [None, Some("methods".into())]);
+
+    let (_, chemicals) = parquet_table(&dir, "chemicals");
+    assert_eq!(
+        strings(&chemicals, "substance"),
+        [Some("Tacrolimus".into())]
+    );
+
+    let (_, grants) = parquet_table(&dir, "grants");
+    assert_eq!(strings(&grants, "grant_id"), [Some("R01 DK012345".into())]);
+
+    let (_, references) = parquet_table(&dir, "references");
+    assert_eq!(numbers(&references, "cited_pmid"), [Some(20000001)]);
+}
+
+#[test]
+fn parquet_skips_records_with_an_invalid_pmid() {
+    let dir = std::env::temp_dir().join("rbiblio_export_parquet_invalid_pmid");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let xml = fs::read_to_string("tests/fixtures/pubmed_articles.xml").unwrap();
+    let broken = xml.replacen(">27150001</PMID>", ">2715OOO1</PMID>", 1);
+    let read = |xml: &str| -> Vec<_> {
+        RecordReader::from_reader(xml.as_bytes())
+            .map(Result::unwrap)
+            .collect()
+    };
+    {
+        let open = |table: &str| File::create(dir.join(format!("{}.parquet", table)));
+        let mut writer = ParquetWriter::new(open, 10).unwrap();
+        let records = read(&broken);
+        let error = writer.write_record(&records[0]).unwrap_err();
+        assert!(error.is_record_error());
+        assert_eq!(error.to_string(), "invalid PMID `2715OOO1`");
+        for record in records[1..].iter().chain(&read(&xml)) {
+            writer.write_record(record).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let (_, articles) = parquet_table(&dir, "articles");
+    assert_eq!(
+        numbers(&articles, "pmid"),
+        [Some(10000002), Some(27150001), Some(10000002)]
+    );
+    // no rows of the skipped record in the child tables
+    let (_, authors) = parquet_table(&dir, "authors");
+    assert_eq!(
+        numbers(&authors, "pmid"),
+        [Some(27150001), Some(27150001), Some(27150001)]
+    );
+}
+
+#[test]
+fn parquet_is_not_a_stream_format() {
+    assert!(Format::Parquet.is_directory());
+    assert!(Format::Parquet.writer(Vec::new()).is_err());
+}
+
+#[test]
+fn csv_default_columns() {
+    let csv = export(
+        Format::Csv,
+        &[
+            "pubmed_articles.xml",
+            "pubmed_books.xml",
+            "pubmed_book_authors.xml",
+        ],
+    );
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "pmid,title,journal,year,doi,first_author");
+    assert_eq!(
+        lines[1],
+        "27150001,Effect of CYP3A5 genotype on tacrolimus levels in H2O-restricted patients.,\
+         \"Nephrology, dialysis, transplantation : official publication of the European Dialysis \
+         and Transplant Association - European Renal Association\",2016,10.1093/ndt/gfw079,\
+         \"Müller, Anna Maria\""
+    );
+    assert_eq!(
+        lines[2],
+        "10000002,Solving x^2 < 1 quickly.,Journal of formulas,1998,,"
+    );
+    assert_eq!(
+        lines[3],
+        "20301295,CFTR-Related Disorders,,1993,,\"Ong, Thida\""
+    );
+    assert_eq!(lines[4], "21250001,Renal Clearance,,2011,,\"Ong, Thida\"");
+}
+
+#[test]
+fn tsv_field_paths() {
+    let columns: Vec<Column> = [
+        "pmid",
+        "mesh[*].descriptor",
+        "qualifiers=mesh.qualifiers.name",
+        "article.journal.title",
+        "authors[1].last_name",
+        "received=data.history[0].date",
+        "editors=book.editors.name",
+        "book_authors=book.authors.name",
+    ]
+    .iter()
+    .map(|c| c.parse().unwrap())
+    .collect();
+    let mut out = Vec::new();
+    {
+        let mut writer = DelimitedWriter::new(&mut out, '\t')
+            .with_columns(columns)
+            .with_separator(" | ");
+        for fixture in [
+            "pubmed_articles.xml",
+            "pubmed_books.xml",
+            "pubmed_book_authors.xml",
+        ] {
+            let file = File::open(format!("tests/fixtures/{}", fixture)).unwrap();
+            for record in RecordReader::from_reader(BufReader::new(file)) {
+                writer.write_record(&record.unwrap()).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+    }
+    let tsv = String::from_utf8(out).unwrap();
+    let rows: Vec<Vec<&str>> = tsv.lines().map(|l| l.split('\t').collect()).collect();
+    assert_eq!(
+        rows[0],
+        [
+            "pmid",
+            "mesh[*].descriptor",
+            "qualifiers",
+            "article.journal.title",
+            "authors[1].last_name",
+            "received",
+            "editors",
+            "book_authors"
+        ]
+    );
+    assert_eq!(rows[1][1], "Humans | Kidney Transplantation");
+    assert_eq!(rows[1][2], "methods");
+    assert!(rows[1][3].starts_with("Nephrology, dialysis"));
+    assert_eq!(rows[1][4], "Smith");
+    assert_eq!(rows[1][5], "2015 Oct 12");
+    assert_eq!(
+        rows[2][1..],
+        ["", "", "Journal of formulas", "", "", "", ""]
+    );
+    assert_eq!(rows[3][0], "20301295");
+    assert_eq!(rows[3][6], "Adam, Margaret P");
+    assert_eq!(rows[3][7], "");
+    // the authors of the book stay apart from those of the chapter
+    assert_eq!(rows[4][4], "");
+    assert_eq!(rows[4][7], "Bookwriter, Bea");
+}
+
+#[test]
+fn invalid_field_paths_are_rejected() {
+    assert!("article[".parse::<FieldPath>().is_err());
+    assert!("article..title".parse::<FieldPath>().is_err());
+    assert!("authors[x]".parse::<FieldPath>().is_err());
+
+    let file = File::open("tests/fixtures/pubmed_articles.xml").unwrap();
+    let record = RecordReader::from_reader(BufReader::new(file))
+        .next()
+        .unwrap()
+        .unwrap();
+    let unknown: FieldPath = "article.foo".parse().unwrap();
+    let error = unknown.values(&record).unwrap_err();
+    assert!(error.to_string().contains("unknown field `foo`"));
+    let object: FieldPath = "article.journal".parse().unwrap();
+    assert!(object.values(&record).is_err());
+}
+
+#[test]
+fn ndjson_records() {
+    let ndjson = export(Format::Ndjson, &["pubmed_articles.xml", "pubmed_books.xml"]);
+    let records: Vec<JsonRecord> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 4);
+
+    let JsonRecord::Article(article) = &records[0] else {
+        panic!("expected an article");
+    };
+    assert_eq!(article.schema_version, SCHEMA_VERSION);
+    assert_eq!(article.pmid, "27150001");
+    assert_eq!(article.date_completed.as_deref(), Some("2017-05-22"));
+    assert_eq!(
+        article.title_markup.as_deref(),
+        Some("Effect of <i>CYP3A5</i> genotype on tacrolimus levels in H<sub>2</sub>O-restricted patients.")
+    );
+    assert_eq!(article.journal.issn_linking.as_deref(), Some("0931-0509"));
+    assert_eq!(article.pub_date.start.as_deref(), Some("2016-07-04"));
+    assert_eq!(article.summary[0].label.as_deref(), Some("BACKGROUND"));
+    assert_eq!(
+        article.authors[2].collective_name.as_deref(),
+        Some("Transplant Study Group")
+    );
+    assert_eq!(article.mesh[1].qualifiers[0].name, "methods");
+    assert_eq!(article.keywords[0].owner.as_deref(), Some("NOTNLM"));
+    assert_eq!(article.history[1].time.as_deref(), Some("06:00"));
+    assert_eq!(article.references[0].pmid.as_deref(), Some("20000001"));
+
+    let JsonRecord::Article(dated) = &records[1] else {
+        panic!("expected an article");
+    };
+    assert_eq!(dated.pub_date.start.as_deref(), Some("1998-03"));
+    assert_eq!(dated.pub_date.end.as_deref(), Some("1998-04"));
+    assert_eq!(dated.pub_date.text.as_deref(), Some("1998 Mar-Apr"));
+    assert!(dated.authors.is_empty());
+
+    let JsonRecord::BookArticle(book) = &records[2] else {
+        panic!("expected a book article");
+    };
+    assert_eq!(book.title.as_deref(), Some("CFTR-Related Disorders"));
+    assert_eq!(book.book.title_markup, "GeneReviews<sup>®</sup>");
+    assert_eq!(book.editors[0].last_name.as_deref(), Some("Adam"));
+    assert_eq!(book.book.editors[0].last_name.as_deref(), Some("Adam"));
+    assert!(book.book.authors.is_empty());
+
+    let ndjson = export(Format::Ndjson, &["pubmed_book_authors.xml"]);
+    let JsonRecord::BookArticle(chapter) = serde_json::from_str(&ndjson).unwrap() else {
+        panic!("expected a book article");
+    };
+    let last_names = |authors: &[JsonAuthor]| -> Vec<String> {
+        authors.iter().filter_map(|a| a.last_name.clone()).collect()
+    };
+    assert_eq!(last_names(&chapter.authors), ["Ong"]);
+    assert_eq!(last_names(&chapter.book.authors), ["Bookwriter"]);
+    assert_eq!(last_names(&chapter.editors), ["Redact"]);
+
+    let JsonRecord::DeleteCitation(deletion) = &records[3] else {
+        panic!("expected a deletion");
+    };
+    assert_eq!(deletion.pmids, ["111", "222"]);
+}
+
+#[test]
+fn ndjson_records_match_the_schema() {
+    let schema = json_schema();
+    let variants = schema["oneOf"].as_array().unwrap();
+    let ndjson = export(Format::Ndjson, &["pubmed_articles.xml", "pubmed_books.xml"]);
+    for line in ndjson.lines() {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        let variant = variants
+            .iter()
+            .find(|v| v["properties"]["type"]["enum"][0] == record["type"])
+            .unwrap();
+        let mut keys: Vec<&String> = record.as_object().unwrap().keys().collect();
+        let mut required: Vec<&str> = variant["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        keys.sort();
+        required.sort();
+        assert_eq!(keys, required);
+    }
+}
+
+#[test]
+fn schema_file_is_up_to_date() {
+    let file = fs::read_to_string("schema/record-v1.schema.json").unwrap();
+    let committed: serde_json::Value = serde_json::from_str(&file).unwrap();
+    assert_eq!(
+        committed,
+        json_schema(),
+        "regenerate with `rbiblio schema > schema/record-v1.schema.json`"
+    );
+}
+
+fn read_records(xml: &[u8]) -> Vec<serde_json::Value> {
+    RecordReader::from_reader(xml)
+        .map(|record| serde_json::to_value(record.unwrap()).unwrap())
+        .collect()
+}
+
+#[test]
+fn xml_round_trip() {
+    let fixtures = [
+        "pubmed_articles.xml",
+        "pubmed_articles_250101.xml",
+        // ends with the deletions, which the writer puts last
+        "pubmed_books.xml",
+    ];
+    let xml = export(Format::Xml, &fixtures);
+    assert!(xml.starts_with(
+        "<?xml version=\"1.0\" ?>\n<!DOCTYPE PubmedArticleSet PUBLIC \
+         \"-//NLM//DTD PubMedArticle, 1st January 2019//EN\" \
+         \"https://dtd.nlm.nih.gov/ncbi/pubmed/out/pubmed_190101.dtd\">\n<PubmedArticleSet>\n"
+    ));
+    assert!(xml.contains("<MedlineCitation Status=\"MEDLINE\" Owner=\"NLM\">"));
+    assert!(xml.contains(
+        "<ArticleTitle>Effect of <i>CYP3A5</i> genotype on tacrolimus levels in \
+         H<sub>2</sub>O-restricted patients.</ArticleTitle>"
+    ));
+    assert!(xml.contains("<mml:math xmlns:mml=\"http://www.w3.org/1998/Math/MathML\">"));
+    assert!(xml.contains("<Initials>AM</Initials>"));
+    // Bookshelf links of book and section titles
+    assert!(xml.contains("<BookTitle book=\"gene\">GeneReviews<sup>®</sup></BookTitle>"));
+    assert!(xml
+        .contains("<ArticleTitle book=\"gene\" part=\"cf\">CFTR-Related Disorders</ArticleTitle>"));
+    assert!(xml.contains(
+        "<SectionTitle book=\"gene\" part=\"cf\" sec=\"cf.Criteria\">Criteria</SectionTitle>"
+    ));
+    assert!(xml
+        .trim_end()
+        .ends_with("</DeleteCitation>\n</PubmedArticleSet>"));
+
+    let mut original = Vec::new();
+    for fixture in fixtures {
+        original.extend(read_records(
+            &fs::read(format!("tests/fixtures/{}", fixture)).unwrap(),
+        ));
+    }
+    let written = read_records(xml.as_bytes());
+    assert_eq!(written.len(), original.len());
+    assert_eq!(written, original);
+
+    let mut rewritten = Vec::new();
+    let mut writer = Format::Xml.writer(&mut rewritten).unwrap();
+    for record in RecordReader::from_reader(xml.as_bytes()) {
+        writer.write_record(&record.unwrap()).unwrap();
+    }
+    writer.finish().unwrap();
+    drop(writer);
+    assert_eq!(String::from_utf8(rewritten).unwrap(), xml);
+}
+
+#[test]
+fn xml_keeps_the_dtd_version_of_the_input() {
+    let file = File::open("tests/fixtures/pubmed_articles_250101.xml").unwrap();
+    let mut reader = RecordReader::from_reader(BufReader::new(file));
+    let records: Vec<_> = reader.by_ref().map(Result::unwrap).collect();
+    let mut out = Vec::new();
+    let mut writer = XmlWriter::new(&mut out).with_dtd_version(reader.dtd_version().unwrap());
+    for record in &records {
+        writer.write_record(record).unwrap();
+    }
+    writer.finish().unwrap();
+    drop(writer);
+    let xml = String::from_utf8(out).unwrap();
+    assert!(xml.starts_with(
+        "<?xml version=\"1.0\" ?>\n<!DOCTYPE PubmedArticleSet PUBLIC \
+         \"-//NLM//DTD PubMedArticle, 1st January 2025//EN\" \
+         \"https://dtd.nlm.nih.gov/ncbi/pubmed/out/pubmed_250101.dtd\">\n"
+    ));
+    let mut reader = RecordReader::from_reader(xml.as_bytes());
+    assert_eq!(reader.by_ref().count(), records.len());
+    assert_eq!(reader.dtd_version(), Some(DtdVersion::PUBMED_250101));
+}
+
+#[test]
+fn xml_names_the_release_set_before_the_first_record() {
+    let file = File::open("tests/fixtures/pubmed_articles.xml").unwrap();
+    let record = RecordReader::from_reader(BufReader::new(file))
+        .next()
+        .unwrap()
+        .unwrap();
+    let mut out = Vec::new();
+    {
+        let mut writer = Format::Xml.writer(&mut out).unwrap();
+        writer.set_dtd_version(DtdVersion::PUBMED_250101);
+        writer.write_record(&record).unwrap();
+        // the DOCTYPE has been written
+        writer.set_dtd_version(DtdVersion::PUBMED_190101);
+        writer.finish().unwrap();
+    }
+    let xml = String::from_utf8(out).unwrap();
+    assert!(xml.contains("/pubmed_250101.dtd\">"));
+    assert!(!xml.contains("pubmed_190101"));
+}
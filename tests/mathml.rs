@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use rbiblio::pubmed::{Math, RichText, RunContent};
+use rbiblio::PubmedReader;
+
+fn math(body: &str) -> Math {
+    Math::new(format!(
+        "<mml:math xmlns:mml=\"http://www.w3.org/1998/Math/MathML\">{}</mml:math>",
+        body
+    ))
+}
+
+#[test]
+fn formulas_are_read_intact() {
+    let file = File::open("tests/fixtures/pubmed_articles.xml").unwrap();
+    let articles: Vec<_> = PubmedReader::from_reader(BufReader::new(file))
+        .map(|a| a.unwrap())
+        .collect();
+    let title = articles[1].article().title().unwrap();
+    let formula = title
+        .runs()
+        .into_iter()
+        .find_map(|run| match run.content {
+            RunContent::Math(math) => Some(math.clone()),
+            RunContent::Text(_) => None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        formula.mathml(),
+        "<mml:math xmlns:mml=\"http://www.w3.org/1998/Math/MathML\"><mml:msup><mml:mi>x</mml:mi><mml:mn>2</mml:mn></mml:msup><mml:mo>&lt;</mml:mo><mml:mn>1</mml:mn></mml:math>"
+    );
+    let root = formula.root().unwrap();
+    assert_eq!(root.name(), "math");
+    assert_eq!(root.elements().next().unwrap().name(), "msup");
+    assert_eq!(formula.to_latex().unwrap(), "x^{2}<1");
+    assert_eq!(formula.to_plain(), "x^2 < 1");
+    assert_eq!(title.to_markdown(), "Solving $x^{2}<1$ quickly.");
+    assert!(title.to_html().starts_with(
+        "Solving <math xmlns=\"http://www.w3.org/1998/Math/MathML\"><msup><mi>x</mi>"
+    ));
+}
+
+#[test]
+fn attributes_and_empty_elements_survive_reading() {
+    let xml = std::fs::read_to_string("tests/fixtures/pubmed_articles.xml")
+        .unwrap()
+        .replace(
+            "<mml:mo>&lt;</mml:mo>",
+            "<mml:mspace width=\"0.2em\"/><mml:mo stretchy=\"false\">≤</mml:mo>",
+        );
+    let article = PubmedReader::from_reader(xml.as_bytes())
+        .nth(1)
+        .unwrap()
+        .unwrap();
+    let markup = article.article().title().unwrap().to_markup();
+    assert!(markup.contains("<mml:mspace width=\"0.2em\"/>"));
+    assert!(markup.contains("<mml:mo stretchy=\"false\">≤</mml:mo>"));
+}
+
+#[test]
+fn latex_conversion() {
+    let fraction = math("<mml:mfrac><mml:mrow><mml:mi>a</mml:mi><mml:mo>+</mml:mo><mml:mi>b</mml:mi></mml:mrow><mml:mn>2</mml:mn></mml:mfrac>");
+    assert_eq!(fraction.to_latex().unwrap(), "\\frac{a+b}{2}");
+    assert_eq!(fraction.to_plain(), "(a + b)/2");
+
+    let root = math("<mml:msqrt><mml:msub><mml:mi>σ</mml:mi><mml:mi>x</mml:mi></mml:msub></mml:msqrt><mml:mo>×</mml:mo><mml:mi>sin</mml:mi><mml:mi>θ</mml:mi>");
+    assert_eq!(
+        root.to_latex().unwrap(),
+        "\\sqrt{\\sigma_{x}}\\times\\sin\\theta"
+    );
+    assert_eq!(root.to_plain(), "sqrt(σ_x) × sinθ");
+
+    let vector = math("<mml:mover accent=\"true\"><mml:mi>v</mml:mi><mml:mo>→</mml:mo></mml:mover><mml:mtext>mean_%</mml:mtext>");
+    assert_eq!(vector.to_latex().unwrap(), "\\vec{v}\\text{mean\\_\\%}");
+    assert_eq!(vector.to_plain(), "vmean_%");
+
+    let fenced = math("<mml:mfenced><mml:mi>x</mml:mi><mml:mi>y</mml:mi></mml:mfenced>");
+    assert_eq!(fenced.to_latex().unwrap(), "(x,y)");
+    assert_eq!(fenced.to_plain(), "(x, y)");
+}
+
+#[test]
+fn malformed_mathml_keeps_its_text() {
+    let text = RichText::parse("see <mml:math><mml:mi>x</mml:mo></mml:math> here");
+    let math = match text.runs()[1].content {
+        RunContent::Math(math) => math.clone(),
+        RunContent::Text(_) => panic!("expected a formula"),
+    };
+    assert!(math.root().is_none());
+    assert_eq!(math.to_latex(), None);
+    assert_eq!(text.to_plain(), "see x here");
+
+    // the unparsed markup never reaches HTML as tags
+    let math = Math::new("<mml:math><mml:mi>a &lt; b</mml:mo><script>x</script></mml:math>");
+    assert!(math.root().is_none());
+    assert_eq!(math.to_html(), "a &lt; bx");
+    assert_eq!(math.to_plain(), "a < bx");
+    assert_eq!(text.to_html(), "see x here");
+}
@@ -0,0 +1,257 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use rbiblio::db::to_document;
+use rbiblio::pubmed::{DateRevised, Precision, PubmedArticle, PubmedBookArticle};
+use rbiblio::{PubmedReader, Record, RecordReader};
+
+fn articles() -> Vec<PubmedArticle> {
+    let file = File::open("tests/fixtures/pubmed_articles.xml").unwrap();
+    PubmedReader::from_reader(BufReader::new(file))
+        .map(|a| a.unwrap())
+        .collect()
+}
+
+fn book() -> PubmedBookArticle {
+    let file = File::open("tests/fixtures/pubmed_books.xml").unwrap();
+    RecordReader::from_reader(BufReader::new(file))
+        .find_map(|r| match r.unwrap() {
+            Record::BookArticle(book) => Some(book),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn article_fields_are_readable() {
+    let article = &articles()[0];
+    let citation = article.medline_citation();
+    assert_eq!(citation.status(), "MEDLINE");
+    assert_eq!(citation.date_completed().unwrap().year(), "2017");
+    let revised: &DateRevised = citation.date_revised().unwrap();
+    assert_eq!(revised.date().unwrap().to_string(), "2018 Dec 2");
+
+    let a = article.article();
+    assert_eq!(a.pub_model(), "Print-Electronic");
+    assert_eq!(a.pages().as_deref(), Some("1170-7"));
+    assert_eq!(a.languages(), ["eng"]);
+    assert_eq!(a.year(), Some(2016));
+    assert!(a.title().unwrap().to_plain().starts_with("Effect of"));
+    assert_eq!(a.publication_types()[0].value(), "Journal Article");
+    assert_eq!(a.article_dates()[0].date_type(), Some("Electronic"));
+
+    let journal = a.journal();
+    assert_eq!(journal.issn().unwrap().value(), "1460-2385");
+    assert_eq!(
+        journal.iso_abbreviation(),
+        Some("Nephrol. Dial. Transplant.")
+    );
+    assert_eq!(journal.journal_issue().volume(), Some("31"));
+    assert_eq!(journal.journal_issue().issue(), Some("7"));
+    let date = journal.date().unwrap();
+    assert_eq!(date.precision(), Precision::Day);
+    assert_eq!(date.to_string(), "2016 Jul 4");
+
+    let summary = a.summary().unwrap();
+    assert_eq!(summary.texts().len(), 2);
+    assert_eq!(summary.texts()[1].label(), Some("METHODS"));
+    assert!(summary.text().starts_with("BACKGROUND: Tacrolimus levels"));
+    assert_eq!(summary.copyright_information(), Some("© The Author 2016."));
+
+    let grant = &a.grants()[0];
+    assert_eq!(grant.grant_id(), Some("R01 DK012345"));
+    assert_eq!(grant.agency(), Some("NIDDK NIH HHS"));
+}
+
+#[test]
+fn authors_and_affiliations_are_readable() {
+    let articles = articles();
+    let list = articles[0].article().author_list().unwrap();
+    assert!(list.is_complete());
+
+    let authors = list.authors();
+    assert_eq!(authors.len(), 3);
+    assert_eq!(authors[0].last_name(), Some("Müller"));
+    assert_eq!(authors[0].fore_name(), Some("Anna Maria"));
+    assert_eq!(authors[0].initials(), Some("AM"));
+    assert_eq!(
+        authors[0].affiliations(),
+        ["Department of Nephrology, Charité, Berlin, Germany."]
+    );
+    assert!(authors[1].affiliations().is_empty());
+    assert_eq!(authors[2].collective_name(), Some("Transplant Study Group"));
+    assert!(authors.iter().all(|a| a.is_valid()));
+}
+
+#[test]
+fn ids_and_mesh_terms_are_readable() {
+    let articles = articles();
+    let article = &articles[0];
+    assert_eq!(article.pubmed_id(), Ok(27150001));
+    assert_eq!(article.doi(), Some("10.1093/ndt/gfw079"));
+    assert_eq!(article.pmc(), Some("PMC4900001"));
+    assert_eq!(article.pubmed_references(), [20000001]);
+
+    let data = article.pubmed_data().unwrap();
+    assert_eq!(data.publication_status(), "ppublish");
+    assert_eq!(data.article_id_list().pubmed_id(), Some(27150001));
+    assert_eq!(data.history()[1].pub_status(), "pubmed");
+    assert_eq!(data.history()[1].hour(), Some("6"));
+
+    let citation = article.medline_citation();
+    let mesh = citation.mesh_headings();
+    assert_eq!(mesh.len(), 2);
+    assert_eq!(mesh[1].descriptor().value(), "Kidney Transplantation");
+    assert!(mesh[1].is_major_topic());
+    assert!(!mesh[0].is_major_topic());
+    assert_eq!(mesh[1].qualifiers()[0].ui(), "Q000379");
+    assert_eq!(citation.mesh_descriptor_uis(), ["D006801", "D016030"]);
+    assert_eq!(citation.chemicals()[0].substance().value(), "Tacrolimus");
+    assert_eq!(citation.keywords(), ["CYP3A5", "tacrolimus"]);
+    assert_eq!(citation.keyword_lists()[0].owner(), Some("NOTNLM"));
+    assert_eq!(citation.citation_subsets(), ["IM"]);
+    assert_eq!(
+        citation.medline_journal_info().medline_ta(),
+        Some("Nephrol Dial Transplant")
+    );
+
+    assert_eq!(articles[1].pmc(), None);
+    assert_eq!(articles[1].medline_citation().pmid().version_number(), 2);
+    let date = articles[1].article().date().unwrap();
+    assert!(date.is_range());
+    assert_eq!(date.to_string(), "1998 Mar-Apr");
+}
+
+#[test]
+fn book_fields_are_readable() {
+    let book = book();
+    assert_eq!(book.pubmed_id(), Ok(20301295));
+
+    let document = book.book_document();
+    assert_eq!(
+        document.article_id_list().find("bookaccession"),
+        Some("NBK1116")
+    );
+    assert_eq!(
+        document.book().publisher().name(),
+        "University of Washington, Seattle"
+    );
+    assert_eq!(document.book().publisher().location(), Some("Seattle (WA)"));
+
+    let title = document.article_title().unwrap().link();
+    assert_eq!((title.book(), title.part()), (Some("gene"), Some("cf")));
+    assert_eq!(document.book().book_title().link().part(), None);
+    let criteria = document.sections()[1].sections()[0].section_title();
+    assert_eq!(criteria.link().sec(), Some("cf.Criteria"));
+}
+
+#[test]
+fn coi_statement_and_study_details_are_read() {
+    let articles = articles();
+    let citation = articles[0].medline_citation();
+    assert_eq!(
+        citation.coi_statement().unwrap().to_markup(),
+        "J.S. received fees from <i>Astellas</i>; the other authors declare no conflicts of interest."
+    );
+    assert_eq!(
+        citation.space_flight_missions(),
+        ["Flight Experiment", "STS-95"]
+    );
+
+    let investigator = &citation.investigators()[0];
+    assert_eq!(investigator.last_name(), "Rossi");
+    assert_eq!(investigator.identifiers()[0].source(), "ORCID");
+    assert_eq!(investigator.affiliations(), ["University of Padua, Italy."]);
+
+    let notes = citation.general_notes();
+    assert_eq!(notes[0].owner(), "NASA");
+    assert_eq!(notes[1].owner(), "NLM");
+    assert_eq!(notes[1].value(), "Erratum published separately.");
+
+    let second = articles[1].medline_citation();
+    assert!(second.coi_statement().is_none());
+    assert!(second.investigators().is_empty());
+}
+
+#[test]
+fn new_citation_elements_reach_json_and_bson() {
+    let articles = articles();
+
+    let json = serde_json::to_value(&articles[0]).unwrap();
+    let citation = &json["MedlineCitation"];
+    assert!(citation["CoiStatement"]["$value"]
+        .as_str()
+        .unwrap()
+        .starts_with("J.S. received fees"));
+    assert_eq!(citation["SpaceFlightMission"][1]["$value"], "STS-95");
+    assert_eq!(
+        citation["InvestigatorList"]["Investigator"][0]["LastName"]["$value"],
+        "Rossi"
+    );
+    assert_eq!(citation["GeneralNote"][0]["@Owner"], "NASA");
+    let author = &citation["Article"]["AuthorList"]["Author"][0];
+    assert_eq!(author["Initials"]["value"], "AM");
+
+    let document = to_document(&articles[0]).unwrap();
+    let citation = document
+        .get_document("article")
+        .unwrap()
+        .get_document("MedlineCitation")
+        .unwrap();
+    assert!(citation
+        .get_document("CoiStatement")
+        .unwrap()
+        .get_str("$value")
+        .unwrap()
+        .contains("no conflicts of interest"));
+    assert_eq!(citation.get_array("GeneralNote").unwrap().len(), 2);
+}
+
+#[test]
+fn current_dtd_elements_are_readable() {
+    let file = File::open("tests/fixtures/pubmed_articles_250101.xml").unwrap();
+    let mut reader = PubmedReader::from_reader(BufReader::new(file));
+    let article = reader.next().unwrap().unwrap();
+    let unpaginated = reader.next().unwrap().unwrap();
+    assert!(reader.next().is_none());
+    assert_eq!(reader.summary().dtd.as_deref(), Some("pubmed_250101"));
+
+    // pages from StartPage and EndPage when there is no MedlinePgn
+    let pagination = unpaginated.article().pagination().unwrap();
+    assert_eq!(pagination.medline_pgn(), None);
+    assert_eq!(unpaginated.article().pages().as_deref(), Some("101-110"));
+
+    let citation = article.medline_citation();
+    assert_eq!(citation.indexing_method(), Some("Automated"));
+    assert_eq!(citation.keyword_lists()[0].owner(), Some("NOTNLM"));
+
+    let a = article.article();
+    let pagination = a.pagination().unwrap();
+    assert_eq!(pagination.start_page(), Some("20345"));
+    assert_eq!(pagination.end_page(), Some("20356"));
+    assert_eq!(a.pages().as_deref(), Some("20345-20356"));
+    let eids: Vec<&str> = a.elocation_ids().iter().map(|e| e.eid_type()).collect();
+    assert_eq!(eids, ["pii", "doi"]);
+    assert_eq!(a.publication_types()[1].ui(), "D013485");
+    assert_eq!(a.article_dates()[0].date_type(), Some("Electronic"));
+
+    let authors = a.authors();
+    assert!(authors.iter().all(|a| a.is_equal_contributor()));
+    assert_eq!(authors[0].identifiers()[0].source(), "ORCID");
+    assert_eq!(authors[0].identifiers()[0].value(), "0000-0001-5109-3700");
+    let info = &authors[0].affiliation_info()[0];
+    assert_eq!(info.identifiers()[0].source(), "ROR");
+    assert_eq!(authors[1].suffix(), Some("Jr"));
+    assert!(authors[1].identifiers().is_empty());
+
+    let data = article.pubmed_data().unwrap();
+    let references = &data.reference_lists()[0];
+    assert_eq!(references.title(), Some("References"));
+    assert_eq!(references.references().len(), 1);
+    let nested = &references.reference_lists()[0];
+    assert_eq!(nested.title(), Some("Supplementary references"));
+    assert!(nested.references()[0]
+        .citation()
+        .is_some_and(|c| c.starts_with("Medlock JM")));
+    assert_eq!(article.pubmed_references(), [26126267]);
+}
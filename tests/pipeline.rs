@@ -0,0 +1,171 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::{BufRead, Cursor, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rbiblio::pipeline::{Event, Pipeline};
+use rbiblio::{Record, RecordReader};
+
+fn gzip_fixtures(name: &str, fixtures: &[&str]) -> Vec<PathBuf> {
+    let root = std::env::temp_dir().join(format!("rbiblio_pipeline_{}", name));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    fixtures
+        .iter()
+        .enumerate()
+        .map(|(i, fixture)| {
+            let xml = fs::read(format!("tests/fixtures/{}", fixture)).unwrap();
+            let path = root.join(format!("{:04}_{}.gz", i, fixture));
+            let mut gz = GzEncoder::new(File::create(&path).unwrap(), Compression::fast());
+            gz.write_all(&xml).unwrap();
+            gz.finish().unwrap();
+            path
+        })
+        .collect()
+}
+
+fn label(event: &Event) -> String {
+    match event {
+        Event::Record(record) => match record.as_ref() {
+            Record::Article(article) => format!("article {}", article.pubmed_id().unwrap()),
+            Record::BookArticle(_) => "book".to_string(),
+            Record::DeleteCitation(_) => "delete".to_string(),
+            Record::DeleteDocument(_) => "delete document".to_string(),
+        },
+        Event::Doctype(version) => format!("doctype {}", version),
+        Event::Error(e) => format!("error {}", e.kind()),
+        Event::FileDone(progress) => format!("done {}", progress.summary.records()),
+    }
+}
+
+#[test]
+fn events_keep_input_order() {
+    let fixtures = ["pubmed_articles.xml", "pubmed_books.xml"].repeat(6);
+    let files = gzip_fixtures("order", &fixtures);
+
+    let events: Vec<String> = Pipeline::new()
+        .with_threads(4)
+        .with_capacity(1)
+        .run(files)
+        .map(|e| label(&e))
+        .collect();
+
+    let expected = [
+        "doctype pubmed_190101",
+        "article 27150001",
+        "article 10000002",
+        "done 2",
+        "doctype pubmed_190101",
+        "book",
+        "delete",
+        "done 2",
+    ]
+    .repeat(6);
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn missing_files_are_reported_and_skipped() {
+    let mut files = gzip_fixtures("missing", &["pubmed_books.xml"]);
+    files.insert(0, PathBuf::from("does/not/exist.xml.gz"));
+
+    let events: Vec<String> = Pipeline::new().run(files).map(|e| label(&e)).collect();
+
+    assert_eq!(
+        events,
+        [
+            "error io",
+            "done 0",
+            "doctype pubmed_190101",
+            "book",
+            "delete",
+            "done 2"
+        ]
+    );
+}
+
+#[test]
+fn dropping_the_events_stops_the_workers() {
+    let files = gzip_fixtures("drop", &["pubmed_articles.xml"].repeat(20));
+
+    let mut events = Pipeline::new().with_threads(3).with_capacity(1).run(files);
+    assert_eq!(label(&events.next().unwrap()), "doctype pubmed_190101");
+    assert_eq!(label(&events.next().unwrap()), "article 27150001");
+    // joins the workers blocked on their full channels
+    drop(events);
+}
+
+#[test]
+fn parallel_reader_matches_sequential_reader() {
+    let fixture = fs::read_to_string("tests/fixtures/pubmed_articles.xml").unwrap();
+    let start = fixture.find("<PubmedArticle>").unwrap();
+    let end = fixture.rfind("</PubmedArticleSet>").unwrap();
+    let (head, records, tail) = (&fixture[..start], &fixture[start..end], &fixture[end..]);
+    let broken = records.replacen("</Language>", "</Languag>", 1);
+    let mut xml = head.to_string();
+    for i in 0..300 {
+        xml.push_str(if i % 37 == 5 { &broken } else { records });
+    }
+    xml.push_str(tail);
+
+    let sequential: Vec<String> = RecordReader::from_reader(Cursor::new(xml.clone()))
+        .map(|r| format!("{:?}", r))
+        .collect();
+    let mut parallel = RecordReader::from_reader(Cursor::new(xml)).parallel(4);
+    let records: Vec<String> = parallel.by_ref().map(|r| format!("{:?}", r)).collect();
+
+    assert_eq!(records.len(), 600);
+    assert_eq!(records, sequential);
+    assert_eq!(parallel.summary().error_count(), 8);
+}
+
+/// Counts the bytes taken from the inner reader.
+struct Counting<R> {
+    inner: R,
+    read: Arc<AtomicUsize>,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n, Ordering::SeqCst);
+        Ok(n)
+    }
+}
+
+#[test]
+fn parallel_reader_scans_a_bounded_window_ahead() {
+    let fixture = fs::read_to_string("tests/fixtures/pubmed_articles.xml").unwrap();
+    let start = fixture.find("<PubmedArticle>").unwrap();
+    let end = fixture.rfind("</PubmedArticleSet>").unwrap();
+    let records = &fixture[start..end];
+    let mut xml = fixture[..start].to_string();
+    for _ in 0..2000 {
+        xml.push_str(records);
+    }
+    xml.push_str(&fixture[end..]);
+
+    let read = Arc::new(AtomicUsize::new(0));
+    let counting = Counting {
+        inner: Cursor::new(xml.clone()),
+        read: Arc::clone(&read),
+    };
+    let reader: Box<dyn BufRead + Send> = Box::new(std::io::BufReader::new(counting));
+    let mut parallel = RecordReader::from_reader(reader).parallel(1);
+    assert!(parallel.next().unwrap().is_ok());
+    std::thread::sleep(Duration::from_millis(200));
+
+    // one decoder: the batch being yielded plus 4 ahead, 64 records each
+    let scanned = read.load(Ordering::SeqCst);
+    assert!(
+        scanned <= 6 * 64 * records.len() / 2,
+        "scanned {} of {} bytes",
+        scanned,
+        xml.len()
+    );
+    assert_eq!(parallel.count(), 3999);
+}
@@ -0,0 +1,97 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use rbiblio::{ParseError, PubmedReader};
+
+fn gzip_fixture(name: &str) -> PathBuf {
+    let xml = fs::read(format!("tests/fixtures/{}", name)).unwrap();
+    let path = std::env::temp_dir().join(format!("rbiblio_reader_{}.gz", name));
+    let mut gz = GzEncoder::new(File::create(&path).unwrap(), Compression::fast());
+    gz.write_all(&xml).unwrap();
+    gz.finish().unwrap();
+    path
+}
+
+#[test]
+fn compressed_files_are_read() {
+    let path = gzip_fixture("pubmed_articles.xml");
+    let mut reader = PubmedReader::open(&path).unwrap();
+    let pmids: Vec<u32> = reader
+        .by_ref()
+        .map(|article| article.unwrap().pubmed_id().unwrap())
+        .collect();
+    assert_eq!(pmids, [27150001, 10000002]);
+    assert_eq!(reader.summary().articles, 2);
+    assert_eq!(reader.summary().error_count(), 0);
+    assert_eq!(reader.summary().file, Some(path.display().to_string()));
+}
+
+#[test]
+fn missing_and_uncompressed_files_are_io_errors() {
+    let Err(error) = PubmedReader::open("does/not/exist.xml.gz") else {
+        panic!("opened a missing file");
+    };
+    assert!(matches!(error, ParseError::Io { .. }));
+    assert_eq!(error.kind(), "io");
+    assert!(!error.is_record_error());
+
+    // not gzip: the header is checked on the first read
+    let mut reader = PubmedReader::open("tests/fixtures/pubmed_articles.xml").unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.kind(), "io");
+    assert_eq!(
+        error.context().file.as_deref(),
+        Some("tests/fixtures/pubmed_articles.xml")
+    );
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn malformed_records_are_reported_and_skipped() {
+    let xml = fs::read_to_string("tests/fixtures/pubmed_articles.xml").unwrap();
+    // an unclosed Journal in the first article
+    let broken = xml.replacen(
+        "<Article PubModel=\"Print-Electronic\">",
+        "<Article PubModel=\"Print-Electronic\"><Journal>",
+        1,
+    );
+    let mut reader = PubmedReader::from_reader(broken.as_bytes()).with_file_name("broken.xml");
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(matches!(error, ParseError::Deserialize { .. }));
+    assert_eq!(error.kind(), "deserialize");
+    assert!(error.is_record_error());
+    let context = error.context();
+    assert_eq!(context.file.as_deref(), Some("broken.xml"));
+    assert_eq!(context.pmid.as_deref(), Some("27150001"));
+    assert_eq!(
+        context.path,
+        "PubmedArticle/MedlineCitation/Article/Journal"
+    );
+    assert!(error.to_string().starts_with("broken.xml:"));
+
+    // the next record is read as usual
+    let article = reader.next().unwrap().unwrap();
+    assert_eq!(article.pubmed_id(), Ok(10000002));
+    assert_eq!(reader.summary().articles, 1);
+    assert_eq!(reader.summary().errors["deserialize"], 1);
+}
+
+#[test]
+fn the_iterator_ends_after_the_last_record() {
+    let file = fs::read("tests/fixtures/pubmed_articles.xml").unwrap();
+    let mut reader = PubmedReader::from_reader(&file[..]);
+    assert_eq!(reader.by_ref().count(), 2);
+    assert!(reader.next().is_none());
+    assert!(reader.next().is_none());
+
+    // books and deletions are counted but not yielded
+    let file = fs::read("tests/fixtures/pubmed_books.xml").unwrap();
+    let mut reader = PubmedReader::from_reader(&file[..]);
+    assert!(reader.next().is_none());
+    assert!(reader.next().is_none());
+    let summary = reader.summary();
+    assert_eq!((summary.book_articles, summary.delete_citations), (1, 1));
+}
@@ -0,0 +1,177 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use rbiblio::pubmed::{Inline, PubmedArticle, RichText, RunContent, Style};
+use rbiblio::PubmedReader;
+
+fn articles() -> Vec<PubmedArticle> {
+    let file = File::open("tests/fixtures/pubmed_articles.xml").unwrap();
+    PubmedReader::from_reader(BufReader::new(file))
+        .map(|a| a.unwrap())
+        .collect()
+}
+
+#[test]
+fn inline_markup_becomes_runs() {
+    let articles = articles();
+    let title = articles[0].article().title().unwrap();
+    assert_eq!(
+        title.to_plain(),
+        "Effect of CYP3A5 genotype on tacrolimus levels in H2O-restricted patients."
+    );
+
+    let runs = title.runs();
+    assert_eq!(runs[1].content, RunContent::Text("CYP3A5"));
+    assert!(runs[1].styles.italic);
+    assert!(!runs[0].styles.italic);
+    assert_eq!(runs[3].content, RunContent::Text("2"));
+    assert!(runs[3].styles.subscript);
+
+    let sections = articles[0].article().summary().unwrap().texts();
+    assert_eq!(
+        sections[0].text().to_plain(),
+        "Tacrolimus levels vary with p < 0.05 and x2."
+    );
+    assert_eq!(
+        sections[1].text().nodes()[1],
+        Inline::Styled(Style::Bold, vec![Inline::Text("120".to_string())])
+    );
+}
+
+#[test]
+fn renderers() {
+    let text = RichText::parse("p < 0.05 in <i>E. coli</i> and <b>CO<sub>2</sub></b>");
+    assert_eq!(text.to_plain(), "p < 0.05 in E. coli and CO2");
+    assert_eq!(
+        text.to_html(),
+        "p &lt; 0.05 in <i>E. coli</i> and <b>CO<sub>2</sub></b>"
+    );
+    assert_eq!(
+        text.to_markdown(),
+        "p \\< 0.05 in *E. coli* and **CO<sub>2</sub>**"
+    );
+    assert_eq!(text.to_string(), text.to_plain());
+
+    // emphasis may not start or end with a space in Markdown
+    let spaced = RichText::parse("a<i> b </i>c");
+    assert_eq!(spaced.to_markdown(), "a *b* c");
+}
+
+#[test]
+fn markup_round_trips() {
+    for markup in [
+        "Effect of <i>CYP3A5</i> on H<sub>2</sub>O",
+        "<b><i>nested</i> bold</b>",
+        "a <DispFormula>x<sup>2</sup></DispFormula> b",
+        "stray </i> and <unknown> tags",
+    ] {
+        assert_eq!(RichText::parse(markup).to_markup(), markup);
+    }
+    // an element left open is closed at the end of the text
+    assert_eq!(RichText::parse("<i>open").to_markup(), "<i>open</i>");
+
+    // text that reads as markup is escaped
+    let literal = RichText::parse("a &lt;b> c &amp;lt; d <i>&lt;/i></i>");
+    assert_eq!(
+        literal.nodes(),
+        [
+            Inline::Text("a <b> c &lt; d ".to_string()),
+            Inline::Styled(Style::Italic, vec![Inline::Text("</i>".to_string())]),
+        ]
+    );
+    assert_eq!(literal.to_markup(), "a &lt;b> c &amp;lt; d <i>&lt;/i></i>");
+}
+
+#[test]
+fn escaped_tags_in_the_xml_stay_text() {
+    let xml = std::fs::read_to_string("tests/fixtures/pubmed_articles.xml")
+        .unwrap()
+        .replace(
+            "<ArticleTitle>Effect of <i>CYP3A5</i>",
+            "<ArticleTitle>Cases where a &lt;b&gt; c holds, and <i>CYP3A5</i>",
+        )
+        .replace(
+            "We studied <b>120</b> recipients.",
+            "We studied &lt;i&gt;120&lt;/i&gt; and H&lt;sub&gt;2&lt;/sub&gt; &amp;lt; <b>120</b>.",
+        );
+    let article = PubmedReader::from_reader(xml.as_bytes())
+        .next()
+        .unwrap()
+        .unwrap();
+
+    let title = article.article().title().unwrap();
+    assert_eq!(
+        title.to_plain(),
+        "Cases where a <b> c holds, and CYP3A5 genotype on tacrolimus levels in H2O-restricted patients."
+    );
+    assert_eq!(
+        title.nodes()[0],
+        Inline::Text("Cases where a <b> c holds, and ".to_string())
+    );
+    assert!(title
+        .to_markup()
+        .starts_with("Cases where a &lt;b> c holds, and <i>CYP3A5</i>"));
+
+    let section = &article.article().summary().unwrap().texts()[1];
+    assert_eq!(
+        section.text().to_plain(),
+        "We studied <i>120</i> and H<sub>2</sub> &lt; 120."
+    );
+    assert_eq!(
+        section.text().to_html(),
+        "We studied &lt;i&gt;120&lt;/i&gt; and H&lt;sub&gt;2&lt;/sub&gt; &amp;lt; <b>120</b>."
+    );
+}
+
+#[test]
+fn cdata_is_read_as_text() {
+    let xml = std::fs::read_to_string("tests/fixtures/pubmed_articles.xml")
+        .unwrap()
+        .replace(
+            "<ArticleTitle>Effect of <i>CYP3A5</i>",
+            "<ArticleTitle><![CDATA[Cases where a <b> c & d hold, ]]>and <i>CYP3A5</i>",
+        );
+    let article = PubmedReader::from_reader(xml.as_bytes())
+        .next()
+        .unwrap()
+        .unwrap();
+
+    let title = article.article().title().unwrap();
+    assert!(title
+        .to_plain()
+        .starts_with("Cases where a <b> c & d hold, and CYP3A5 genotype"));
+    assert!(title
+        .to_markup()
+        .starts_with("Cases where a &lt;b> c & d hold, and <i>CYP3A5</i>"));
+}
+
+#[test]
+fn mathml_is_kept_as_a_run() {
+    let articles = articles();
+    let title = articles[1].article().title().unwrap();
+    let markup = title.to_markup();
+    assert!(!markup.contains("&lt/"));
+    assert!(markup.ends_with("<mml:mn>1</mml:mn></mml:math> quickly."));
+
+    let math = title
+        .runs()
+        .into_iter()
+        .find_map(|run| match run.content {
+            RunContent::Math(math) => Some(math),
+            RunContent::Text(_) => None,
+        })
+        .unwrap();
+    assert!(math.mathml().starts_with("<mml:math xmlns:mml="));
+    assert!(math.mathml().ends_with("</mml:math>"));
+    assert_eq!(title.to_plain(), "Solving x^2 < 1 quickly.");
+}
+
+#[test]
+fn rich_text_serializes_as_markup() {
+    let articles = articles();
+    let json = serde_json::to_value(&articles[0]).unwrap();
+    assert_eq!(
+        json["MedlineCitation"]["Article"]["ArticleTitle"]["$value"],
+        "Effect of <i>CYP3A5</i> genotype on tacrolimus levels in H<sub>2</sub>O-restricted patients."
+    );
+}
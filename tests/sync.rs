@@ -0,0 +1,221 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use bson::doc;
+use rbiblio::db::{ArticleStore, Config};
+use rbiblio::sync::{mirror_files, SyncError, Synchronizer};
+use rbiblio::{ErrorHandler, ParseError};
+
+fn mirror(name: &str, files: &[&str]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rbiblio_sync_{}", name));
+    let _ = fs::remove_dir_all(&root);
+    for file in files {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, file.as_bytes()).unwrap();
+    }
+    root
+}
+
+#[test]
+fn baseline_is_applied_before_current_updates() {
+    let root = mirror(
+        "order",
+        &[
+            "updatefiles/pubmed25n1275.xml.gz",
+            "updatefiles/pubmed24n1300.xml.gz",
+            "baseline/pubmed25n0002.xml.gz",
+            "baseline/pubmed25n0001.xml.gz",
+            "baseline/pubmed25n0001.xml.gz.md5",
+            "updatefiles/pubmed25n1274.xml.gz",
+        ],
+    );
+
+    let names: Vec<String> = mirror_files(&root)
+        .unwrap()
+        .into_iter()
+        .map(|f| f.name)
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "pubmed25n0001.xml.gz",
+            "pubmed25n0002.xml.gz",
+            "pubmed25n1274.xml.gz",
+            "pubmed25n1275.xml.gz",
+        ]
+    );
+}
+
+#[test]
+fn checksums_are_read_from_md5_files() {
+    let root = mirror(
+        "md5",
+        &[
+            "baseline/pubmed25n0001.xml.gz",
+            "baseline/pubmed25n0002.xml.gz",
+        ],
+    );
+    // md5 of the file contents, which is its own relative path
+    let expected = format!("{:x}", md5::compute("baseline/pubmed25n0001.xml.gz"));
+    fs::write(
+        root.join("baseline/pubmed25n0001.xml.gz.md5"),
+        format!("MD5(pubmed25n0001.xml.gz)= {}\n", expected),
+    )
+    .unwrap();
+    fs::write(
+        root.join("baseline/pubmed25n0002.xml.gz.md5"),
+        "MD5(pubmed25n0002.xml.gz)= 00000000000000000000000000000000\n",
+    )
+    .unwrap();
+
+    let files = mirror_files(&root).unwrap();
+    assert_eq!(files[0].verify().unwrap(), expected);
+    assert!(matches!(
+        files[1].verify(),
+        Err(SyncError::ChecksumMismatch { .. })
+    ));
+}
+
+/// Writes `xml` gzipped to `root/file` with its `.md5` companion.
+fn write_gz(root: &Path, file: &str, xml: &str) {
+    let path = root.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut gz = GzEncoder::new(Vec::new(), Compression::fast());
+    gz.write_all(xml.as_bytes()).unwrap();
+    let bytes = gz.finish().unwrap();
+    fs::write(&path, &bytes).unwrap();
+    let name = path.file_name().unwrap().to_str().unwrap();
+    fs::write(
+        root.join(format!("{}.md5", file)),
+        format!("MD5({})= {:x}\n", name, md5::compute(&bytes)),
+    )
+    .unwrap();
+}
+
+async fn synchronizer(name: &str) -> Synchronizer {
+    let config = Config {
+        uri: std::env::var("MONGODB_URI").unwrap_or_else(|_| Config::default().uri),
+        database: format!("rbiblio_test_{}", name),
+        collection: String::from("articles"),
+        batch_size: 100,
+    };
+    let store = ArticleStore::connect(&config).await.unwrap();
+    store.database().drop(None).await.unwrap();
+    Synchronizer::new(store, "sync_state")
+}
+
+/// A baseline with both fixture articles, and an update file with an older
+/// version of 10000002, the same version of 27150001 and a deletion of it.
+fn versioned_mirror(name: &str) -> PathBuf {
+    let root = mirror(name, &[]);
+    let baseline = fs::read_to_string("tests/fixtures/pubmed_articles.xml").unwrap();
+    write_gz(&root, "baseline/pubmed25n0001.xml.gz", &baseline);
+    let update = baseline
+        .replace(
+            r#"<PMID Version="2">10000002"#,
+            r#"<PMID Version="1">10000002"#,
+        )
+        .replace(
+            "</PubmedArticleSet>",
+            "<DeleteCitation><PMID Version=\"1\">27150001</PMID></DeleteCitation>\n</PubmedArticleSet>",
+        );
+    write_gz(&root, "updatefiles/pubmed25n1275.xml.gz", &update);
+    root
+}
+
+#[tokio::test]
+#[ignore = "requires a local mongod"]
+async fn updates_keep_newer_versions_and_apply_deletions() {
+    let root = versioned_mirror("apply");
+    let mut synchronizer = synchronizer("sync_apply").await;
+
+    let outcomes = synchronizer.run(&root).await.unwrap();
+    let counts: Vec<(&str, u64, u64, u64)> = outcomes
+        .iter()
+        .map(|o| (o.name.as_str(), o.articles, o.stale, o.deleted))
+        .collect();
+    // the update's articles are still queued when its deletion arrives
+    assert_eq!(
+        counts,
+        [
+            ("pubmed25n0001.xml.gz", 2, 0, 0),
+            ("pubmed25n1275.xml.gz", 1, 1, 1),
+        ]
+    );
+
+    let collection = synchronizer.store().collection();
+    assert_eq!(collection.count_documents(None, None).await.unwrap(), 1);
+    let stored = collection
+        .find_one(doc! { "_id": 10000002_i64 }, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.get_i64("version").unwrap(), 2);
+
+    synchronizer.store().database().drop(None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a local mongod"]
+async fn applied_files_are_recorded_and_skipped() {
+    let root = versioned_mirror("state");
+    let mut synchronizer = synchronizer("sync_state").await;
+    synchronizer.run(&root).await.unwrap();
+
+    let database = synchronizer.store().database().clone();
+    let state = database
+        .collection::<bson::Document>("sync_state")
+        .find_one(doc! { "_id": "pubmed25n1275.xml.gz" }, None)
+        .await
+        .unwrap()
+        .unwrap();
+    let md5 = mirror_files(&root).unwrap()[1].expected_md5().unwrap();
+    assert_eq!(state.get_str("md5").unwrap(), md5);
+    assert_eq!(state.get_i64("articles").unwrap(), 1);
+    assert_eq!(state.get_i64("stale").unwrap(), 1);
+    assert_eq!(state.get_i64("deleted").unwrap(), 1);
+
+    // applied files are matched by their .md5 file and not read again
+    fs::write(root.join("baseline/pubmed25n0001.xml.gz"), b"corrupt").unwrap();
+    assert!(synchronizer.run(&root).await.unwrap().is_empty());
+
+    database.drop(None).await.unwrap();
+}
+
+/// Collects the PMIDs of the record errors of a run.
+#[derive(Clone, Default)]
+struct Collected(Arc<Mutex<Vec<Option<String>>>>);
+
+impl ErrorHandler for Collected {
+    fn write(&mut self, error: &ParseError) -> std::io::Result<()> {
+        let pmid = error.context().pmid.clone();
+        self.0.lock().unwrap().push(pmid);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a local mongod"]
+async fn record_errors_reach_the_error_handler() {
+    let root = mirror("errors", &[]);
+    let xml = fs::read_to_string("tests/fixtures/pubmed_articles.xml")
+        .unwrap()
+        .replacen("Journal>", "Periodical>", 2);
+    write_gz(&root, "baseline/pubmed25n0001.xml.gz", &xml);
+
+    let errors = Collected::default();
+    let mut synchronizer = synchronizer("sync_errors")
+        .await
+        .with_error_handler(errors.clone());
+    let outcomes = synchronizer.run(&root).await.unwrap();
+    assert_eq!(outcomes[0].errors, 1);
+    assert_eq!(outcomes[0].articles, 1);
+    assert_eq!(*errors.0.lock().unwrap(), [Some(String::from("27150001"))]);
+
+    synchronizer.store().database().drop(None).await.unwrap();
+}